@@ -1,10 +1,25 @@
 // Benchmarks de performance pour xsshend
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkId, Criterion, SamplingMode, Throughput,
+};
+use russh_sftp::client::RawSftpSession;
+use russh_sftp::protocol::{FileAttributes, Handle, OpenFlags, Status, StatusCode};
+use sha2::Sha256;
 use std::fs;
+use std::time::Duration;
 use tempfile::TempDir;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio::time::Instant;
 use xsshend::config::{HostEntry, HostsConfig};
 use xsshend::core::validator::Validator;
 use xsshend::ssh::keys::SshKey;
+use xsshend::ssh::pipeline::Pipeline;
+
+/// Latence d'un sens du lien simulé: 20 ms aller-retour, comme entre deux régions
+const LINK_LATENCY: Duration = Duration::from_millis(10);
+/// Taille du fichier envoyé à chaque itération
+const PIPELINE_FILE_SIZE: usize = 2 * 1024 * 1024;
 
 fn benchmark_config_parsing(c: &mut Criterion) {
     // Créer une grande configuration JSON
@@ -165,8 +180,149 @@ fn benchmark_server_alias_parsing(c: &mut Criterion) {
     });
 }
 
+fn benchmark_sftp_pipelined_write(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let session = runtime.block_on(start_sftp_server());
+    let content = vec![0x5au8; PIPELINE_FILE_SIZE];
+
+    let mut group = c.benchmark_group("sftp_write_20ms_rtt");
+    // Itérations longues (plusieurs allers-retours): un échantillon par itération
+    group.sampling_mode(SamplingMode::Flat);
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(PIPELINE_FILE_SIZE as u64));
+    // 1 requête en vol: comportement d'un write_all par bloc de 64 Kio
+    for requests in [1, 4, 16, 64] {
+        let pipeline = Pipeline {
+            requests,
+            ..Default::default()
+        };
+        group.bench_with_input(
+            BenchmarkId::new("requests", requests),
+            &pipeline,
+            |b, pipeline| {
+                b.iter(|| {
+                    runtime.block_on(async {
+                        let handle = session
+                            .open(
+                                "bench.bin",
+                                OpenFlags::CREATE | OpenFlags::WRITE,
+                                FileAttributes::default(),
+                            )
+                            .await
+                            .unwrap()
+                            .handle;
+                        let written = pipeline
+                            .write(
+                                &session,
+                                &handle,
+                                &mut &content[..],
+                                0,
+                                &mut Sha256::default(),
+                                |_| {},
+                            )
+                            .await
+                            .unwrap();
+                        session.close(handle).await.unwrap();
+                        black_box(written)
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
 // Helpers pour créer des données de test
 
+/// Serveur SFTP en mémoire qui accepte les écritures sans les conserver
+struct SinkServer;
+
+impl russh_sftp::server::Handler for SinkServer {
+    type Error = StatusCode;
+
+    fn unimplemented(&self) -> Self::Error {
+        StatusCode::OpUnsupported
+    }
+
+    async fn open(
+        &mut self,
+        id: u32,
+        filename: String,
+        _pflags: OpenFlags,
+        _attrs: FileAttributes,
+    ) -> Result<Handle, Self::Error> {
+        Ok(Handle {
+            id,
+            handle: filename,
+        })
+    }
+
+    async fn write(
+        &mut self,
+        id: u32,
+        _handle: String,
+        _offset: u64,
+        _data: Vec<u8>,
+    ) -> Result<Status, Self::Error> {
+        Ok(ok_status(id))
+    }
+
+    async fn close(&mut self, id: u32, _handle: String) -> Result<Status, Self::Error> {
+        Ok(ok_status(id))
+    }
+}
+
+fn ok_status(id: u32) -> Status {
+    Status {
+        id,
+        status_code: StatusCode::Ok,
+        error_message: "Ok".to_string(),
+        language_tag: "en-US".to_string(),
+    }
+}
+
+/// Session SFTP cliente reliée à un `SinkServer` par un lien à latence simulée
+async fn start_sftp_server() -> RawSftpSession {
+    let (client, client_link) = tokio::io::duplex(1024 * 1024);
+    let (server_link, server) = tokio::io::duplex(1024 * 1024);
+    let (client_read, client_write) = tokio::io::split(client_link);
+    let (server_read, server_write) = tokio::io::split(server_link);
+    tokio::spawn(delay_line(client_read, server_write));
+    tokio::spawn(delay_line(server_read, client_write));
+
+    russh_sftp::server::run(server, SinkServer).await;
+    let session = RawSftpSession::new(client);
+    session.init().await.unwrap();
+    session
+}
+
+/// Retransmet les octets lus après `LINK_LATENCY`, sans limite de débit
+async fn delay_line<R, W>(mut reader: R, mut writer: W)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin,
+{
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut buffer = vec![0u8; 256 * 1024];
+        while let Ok(n) = reader.read(&mut buffer).await {
+            if n == 0
+                || tx
+                    .send((Instant::now() + LINK_LATENCY, buffer[..n].to_vec()))
+                    .is_err()
+            {
+                break;
+            }
+        }
+    });
+    while let Some((deadline, bytes)) = rx.recv().await {
+        tokio::time::sleep_until(deadline).await;
+        if writer.write_all(&bytes).await.is_err() {
+            break;
+        }
+    }
+}
+
 fn create_large_config_json(server_count: usize) -> String {
     let mut json = String::from("{\n");
 
@@ -229,6 +385,7 @@ fn create_large_config(server_count: usize) -> HostsConfig {
                         HostEntry {
                             alias: format!("user{}@server{}.com", server_i, server_i),
                             env: format!("ENV{}", env_i),
+                            ..Default::default()
                         },
                     );
                 }
@@ -240,7 +397,10 @@ fn create_large_config(server_count: usize) -> HostsConfig {
         environments.insert(format!("Env_{}", env_i), regions);
     }
 
-    HostsConfig { environments }
+    HostsConfig {
+        environments,
+        ..Default::default()
+    }
 }
 
 criterion_group!(
@@ -251,7 +411,8 @@ criterion_group!(
     benchmark_file_size_formatting,
     benchmark_ssh_key_detection,
     benchmark_ssh_key_selection,
-    benchmark_server_alias_parsing
+    benchmark_server_alias_parsing,
+    benchmark_sftp_pipelined_write
 );
 
 criterion_main!(benches);
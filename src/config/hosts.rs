@@ -7,10 +7,83 @@ use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::ssh::known_hosts::HostKeyPolicy;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HostEntry {
     pub alias: String,
     pub env: String,
+    #[serde(flatten)]
+    pub options: HostOptions,
+}
+
+/// Options de connexion facultatives d'un hôte.
+///
+/// Définissables directement sur l'hôte ou héritées d'une portée de `_defaults`
+/// ("Production", "Production:Region-A", "Production:Region-A:Public").
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HostOptions {
+    /// Port SSH (si l'alias n'en précise pas)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Vérification de la clé d'hôte: strict, accept-new (défaut) ou off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_key_policy: Option<HostKeyPolicy>,
+    /// Clé privée à utiliser en priorité pour cet hôte
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<PathBuf>,
+    /// N'essayer que `identity_file` (équivalent IdentitiesOnly d'OpenSSH)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identities_only: Option<bool>,
+    /// Hôtes de rebond, séparés par des virgules ("ops@bastion1,ops@bastion2:2222").
+    /// "none" force une connexion directe.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jump: Option<String>,
+    /// Fichiers téléversés: reporter mode et date du fichier local
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preserve: Option<bool>,
+    /// Fichiers téléversés: mode octal imposé ("0644")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chmod: Option<String>,
+    /// Fichiers téléversés: propriétaire imposé
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chown: Option<String>,
+    /// Fichiers téléversés: groupe imposé
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chgrp: Option<String>,
+}
+
+impl HostOptions {
+    /// Complète les options absentes avec celles d'une portée plus générale
+    pub fn inherit(&mut self, parent: &HostOptions) {
+        if self.port.is_none() {
+            self.port = parent.port;
+        }
+        if self.host_key_policy.is_none() {
+            self.host_key_policy = parent.host_key_policy;
+        }
+        if self.identity_file.is_none() {
+            self.identity_file = parent.identity_file.clone();
+        }
+        if self.identities_only.is_none() {
+            self.identities_only = parent.identities_only;
+        }
+        if self.jump.is_none() {
+            self.jump = parent.jump.clone();
+        }
+        if self.preserve.is_none() {
+            self.preserve = parent.preserve;
+        }
+        if self.chmod.is_none() {
+            self.chmod = parent.chmod.clone();
+        }
+        if self.chown.is_none() {
+            self.chown = parent.chown.clone();
+        }
+        if self.chgrp.is_none() {
+            self.chgrp = parent.chgrp.clone();
+        }
+    }
 }
 
 pub type HostGroup = HashMap<String, HostEntry>;
@@ -18,8 +91,15 @@ pub type ServerType = HashMap<String, HostGroup>;
 pub type Region = HashMap<String, ServerType>;
 pub type Environment = HashMap<String, Region>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct HostsConfig {
+    /// Options par défaut, indexées par portée "env[:région[:type]]"
+    #[serde(
+        rename = "_defaults",
+        default,
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub defaults: HashMap<String, HostOptions>,
     #[serde(flatten)]
     pub environments: Environment,
 }
@@ -57,12 +137,43 @@ impl HostsConfig {
         let content = fs::read_to_string(&config_path)
             .with_context(|| format!("Impossible de lire {}", config_path.display()))?;
 
-        let config: HostsConfig = serde_json::from_str(&content)
+        let mut config: HostsConfig = serde_json::from_str(&content)
             .with_context(|| format!("Erreur de parsing JSON dans {}", config_path.display()))?;
+        config.apply_defaults();
 
         Ok(config)
     }
 
+    /// Propage les options de `_defaults` vers chaque hôte.
+    ///
+    /// La portée la plus spécifique l'emporte; une option définie sur l'hôte
+    /// n'est jamais écrasée.
+    pub fn apply_defaults(&mut self) {
+        if self.defaults.is_empty() {
+            return;
+        }
+
+        for (env_name, regions) in self.environments.iter_mut() {
+            for (region_name, server_types) in regions.iter_mut() {
+                for (type_name, hosts) in server_types.iter_mut() {
+                    let scopes = [
+                        format!("{}:{}:{}", env_name, region_name, type_name),
+                        format!("{}:{}", env_name, region_name),
+                        env_name.clone(),
+                    ];
+
+                    for host_entry in hosts.values_mut() {
+                        for scope in &scopes {
+                            if let Some(defaults) = self.defaults.get(scope) {
+                                host_entry.options.inherit(defaults);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     /// Retourne le chemin vers le fichier de configuration
     pub fn get_config_path() -> Result<PathBuf> {
         let home = home_dir().context("Impossible de déterminer le répertoire home")?;
@@ -320,6 +431,95 @@ mod tests {
         assert_eq!(config.filter_hosts(None, None, None).len(), 1);
     }
 
+    #[test]
+    fn test_defaults_inheritance() {
+        let json_content = r#"
+        {
+            "_defaults": {
+                "Production": { "host_key_policy": "strict" },
+                "Production:Region-A:Private": { "host_key_policy": "off" }
+            },
+            "Production": {
+                "Region-A": {
+                    "Public": {
+                        "WEB_01": { "alias": "web01@prod.example.com", "env": "PROD" },
+                        "WEB_02": {
+                            "alias": "web02@prod.example.com",
+                            "env": "PROD",
+                            "host_key_policy": "accept-new"
+                        }
+                    },
+                    "Private": {
+                        "DB_01": { "alias": "db01@prod.example.com", "env": "PROD" }
+                    }
+                }
+            }
+        }
+        "#;
+
+        let mut config: HostsConfig = serde_json::from_str(json_content).unwrap();
+        config.apply_defaults();
+
+        let policy = |name: &str| {
+            config
+                .filter_hosts(None, None, None)
+                .into_iter()
+                .find(|(full_name, _)| full_name.ends_with(name))
+                .and_then(|(_, entry)| entry.options.host_key_policy)
+        };
+        assert_eq!(policy("WEB_01"), Some(HostKeyPolicy::Strict));
+        assert_eq!(policy("WEB_02"), Some(HostKeyPolicy::AcceptNew));
+        assert_eq!(policy("DB_01"), Some(HostKeyPolicy::Off));
+    }
+
+    #[test]
+    fn test_upload_attribute_defaults_per_group() {
+        let json_content = r#"
+        {
+            "_defaults": {
+                "Production": { "preserve": true },
+                "Production:Region-A:Public": { "chown": "www-data", "chmod": "0644" }
+            },
+            "Production": {
+                "Region-A": {
+                    "Public": {
+                        "WEB_01": { "alias": "web01@prod.example.com", "env": "PROD" },
+                        "WEB_02": {
+                            "alias": "web02@prod.example.com",
+                            "env": "PROD",
+                            "chmod": "0600"
+                        }
+                    },
+                    "Private": {
+                        "DB_01": { "alias": "db01@prod.example.com", "env": "PROD" }
+                    }
+                }
+            }
+        }
+        "#;
+
+        let mut config: HostsConfig = serde_json::from_str(json_content).unwrap();
+        config.apply_defaults();
+
+        let options = |name: &str| {
+            config
+                .filter_hosts(None, None, None)
+                .into_iter()
+                .find(|(full_name, _)| full_name.ends_with(name))
+                .map(|(_, entry)| entry.options.clone())
+                .unwrap()
+        };
+        let web01 = options("WEB_01");
+        assert_eq!(web01.preserve, Some(true));
+        assert_eq!(web01.chmod.as_deref(), Some("0644"));
+        assert_eq!(web01.chown.as_deref(), Some("www-data"));
+        assert_eq!(options("WEB_02").chmod.as_deref(), Some("0600"));
+
+        let db01 = options("DB_01");
+        assert_eq!(db01.preserve, Some(true));
+        assert_eq!((db01.chmod, db01.chown), (None, None));
+    }
+
     #[test]
     fn test_host_filtering() {
         let json_content = r#"
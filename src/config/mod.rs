@@ -1,3 +1,5 @@
 pub mod hosts;
+pub mod ssh_config;
 
-pub use hosts::{HostEntry, HostsConfig};
+pub use hosts::{HostEntry, HostOptions, HostsConfig};
+pub use ssh_config::SshConfig;
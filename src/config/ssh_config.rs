@@ -0,0 +1,600 @@
+// Lecture de ~/.ssh/config (format ssh_config d'OpenSSH)
+//
+// Sous-ensemble supporté :
+// - blocs `Host` (motifs `*`, `?`, `!`) et `Match host|originalhost|user|localuser|all`
+// - `Include` (chemins relatifs à ~/.ssh, `*`/`?` dans le nom de fichier)
+// - sémantique « première valeur trouvée gagne » pour chaque option
+// - options : HostName, User, Port, IdentityFile, IdentitiesOnly, ProxyJump,
+//   ConnectTimeout, StrictHostKeyChecking
+use anyhow::{Context, Result};
+use dirs::home_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::hosts::HostOptions;
+use crate::ssh::known_hosts::HostKeyPolicy;
+use crate::utils::pattern::{match_pattern_list, wildcard_match};
+
+/// Profondeur maximale d'imbrication des `Include` (identique à OpenSSH)
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Critère de sélection d'un bloc `Host` ou `Match`
+#[derive(Debug, Clone, PartialEq)]
+enum Criterion {
+    /// Motifs `Host` ou `Match originalhost`, testés sur le nom demandé
+    OriginalHost(Vec<String>),
+    /// `Match host`, testé sur le HostName résolu jusque-là
+    Host(Vec<String>),
+    /// `Match user`, testé sur l'utilisateur distant
+    User(Vec<String>),
+    /// `Match localuser`
+    LocalUser(Vec<String>),
+    /// `Match all` ou options globales (avant tout bloc)
+    All,
+    /// Critère non supporté (`exec`, `canonical`...) : le bloc ne s'applique jamais
+    Unsupported(String),
+}
+
+/// Bloc de configuration : critères (tous requis) et options dans l'ordre du fichier
+#[derive(Debug, Clone)]
+struct Section {
+    criteria: Vec<(Criterion, bool)>,
+    options: Vec<(String, Vec<String>)>,
+}
+
+/// Paramètres effectifs d'un hôte d'après ~/.ssh/config
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SshConfigHost {
+    pub hostname: Option<String>,
+    pub user: Option<String>,
+    pub connect_timeout: Option<Duration>,
+    /// Port, IdentityFile, IdentitiesOnly, ProxyJump et StrictHostKeyChecking
+    pub options: HostOptions,
+}
+
+/// Fichier ssh_config analysé
+#[derive(Debug, Clone, Default)]
+pub struct SshConfig {
+    sections: Vec<Section>,
+}
+
+impl SshConfig {
+    /// Chemin par défaut: ~/.ssh/config
+    pub fn default_path() -> Result<PathBuf> {
+        let home = home_dir().context("Impossible de déterminer le répertoire home")?;
+        Ok(home.join(".ssh").join("config"))
+    }
+
+    /// Charge ~/.ssh/config; un fichier absent ou illisible donne une configuration vide
+    pub fn load_default() -> Self {
+        let path = match Self::default_path() {
+            Ok(path) => path,
+            Err(_) => return Self::default(),
+        };
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match Self::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("⚠️  {} ignoré: {:#}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Charge un fichier ssh_config (les `Include` relatifs partent de son répertoire)
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut parser = Parser::new(path.parent().unwrap_or(Path::new(".")));
+        parser.parse_file(path, &[], 0)?;
+        Ok(SshConfig {
+            sections: parser.sections,
+        })
+    }
+
+    /// Résout les options applicables à `host` (nom tel qu'écrit dans l'alias).
+    /// `user` est l'utilisateur de l'alias, s'il est connu.
+    pub fn resolve(&self, host: &str, user: Option<&str>) -> SshConfigHost {
+        let mut resolved = SshConfigHost::default();
+        let mut identity_file: Option<String> = None;
+        let local_user = local_username();
+
+        for section in &self.sections {
+            let current_host = resolved.hostname.as_deref().unwrap_or(host);
+            let current_user = user.or(resolved.user.as_deref());
+
+            let applies = section.criteria.iter().all(|(criterion, negated)| {
+                let matched = match criterion {
+                    Criterion::OriginalHost(patterns) => {
+                        match_pattern_list(patterns.iter().map(String::as_str), host)
+                    }
+                    Criterion::Host(patterns) => {
+                        match_pattern_list(patterns.iter().map(String::as_str), current_host)
+                    }
+                    Criterion::User(patterns) => current_user.is_some_and(|u| {
+                        match_pattern_list(patterns.iter().map(String::as_str), u)
+                    }),
+                    Criterion::LocalUser(patterns) => {
+                        match_pattern_list(patterns.iter().map(String::as_str), &local_user)
+                    }
+                    Criterion::All => true,
+                    Criterion::Unsupported(_) => return false,
+                };
+                matched != *negated
+            });
+            if !applies {
+                continue;
+            }
+
+            for (keyword, args) in &section.options {
+                let Some(value) = args.first() else {
+                    continue;
+                };
+                match keyword.as_str() {
+                    "hostname" if resolved.hostname.is_none() => {
+                        resolved.hostname = Some(value.replace("%h", host));
+                    }
+                    "user" if resolved.user.is_none() => {
+                        resolved.user = Some(value.clone());
+                    }
+                    "port" if resolved.options.port.is_none() => {
+                        resolved.options.port = value.parse().ok().filter(|p| *p != 0);
+                    }
+                    "identityfile" if identity_file.is_none() => {
+                        identity_file = Some(value.clone());
+                    }
+                    "identitiesonly" if resolved.options.identities_only.is_none() => {
+                        resolved.options.identities_only = parse_yes_no(value);
+                    }
+                    "proxyjump" if resolved.options.jump.is_none() => {
+                        // "none" est conservé: il bloque les rebonds de portées moins prioritaires
+                        resolved.options.jump = Some(value.clone());
+                    }
+                    "connecttimeout" if resolved.connect_timeout.is_none() => {
+                        resolved.connect_timeout = value.parse().ok().map(Duration::from_secs);
+                    }
+                    "stricthostkeychecking" if resolved.options.host_key_policy.is_none() => {
+                        resolved.options.host_key_policy = parse_host_key_policy(value);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let remote_user = user.or(resolved.user.as_deref()).unwrap_or(&local_user);
+        let real_host = resolved.hostname.as_deref().unwrap_or(host);
+        resolved.options.identity_file = identity_file
+            .filter(|f| !f.eq_ignore_ascii_case("none"))
+            .map(|f| PathBuf::from(expand_tokens(&f, host, real_host, remote_user)));
+
+        resolved
+    }
+}
+
+/// État de l'analyse (fichiers inclus compris)
+struct Parser {
+    base_dir: PathBuf,
+    sections: Vec<Section>,
+}
+
+impl Parser {
+    fn new(base_dir: &Path) -> Self {
+        Parser {
+            base_dir: base_dir.to_path_buf(),
+            // Options globales, avant le premier bloc Host/Match
+            sections: vec![Section {
+                criteria: vec![(Criterion::All, false)],
+                options: Vec::new(),
+            }],
+        }
+    }
+
+    fn parse_file(&mut self, path: &Path, outer: &[(Criterion, bool)], depth: usize) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Impossible de lire {}", path.display()))?;
+        self.parse_content(&content, path, outer, depth)
+    }
+
+    /// `outer` : critères du bloc contenant l'`Include` (inclusion conditionnelle)
+    fn parse_content(
+        &mut self,
+        content: &str,
+        path: &Path,
+        outer: &[(Criterion, bool)],
+        depth: usize,
+    ) -> Result<()> {
+        for (index, line) in content.lines().enumerate() {
+            let line_no = index + 1;
+            let Some((keyword, args)) = split_line(line) else {
+                continue;
+            };
+
+            match keyword.as_str() {
+                "host" => {
+                    let mut criteria = outer.to_vec();
+                    criteria.push((Criterion::OriginalHost(args), false));
+                    self.start_section(criteria);
+                }
+                "match" => {
+                    let mut criteria = outer.to_vec();
+                    criteria.extend(
+                        parse_match(&args)
+                            .with_context(|| format!("{}:{}", path.display(), line_no))?,
+                    );
+                    self.start_section(criteria);
+                }
+                "include" => {
+                    if depth >= MAX_INCLUDE_DEPTH {
+                        anyhow::bail!(
+                            "{}:{}: trop d'Include imbriqués (max {})",
+                            path.display(),
+                            line_no,
+                            MAX_INCLUDE_DEPTH
+                        );
+                    }
+                    let enclosing = self.current_criteria();
+                    for pattern in &args {
+                        for included in self.expand_include(pattern) {
+                            self.parse_file(&included, &enclosing, depth + 1)?;
+                        }
+                    }
+                    // Les lignes suivantes appartiennent toujours au bloc englobant
+                    self.start_section(enclosing);
+                }
+                _ => {
+                    if let Some(section) = self.sections.last_mut() {
+                        section.options.push((keyword, args));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn start_section(&mut self, criteria: Vec<(Criterion, bool)>) {
+        self.sections.push(Section {
+            criteria,
+            options: Vec::new(),
+        });
+    }
+
+    fn current_criteria(&self) -> Vec<(Criterion, bool)> {
+        self.sections
+            .last()
+            .map(|s| s.criteria.clone())
+            .unwrap_or_default()
+    }
+
+    /// Résout un motif d'`Include` en fichiers existants, triés par nom
+    fn expand_include(&self, pattern: &str) -> Vec<PathBuf> {
+        let path = if let Some(rest) = pattern.strip_prefix("~/") {
+            home_dir().map(|h| h.join(rest)).unwrap_or_default()
+        } else if Path::new(pattern).is_absolute() {
+            PathBuf::from(pattern)
+        } else {
+            self.base_dir.join(pattern)
+        };
+
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !file_name.contains(['*', '?']) {
+            return if path.is_file() {
+                vec![path]
+            } else {
+                Vec::new()
+            };
+        }
+
+        let Some(dir) = path.parent() else {
+            return Vec::new();
+        };
+        let mut matches: Vec<PathBuf> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.is_file())
+                    .filter(|p| {
+                        p.file_name()
+                            .is_some_and(|n| wildcard_match(&file_name, &n.to_string_lossy()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        matches.sort();
+        matches
+    }
+}
+
+/// Découpe une ligne en (mot-clé en minuscules, arguments); `None` si vide ou commentaire
+fn split_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    // Le mot-clé peut être séparé par des espaces et/ou un seul '='
+    let end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let keyword = line[..end].to_lowercase();
+    let rest = line[end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim_start();
+
+    Some((keyword, tokenize(rest)))
+}
+
+/// Découpe les arguments en respectant les guillemets doubles
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    tokens.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Analyse les critères d'une ligne `Match`
+fn parse_match(args: &[String]) -> Result<Vec<(Criterion, bool)>> {
+    let mut criteria = Vec::new();
+    let mut iter = args.iter();
+
+    while let Some(arg) = iter.next() {
+        let (name, negated) = match arg.strip_prefix('!') {
+            Some(name) => (name.to_lowercase(), true),
+            None => (arg.to_lowercase(), false),
+        };
+
+        let criterion = match name.as_str() {
+            "all" => Criterion::All,
+            "host" | "originalhost" | "user" | "localuser" => {
+                let value = iter
+                    .next()
+                    .with_context(|| format!("Match {}: motif manquant", name))?;
+                let patterns = value.split(',').map(str::to_string).collect();
+                match name.as_str() {
+                    "host" => Criterion::Host(patterns),
+                    "originalhost" => Criterion::OriginalHost(patterns),
+                    "user" => Criterion::User(patterns),
+                    _ => Criterion::LocalUser(patterns),
+                }
+            }
+            "canonical" | "final" => Criterion::Unsupported(name),
+            _ => {
+                // exec, tagged, address... : argument ignoré, bloc jamais appliqué
+                iter.next();
+                Criterion::Unsupported(name)
+            }
+        };
+
+        if let Criterion::Unsupported(ref name) = criterion {
+            log::debug!("ssh_config: critère Match '{}' non supporté", name);
+        }
+        criteria.push((criterion, negated));
+    }
+
+    if criteria.is_empty() {
+        anyhow::bail!("Match sans critère");
+    }
+    Ok(criteria)
+}
+
+fn parse_yes_no(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "yes" | "true" => Some(true),
+        "no" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// StrictHostKeyChecking → politique xsshend ("ask" garde le défaut)
+fn parse_host_key_policy(value: &str) -> Option<HostKeyPolicy> {
+    match value.to_lowercase().as_str() {
+        "yes" => Some(HostKeyPolicy::Strict),
+        "accept-new" => Some(HostKeyPolicy::AcceptNew),
+        "no" | "off" => Some(HostKeyPolicy::Off),
+        _ => None,
+    }
+}
+
+/// Remplace les jetons %h, %n, %r, %u, %d et %% d'un chemin IdentityFile
+fn expand_tokens(value: &str, original_host: &str, host: &str, remote_user: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => out.push_str(host),
+            Some('n') => out.push_str(original_host),
+            Some('r') => out.push_str(remote_user),
+            Some('u') => out.push_str(&local_username()),
+            Some('d') => out.push_str(
+                &home_dir()
+                    .map(|h| h.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+fn local_username() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> SshConfig {
+        let mut parser = Parser::new(Path::new("/nonexistent"));
+        parser
+            .parse_content(content, Path::new("config"), &[], 0)
+            .unwrap();
+        SshConfig {
+            sections: parser.sections,
+        }
+    }
+
+    #[test]
+    fn test_first_match_wins() {
+        let config = parse(
+            r#"
+Host web01
+    HostName 10.0.0.11
+    Port 2222
+
+Host web*
+    HostName ignored.example.com
+    User deploy
+    Port 22
+    IdentityFile ~/.ssh/web_key
+
+Host *
+    User nobody
+    ConnectTimeout 5
+"#,
+        );
+
+        let resolved = config.resolve("web01", None);
+        assert_eq!(resolved.hostname.as_deref(), Some("10.0.0.11"));
+        assert_eq!(resolved.user.as_deref(), Some("deploy"));
+        assert_eq!(resolved.options.port, Some(2222));
+        assert_eq!(
+            resolved.options.identity_file,
+            Some(PathBuf::from("~/.ssh/web_key"))
+        );
+        assert_eq!(resolved.connect_timeout, Some(Duration::from_secs(5)));
+
+        let other = config.resolve("db01", None);
+        assert_eq!(other.hostname, None);
+        assert_eq!(other.user.as_deref(), Some("nobody"));
+    }
+
+    #[test]
+    fn test_host_negation_and_equals_syntax() {
+        let config = parse(
+            r#"
+Host *.prod !bastion.prod
+    ProxyJump=bastion.prod
+    StrictHostKeyChecking yes
+    IdentitiesOnly yes
+"#,
+        );
+
+        let web = config.resolve("web.prod", None);
+        assert_eq!(web.options.jump.as_deref(), Some("bastion.prod"));
+        assert_eq!(web.options.host_key_policy, Some(HostKeyPolicy::Strict));
+        assert_eq!(web.options.identities_only, Some(true));
+
+        assert_eq!(
+            config.resolve("bastion.prod", None),
+            SshConfigHost::default()
+        );
+    }
+
+    #[test]
+    fn test_match_host_uses_resolved_hostname() {
+        let config = parse(
+            r#"
+Host app
+    HostName app.internal.example.com
+
+Match host *.internal.example.com
+    ProxyJump jump.example.com
+
+Match user root
+    Port 2200
+
+Match exec "true"
+    User never
+"#,
+        );
+
+        let resolved = config.resolve("app", Some("root"));
+        assert_eq!(resolved.options.jump.as_deref(), Some("jump.example.com"));
+        assert_eq!(resolved.options.port, Some(2200));
+        assert_eq!(resolved.user, None);
+
+        assert_eq!(config.resolve("other", Some("deploy")).options.jump, None);
+    }
+
+    #[test]
+    fn test_identity_file_tokens() {
+        let config = parse(
+            r#"
+Host *
+    IdentityFile "/keys/%r@%h.key"
+"#,
+        );
+        let resolved = config.resolve("web01", Some("deploy"));
+        assert_eq!(
+            resolved.options.identity_file,
+            Some(PathBuf::from("/keys/deploy@web01.key"))
+        );
+    }
+
+    #[test]
+    fn test_include_with_glob_and_conditional_block() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("config.d")).unwrap();
+        fs::write(
+            dir.path().join("config.d/10-web"),
+            "Host web*\n    User www\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("config.d/20-db"), "Host db*\n    User pg\n").unwrap();
+        fs::write(dir.path().join("staging.conf"), "Port 2022\n").unwrap();
+        fs::write(
+            dir.path().join("config"),
+            "Include config.d/*\n\nHost *.staging\n    Include staging.conf\n    User stage\n",
+        )
+        .unwrap();
+
+        let config = SshConfig::load(&dir.path().join("config")).unwrap();
+        assert_eq!(config.resolve("web01", None).user.as_deref(), Some("www"));
+        assert_eq!(config.resolve("db01", None).user.as_deref(), Some("pg"));
+
+        let staging = config.resolve("api.staging", None);
+        assert_eq!(staging.options.port, Some(2022));
+        assert_eq!(staging.user.as_deref(), Some("stage"));
+        assert_eq!(config.resolve("api.prod", None).options.port, None);
+    }
+}
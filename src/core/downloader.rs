@@ -0,0 +1,549 @@
+// Téléchargement de fichiers depuis plusieurs serveurs (xsshend download)
+//
+// Le chemin distant (jokers shell acceptés) est développé sur chaque serveur,
+// puis chaque fichier est copié en local via les sessions SFTP poolées, sous le
+// chemin construit par le modèle --template (par défaut `{host}/{path}`).
+// Comme pour l'upload, --jobs borne les transferts simultanés au total et
+// --jobs-per-host ceux d'un même serveur.
+
+use crate::config::{HostEntry, HostOptions};
+use crate::core::output::short_name;
+use crate::core::validator::Validator;
+use crate::ssh::client::{DownloadedFile, RemoteFile};
+use crate::ssh::keys::PassphraseCache;
+use crate::ssh::password::PasswordCache;
+use crate::ssh::pool::{Concurrency, ConnectionPool};
+use crate::ssh::retry::RetryPolicy;
+use crate::ssh::target::SshTarget;
+use crate::utils::interrupt;
+use crate::utils::path_expansion;
+use crate::utils::progress::{TransferDisplay, TransferProgress};
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Modèle par défaut: un répertoire par serveur, arborescence distante conservée
+pub const DEFAULT_TEMPLATE: &str = "{host}/{path}";
+
+/// Variables utilisables dans un modèle de chemin local
+const PLACEHOLDERS: [&str; 6] = ["host", "env", "region", "type", "path", "name"];
+
+/// Modèle du chemin local d'un fichier téléchargé, relatif à --into.
+///
+/// `{host}` nom court du serveur, `{env}`, `{region}`, `{type}`, `{path}` chemin
+/// distant complet (sans `/` initial) et `{name}` nom du fichier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathTemplate(String);
+
+impl Default for PathTemplate {
+    fn default() -> Self {
+        Self(DEFAULT_TEMPLATE.to_string())
+    }
+}
+
+impl fmt::Display for PathTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for PathTemplate {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let template = Self(value.to_string());
+        template.expand(|name| PLACEHOLDERS.contains(&name).then_some(""))?;
+        if !value.contains("{path}") && !value.contains("{name}") {
+            return Err(format!(
+                "modèle sans {{path}} ni {{name}}: tous les fichiers auraient le même nom ({})",
+                value
+            ));
+        }
+        Ok(template)
+    }
+}
+
+impl PathTemplate {
+    /// Le modèle sépare-t-il les fichiers des différents serveurs ?
+    pub fn is_per_host(&self) -> bool {
+        self.0.contains("{host}")
+    }
+
+    /// Chemin local (relatif à --into) du fichier `remote_path` de `host_name`
+    pub fn render(&self, host_name: &str, remote_path: &str) -> Result<PathBuf> {
+        let segments: Vec<&str> = host_name.split(':').collect();
+        let (env, region, server_type) = match segments.as_slice() {
+            [env, region, server_type, _] => (*env, *region, *server_type),
+            _ => ("", "", ""),
+        };
+        let name = remote_path.rsplit('/').next().unwrap_or(remote_path);
+        let rendered = self
+            .expand(|placeholder| match placeholder {
+                "host" => Some(short_name(host_name)),
+                "env" => Some(env),
+                "region" => Some(region),
+                "type" => Some(server_type),
+                "path" => Some(remote_path.trim_start_matches('/')),
+                "name" => Some(name),
+                _ => None,
+            })
+            .map_err(|e| anyhow::anyhow!(e))?;
+
+        // Rester sous --into, quels que soient les noms renvoyés par le serveur
+        let mut path = PathBuf::new();
+        for component in rendered.split('/') {
+            match component {
+                "" | "." => {}
+                ".." => anyhow::bail!("Chemin local hors de --into: {}", rendered),
+                component => path.push(component),
+            }
+        }
+        if path.as_os_str().is_empty() {
+            anyhow::bail!("Chemin local vide pour {}", remote_path);
+        }
+        Ok(path)
+    }
+
+    /// Remplacer chaque `{variable}` en un seul passage
+    fn expand<'a>(&self, value_of: impl Fn(&str) -> Option<&'a str>) -> Result<String, String> {
+        let mut expanded = String::with_capacity(self.0.len());
+        let mut rest = self.0.as_str();
+        while let Some(start) = rest.find('{') {
+            expanded.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}') else {
+                return Err(format!("accolade non fermée dans le modèle {}", self.0));
+            };
+            let placeholder = &rest[start + 1..start + end];
+            let value = value_of(placeholder).ok_or_else(|| {
+                format!(
+                    "variable inconnue {{{}}} (disponibles: {})",
+                    placeholder,
+                    PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+                )
+            })?;
+            expanded.push_str(value);
+            rest = &rest[start + end + 1..];
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+}
+
+/// Résultat d'un fichier (ou d'un serveur injoignable) lors d'un téléchargement
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadRecord {
+    pub host: String,
+    /// Chemin distant, ou motif demandé si la liste des fichiers a échoué
+    pub remote: String,
+    /// Chemin local écrit
+    pub local: Option<String>,
+    pub success: bool,
+    pub bytes: u64,
+    /// SHA-256 du contenu reçu
+    pub sha256: Option<String>,
+    pub error: Option<String>,
+}
+
+impl DownloadRecord {
+    fn succeeded(host: &str, remote: &str, local: &Path, downloaded: DownloadedFile) -> Self {
+        Self {
+            host: host.to_string(),
+            remote: remote.to_string(),
+            local: Some(local.display().to_string()),
+            success: true,
+            bytes: downloaded.bytes,
+            sha256: Some(downloaded.sha256),
+            error: None,
+        }
+    }
+
+    fn failed(host: &str, remote: &str, local: Option<&Path>, error: &anyhow::Error) -> Self {
+        Self {
+            host: host.to_string(),
+            remote: remote.to_string(),
+            local: local.map(|local| local.display().to_string()),
+            success: false,
+            bytes: 0,
+            sha256: None,
+            error: Some(format!("{:#}", error)),
+        }
+    }
+}
+
+/// Décompte des couples serveur × fichier d'un téléchargement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DownloadSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub bytes: u64,
+}
+
+impl DownloadSummary {
+    pub fn of(records: &[DownloadRecord]) -> Self {
+        let mut summary = Self {
+            total: records.len(),
+            ..Default::default()
+        };
+        for record in records {
+            if record.success {
+                summary.succeeded += 1;
+            } else {
+                summary.failed += 1;
+            }
+            summary.bytes += record.bytes;
+        }
+        summary
+    }
+}
+
+pub struct Downloader {
+    pool: ConnectionPool,
+    /// Copier le contenu des répertoires correspondants (--recursive)
+    recursive: bool,
+    /// Chemin local de chaque fichier, relatif à --into
+    template: PathTemplate,
+    /// Nouvelles tentatives sur erreur réseau passagère (--retries)
+    retry: RetryPolicy,
+    /// Transferts simultanés, au total et par serveur (--jobs, --jobs-per-host)
+    concurrency: Concurrency,
+    /// Pas de messages sur stdout (sortie JSON)
+    quiet: bool,
+}
+
+impl Downloader {
+    /// Créer avec des options de connexion imposées par la CLI (--key, ...)
+    pub fn with_overrides(overrides: HostOptions) -> Self {
+        Downloader {
+            pool: ConnectionPool::new(PassphraseCache::new()).with_overrides(overrides),
+            recursive: false,
+            template: PathTemplate::default(),
+            retry: RetryPolicy::default(),
+            concurrency: Concurrency::default(),
+            quiet: false,
+        }
+    }
+
+    /// Partager le cache de mots de passe de l'exécution (--password-stdin, ...)
+    pub fn with_password_cache(mut self, password_cache: PasswordCache) -> Self {
+        self.pool = self.pool.with_password_cache(password_cache);
+        self
+    }
+
+    /// Copier aussi le contenu des répertoires (--recursive)
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Modèle du chemin local des fichiers (--template)
+    pub fn with_template(mut self, template: PathTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Nouvelles tentatives par fichier sur erreur réseau passagère
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Transferts simultanés au total et par serveur
+    pub fn with_concurrency(mut self, concurrency: Concurrency) -> Self {
+        self.concurrency = concurrency.at_least_one();
+        self.pool = self.pool.with_concurrency(self.concurrency);
+        self
+    }
+
+    /// Sortie JSON: n'afficher ni récapitulatif ni messages de progression sur stdout
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Télécharge `pattern` depuis chaque serveur vers `into` (connexions poolées).
+    /// Renvoie le résultat de chaque couple serveur × fichier.
+    pub async fn download(
+        &self,
+        pattern: &str,
+        hosts: &[(String, &HostEntry)],
+        into: &Path,
+    ) -> Result<Vec<DownloadRecord>> {
+        if hosts.len() > 1 && !self.template.is_per_host() {
+            anyhow::bail!(
+                "Le modèle {} doit contenir {{host}} pour télécharger depuis plusieurs serveurs",
+                self.template
+            );
+        }
+
+        if !self.quiet {
+            println!(
+                "📥 Téléchargement de {} depuis {} serveur(s)",
+                pattern,
+                hosts.len()
+            );
+            println!("📂 Destination: {}/{}", into.display(), self.template);
+        }
+
+        let display = TransferDisplay::new(0, self.quiet);
+        let download_futures = hosts.iter().map(|(host_name, host_entry)| {
+            let host_name = host_name.clone();
+            let host_entry = (*host_entry).clone();
+            let pool = self.pool.clone();
+            let display = display.clone();
+
+            async move {
+                let records = self
+                    .download_from_host(pool, &host_name, &host_entry, pattern, into, &display)
+                    .await;
+
+                let failed = records.iter().filter(|record| !record.success).count();
+                if failed == 0 {
+                    let bytes: u64 = records.iter().map(|record| record.bytes).sum();
+                    display.println(&format!(
+                        "  ✅ {} ({} fichier(s), {})",
+                        host_name,
+                        records.len(),
+                        Validator::format_file_size(bytes)
+                    ));
+                } else {
+                    for record in records.iter().filter(|record| !record.success) {
+                        display.println(&format!(
+                            "  ❌ {} : {} : {}",
+                            host_name,
+                            record.remote,
+                            record.error.as_deref().unwrap_or_default()
+                        ));
+                    }
+                }
+                records
+            }
+        });
+
+        let records: Vec<Vec<DownloadRecord>> = stream::iter(download_futures)
+            .buffer_unordered(self.concurrency.global)
+            .collect()
+            .await;
+        display.finish();
+
+        self.pool.close_all().await;
+        log::debug!(
+            "Pool downloader fermé ({} connexion(s) fermée(s))",
+            self.pool.active_connections()
+        );
+
+        let records: Vec<DownloadRecord> = records.into_iter().flatten().collect();
+        if !self.quiet {
+            let summary = DownloadSummary::of(&records);
+            if summary.failed == 0 {
+                println!("\n✅ Téléchargement terminé avec succès!");
+            } else {
+                println!("\n⚠️ Téléchargement terminé avec des erreurs");
+            }
+            println!(
+                "📊 Serveur × fichier: {} téléchargé(s), {} en échec ({})",
+                summary.succeeded,
+                summary.failed,
+                Validator::format_file_size(summary.bytes)
+            );
+        }
+        Ok(records)
+    }
+
+    /// Télécharger les fichiers correspondant à `pattern` depuis un serveur.
+    /// Un fichier en échec n'arrête pas les suivants.
+    async fn download_from_host(
+        &self,
+        pool: ConnectionPool,
+        host_name: &str,
+        host_entry: &HostEntry,
+        pattern: &str,
+        into: &Path,
+        display: &TransferDisplay,
+    ) -> Vec<DownloadRecord> {
+        let listed = async {
+            let target = pool.resolve(host_entry)?;
+            let files = self
+                .retry
+                .run(host_name, || {
+                    Self::list_once(&pool, &target, pattern, self.recursive)
+                })
+                .await?;
+            Ok::<_, anyhow::Error>((target, files))
+        }
+        .await;
+        let (target, files) = match listed {
+            Ok(listed) => listed,
+            Err(e) => return vec![DownloadRecord::failed(host_name, pattern, None, &e)],
+        };
+
+        let total: u64 = files.iter().map(|file| file.size).sum();
+        display.add_total(total);
+        let transfer = display.start(short_name(host_name), total);
+
+        let jobs = files.iter().enumerate().map(|(index, file)| {
+            let (pool, target, transfer) = (&pool, &target, &transfer);
+            async move {
+                let local = match self.template.render(host_name, &file.path) {
+                    Ok(path) => into.join(path),
+                    Err(e) => {
+                        return (
+                            index,
+                            DownloadRecord::failed(host_name, &file.path, None, &e),
+                        )
+                    }
+                };
+                if interrupt::is_interrupted() {
+                    let e = anyhow::anyhow!("Téléchargement interrompu");
+                    let record = DownloadRecord::failed(host_name, &file.path, Some(&local), &e);
+                    return (index, record);
+                }
+
+                let progress = transfer.file(&file.path);
+                let result = self
+                    .retry
+                    .run(host_name, || {
+                        Self::download_once(pool, target, &file.path, &local, &progress)
+                    })
+                    .await;
+                let record = match result {
+                    Ok(downloaded) => {
+                        progress.complete_file(file.size);
+                        DownloadRecord::succeeded(host_name, &file.path, &local, downloaded)
+                    }
+                    Err(e) => {
+                        progress.restart_file(0);
+                        DownloadRecord::failed(host_name, &file.path, Some(&local), &e)
+                    }
+                };
+                (index, record)
+            }
+        });
+        let mut records: Vec<(usize, DownloadRecord)> = stream::iter(jobs)
+            .buffer_unordered(self.concurrency.per_host)
+            .collect()
+            .await;
+        transfer.finish();
+
+        // Résultats dans l'ordre des fichiers, quel que soit l'ordre de fin
+        records.sort_by_key(|(index, _)| *index);
+        records.into_iter().map(|(_, record)| record).collect()
+    }
+
+    /// Liste des fichiers distants correspondant au motif (`~` développé)
+    async fn list_once(
+        pool: &ConnectionPool,
+        target: &SshTarget,
+        pattern: &str,
+        recursive: bool,
+    ) -> Result<Vec<RemoteFile>> {
+        let (client_arc, _permit) = pool.acquire(target).await?;
+        let mut client = client_arc.lock().await;
+
+        let result = async {
+            let pattern =
+                path_expansion::expand_path(pattern, &target.username, client.get_remote_home())
+                    .context("Erreur lors de l'expansion du chemin distant")?;
+            let paths = client.expand_remote_glob(&pattern).await?;
+            if paths.is_empty() {
+                anyhow::bail!("Aucun fichier ne correspond à {}", pattern);
+            }
+
+            let mut files = Vec::new();
+            for path in paths {
+                files.extend(client.list_remote_files(&path, recursive).await?);
+            }
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            files.dedup();
+            Ok(files)
+        }
+        .await;
+
+        if let Err(e) = &result {
+            pool.invalidate_on_error(&target.pool_key(), &client, e);
+        }
+        result
+    }
+
+    /// Une tentative de téléchargement d'un fichier
+    async fn download_once(
+        pool: &ConnectionPool,
+        target: &SshTarget,
+        remote: &str,
+        local: &Path,
+        progress: &TransferProgress,
+    ) -> Result<DownloadedFile> {
+        let (mut client, _permit) = pool.acquire_lane(target).await?;
+        let result = client.download_file(remote, local, progress).await;
+        if let Err(e) = &result {
+            pool.invalidate_on_error(&target.pool_key(), &client, e);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_rendering() {
+        let host = "Production:Region-A:Public:WEB_01";
+        let default = PathTemplate::default();
+        assert_eq!(
+            default.render(host, "/etc/hosts").unwrap(),
+            PathBuf::from("WEB_01/etc/hosts")
+        );
+
+        let flat: PathTemplate = "{env}/{region}-{type}/{host}_{name}".parse().unwrap();
+        assert_eq!(
+            flat.render(host, "/var/crash/core.1234").unwrap(),
+            PathBuf::from("Production/Region-A-Public/WEB_01_core.1234")
+        );
+        // Une accolade dans le nom distant n'est pas interprétée
+        assert_eq!(
+            default.render(host, "/tmp/{env}.txt").unwrap(),
+            PathBuf::from("WEB_01/tmp/{env}.txt")
+        );
+    }
+
+    #[test]
+    fn test_template_rejects_invalid_or_escaping_paths() {
+        assert!("{host}/{file}".parse::<PathTemplate>().is_err());
+        assert!("{host}/{path".parse::<PathTemplate>().is_err());
+        assert!("{host}".parse::<PathTemplate>().is_err());
+        assert!(!"{env}/{path}"
+            .parse::<PathTemplate>()
+            .unwrap()
+            .is_per_host());
+
+        let default = PathTemplate::default();
+        assert!(default
+            .render("Production:A:Web:WEB_01", "/srv/../../etc/passwd")
+            .is_err());
+    }
+
+    #[test]
+    fn test_summary_counts_failures_and_bytes() {
+        let downloaded = DownloadedFile {
+            bytes: 42,
+            sha256: "ab".to_string(),
+        };
+        let records = vec![
+            DownloadRecord::succeeded("WEB_01", "/etc/hosts", Path::new("out/a"), downloaded),
+            DownloadRecord::failed("WEB_02", "/etc/hosts", None, &anyhow::anyhow!("refusé")),
+        ];
+        assert_eq!(
+            DownloadSummary::of(&records),
+            DownloadSummary {
+                total: 2,
+                succeeded: 1,
+                failed: 1,
+                bytes: 42,
+            }
+        );
+    }
+}
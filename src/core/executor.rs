@@ -1,7 +1,10 @@
-use crate::config::HostEntry;
-use crate::core::uploader::Uploader;
+use crate::config::{HostEntry, HostOptions};
+use crate::core::output::LiveOutput;
+use crate::ssh::client::ExecOptions;
 use crate::ssh::keys::PassphraseCache;
+use crate::ssh::password::PasswordCache;
 use crate::ssh::pool::ConnectionPool;
+use crate::utils::interrupt;
 use anyhow::Result;
 use futures::stream::{self, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
@@ -11,6 +14,10 @@ use tokio::sync::Mutex;
 
 pub struct CommandExecutor {
     pool: ConnectionPool,
+    /// Affichage en direct de la sortie (None: sortie uniquement dans les résultats)
+    live_output: Option<LiveOutput>,
+    /// Entrée rejouée sur chaque serveur (--stdin), PTY (--pty) et sudo (--become)
+    exec_options: ExecOptions,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -19,11 +26,27 @@ pub struct CommandResult {
     pub exit_code: i32,
     pub stdout: String,
     pub stderr: String,
+    /// Signal ayant terminé le processus distant ("TERM", "KILL"...)
+    pub exit_signal: Option<String>,
+    /// Arrêtée par xsshend (timeout ou Ctrl-C)
+    pub killed: bool,
     #[serde(serialize_with = "serialize_duration")]
     pub duration: Duration,
     pub success: bool,
 }
 
+impl CommandResult {
+    /// Motif d'échec affiché à l'utilisateur
+    pub fn failure_reason(&self) -> String {
+        match (&self.exit_signal, self.killed) {
+            (Some(signal), true) => format!("Arrêtée (SIG{})", signal),
+            (None, true) => "Arrêtée (timeout ou interruption)".to_string(),
+            (Some(signal), false) => format!("Terminée par SIG{}", signal),
+            (None, false) => format!("Exit code: {}", self.exit_code),
+        }
+    }
+}
+
 fn serialize_duration<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -41,11 +64,48 @@ pub struct ExecutionSummary {
 
 impl CommandExecutor {
     pub fn new() -> Self {
+        Self::with_overrides(HostOptions::default())
+    }
+
+    /// Créer avec des options de connexion imposées par la CLI (--key, ...)
+    pub fn with_overrides(overrides: HostOptions) -> Self {
         CommandExecutor {
-            pool: ConnectionPool::new(PassphraseCache::new()),
+            pool: ConnectionPool::new(PassphraseCache::new()).with_overrides(overrides),
+            live_output: None,
+            exec_options: ExecOptions::default(),
         }
     }
 
+    /// Partager le cache de mots de passe de l'exécution (--password-stdin, ...)
+    pub fn with_password_cache(mut self, password_cache: PasswordCache) -> Self {
+        self.pool = self.pool.with_password_cache(password_cache);
+        self
+    }
+
+    /// Afficher la sortie de chaque serveur au fil de l'eau, préfixée par son nom
+    pub fn with_live_output(mut self, live_output: LiveOutput) -> Self {
+        self.live_output = Some(live_output);
+        self
+    }
+
+    /// Transmettre ces données sur l'entrée standard de chaque commande distante
+    pub fn with_stdin(mut self, input: Vec<u8>) -> Self {
+        self.exec_options.input = Some(input.into());
+        self
+    }
+
+    /// Exécuter en tant qu'un autre utilisateur via sudo (--become)
+    pub fn with_become(mut self, user: Option<String>) -> Self {
+        self.exec_options.become_user = user;
+        self
+    }
+
+    /// Allouer un pseudo-terminal pour chaque commande
+    pub fn with_pty(mut self, pty: bool) -> Self {
+        self.exec_options.pty = pty;
+        self
+    }
+
     pub async fn execute(
         &self,
         command: &str,
@@ -80,14 +140,28 @@ impl CommandExecutor {
             pb.set_message("Exécution en cours...");
         }
 
-        for (host_name, host_entry) in hosts.iter() {
-            {
+        for (index, (host_name, host_entry)) in hosts.iter().enumerate() {
+            if interrupt::is_interrupted() {
                 let pb = progress.lock().await;
-                pb.set_message(format!("Serveur: {}", host_name));
+                pb.println("  ⚠️  Interruption : serveurs restants ignorés");
+                break;
             }
 
+            let pb_handle = {
+                let pb = progress.lock().await;
+                pb.set_message(format!("Serveur: {}", host_name));
+                pb.clone()
+            };
+
             match self
-                .execute_on_host(command, host_name, host_entry, timeout)
+                .execute_on_host(
+                    command,
+                    host_name,
+                    index,
+                    host_entry,
+                    timeout,
+                    Some(pb_handle),
+                )
                 .await
             {
                 Ok(result) => {
@@ -99,10 +173,7 @@ impl CommandExecutor {
                             result.duration.as_secs_f64()
                         ));
                     } else {
-                        pb.println(format!(
-                            "  ❌ {} - Exit code: {}",
-                            host_name, result.exit_code
-                        ));
+                        pb.println(format!("  ❌ {} - {}", host_name, result.failure_reason()));
                     }
                     pb.inc(1);
                     results.push(result);
@@ -133,34 +204,47 @@ impl CommandExecutor {
     ) -> Result<Vec<CommandResult>> {
         println!("🚀 Exécution parallèle sur {} serveur(s)...\n", hosts.len());
 
-        let futures = hosts.iter().map(|(host_name, host_entry)| {
-            let cmd = command.to_owned();
-            let name = host_name.clone();
-            let entry = (*host_entry).clone();
-            let pool = self.pool.clone();
-
-            async move {
-                let executor = CommandExecutor { pool };
-                executor.execute_on_host(&cmd, &name, &entry, timeout).await
-            }
-        });
+        let futures = hosts
+            .iter()
+            .enumerate()
+            .map(|(index, (host_name, host_entry))| {
+                let cmd = command.to_owned();
+                let name = host_name.clone();
+                let entry = (*host_entry).clone();
+                let executor = CommandExecutor {
+                    pool: self.pool.clone(),
+                    live_output: self.live_output.clone(),
+                    exec_options: self.exec_options.clone(),
+                };
 
-        let results: Vec<_> = stream::iter(futures).buffer_unordered(10).collect().await;
+                async move {
+                    let result = executor
+                        .execute_on_host(&cmd, &name, index, &entry, timeout, None)
+                        .await;
+                    (name, result)
+                }
+            });
 
-        for result in results.iter().flatten() {
-            if result.success {
-                println!(
+        // Statut affiché dès qu'un serveur termine
+        let results: Vec<_> = stream::iter(futures)
+            .buffer_unordered(10)
+            .inspect(|(host_name, result)| match result {
+                Ok(result) if result.success => println!(
                     "  ✅ {} ({:.2}s)",
                     result.host,
                     result.duration.as_secs_f64()
-                );
-            } else {
-                println!("  ❌ {} - Exit code: {}", result.host, result.exit_code);
-            }
-        }
+                ),
+                Ok(result) => println!("  ❌ {} - {}", result.host, result.failure_reason()),
+                Err(e) => println!("  ❌ {} - Erreur: {}", host_name, e),
+            })
+            .collect()
+            .await;
 
         self.pool.close_all().await;
-        Ok(results.into_iter().filter_map(Result::ok).collect())
+        Ok(results
+            .into_iter()
+            .filter_map(|(_, result)| result.ok())
+            .collect())
     }
 
     /// Exécuter via le pool — ne déconnecte PAS (connexion réutilisée).
@@ -168,24 +252,45 @@ impl CommandExecutor {
         &self,
         command: &str,
         host_name: &str,
+        index: usize,
         host_entry: &HostEntry,
         timeout: Duration,
+        progress: Option<ProgressBar>,
     ) -> Result<CommandResult> {
-        let (username, host) = Uploader::parse_server_alias(&host_entry.alias)?;
-        let host_key = format!("{}@{}", username, host);
+        let target = self.pool.resolve(host_entry)?;
+        let host_key = target.pool_key();
 
         log::debug!("Exécution sur {} via pool", host_key);
         let start = std::time::Instant::now();
 
-        let (client_arc, _permit) = self.pool.acquire(&host_key, username, host).await?;
+        let (client_arc, _permit) = self.pool.acquire(&target).await?;
         let mut client = client_arc.lock().await;
 
-        let output = match client.execute_command(command, timeout).await {
+        // Ctrl-C pendant la connexion: ne pas lancer la commande
+        if interrupt::is_interrupted() {
+            anyhow::bail!("Interrompu avant l'exécution");
+        }
+
+        let mut printer = self
+            .live_output
+            .as_ref()
+            .map(|live| live.for_host(host_name, index, progress));
+        let output = client
+            .execute_command_streaming(command, &self.exec_options, timeout, |stream, data| {
+                if let Some(printer) = printer.as_mut() {
+                    printer.push(stream, data);
+                }
+            })
+            .await;
+        if let Some(printer) = printer.as_mut() {
+            printer.finish();
+        }
+
+        let output = match output {
             Ok(out) => out,
             Err(e) => {
-                drop(client);
-                self.pool.invalidate(&host_key);
-                log::warn!("⚠️  Erreur sur {} (connexion invalidée) : {}", host_key, e);
+                self.pool.invalidate_on_error(&host_key, &client, &e);
+                log::warn!("⚠️  Erreur sur {} : {}", host_key, e);
                 return Err(e);
             }
         };
@@ -195,8 +300,10 @@ impl CommandExecutor {
             exit_code: output.exit_code,
             stdout: output.stdout,
             stderr: output.stderr,
+            exit_signal: output.exit_signal,
+            killed: output.killed,
             duration: start.elapsed(),
-            success: output.exit_code == 0,
+            success: output.exit_code == 0 && !output.killed,
         })
     }
 }
@@ -224,9 +331,31 @@ mod tests {
             exit_code: 0,
             stdout: "ok".to_string(),
             stderr: "".to_string(),
+            exit_signal: None,
+            killed: false,
             duration: Duration::from_secs(1),
             success: true,
         };
         assert!(result.success);
     }
+
+    #[test]
+    fn test_failure_reason_reports_signal() {
+        let mut result = CommandResult {
+            host: "test-host".to_string(),
+            exit_code: 143,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_signal: Some("TERM".to_string()),
+            killed: true,
+            duration: Duration::from_secs(30),
+            success: false,
+        };
+        assert_eq!(result.failure_reason(), "Arrêtée (SIGTERM)");
+
+        result.killed = false;
+        result.exit_signal = None;
+        result.exit_code = 2;
+        assert_eq!(result.failure_reason(), "Exit code: 2");
+    }
 }
@@ -1,6 +1,7 @@
-use crate::config::HostEntry;
-use crate::core::uploader::Uploader;
+use crate::config::{HostEntry, HostOptions};
+use crate::ssh::client::ExecOptions;
 use crate::ssh::keys::PassphraseCache;
+use crate::ssh::password::PasswordCache;
 use crate::ssh::pool::ConnectionPool;
 use anyhow::Result;
 use futures::stream::{self, StreamExt};
@@ -23,15 +24,35 @@ impl GrepResult {
 
 pub struct GrepExecutor {
     pool: ConnectionPool,
+    /// Options d'exécution du grep distant (--become)
+    exec_options: ExecOptions,
 }
 
 impl GrepExecutor {
     pub fn new() -> Self {
+        Self::with_overrides(HostOptions::default())
+    }
+
+    /// Créer avec des options de connexion imposées par la CLI (--key, ...)
+    pub fn with_overrides(overrides: HostOptions) -> Self {
         GrepExecutor {
-            pool: ConnectionPool::new(PassphraseCache::new()),
+            pool: ConnectionPool::new(PassphraseCache::new()).with_overrides(overrides),
+            exec_options: ExecOptions::default(),
         }
     }
 
+    /// Partager le cache de mots de passe de l'exécution (--password-stdin, ...)
+    pub fn with_password_cache(mut self, password_cache: PasswordCache) -> Self {
+        self.pool = self.pool.with_password_cache(password_cache);
+        self
+    }
+
+    /// Lire les logs en tant qu'un autre utilisateur via sudo (--become)
+    pub fn with_become(mut self, user: Option<String>) -> Self {
+        self.exec_options.become_user = user;
+        self
+    }
+
     pub async fn grep(
         &self,
         pattern: &str,
@@ -68,24 +89,30 @@ impl GrepExecutor {
         command: &str,
         timeout: Duration,
     ) -> Result<GrepResult> {
-        let (username, host) = Uploader::parse_server_alias(&host_entry.alias)?;
-        let host_key = format!("{}@{}", username, host);
+        let target = self.pool.resolve(host_entry)?;
+        let host_key = target.pool_key();
 
         log::debug!("Grep sur {} via pool", host_key);
 
-        let (client_arc, _permit) = self.pool.acquire(&host_key, username, host).await?;
+        let (client_arc, _permit) = self.pool.acquire(&target).await?;
         let mut client = client_arc.lock().await;
 
-        let output = match client.execute_command(command, timeout).await {
+        let output = match client
+            .execute_command_streaming(command, &self.exec_options, timeout, |_, _| {})
+            .await
+        {
             Ok(out) => out,
             Err(e) => {
-                drop(client);
-                self.pool.invalidate(&host_key);
+                self.pool.invalidate_on_error(&host_key, &client, &e);
                 log::warn!("⚠️  Erreur grep sur {} : {}", host_key, e);
                 return Err(e);
             }
         };
 
+        if output.killed {
+            anyhow::bail!("Recherche arrêtée (timeout ou interruption)");
+        }
+
         let (lines_str, exit_code) = Self::parse_grep_output(&output.stdout);
         let matches: Vec<String> = lines_str.lines().map(|l| l.to_string()).collect();
         let match_count = if exit_code == 0 {
@@ -134,20 +161,28 @@ impl GrepExecutor {
             let name = host_name.clone();
             let entry = (*host_entry).clone();
             let pool = self.pool.clone();
+            let exec_options = self.exec_options.clone();
 
             async move {
-                let executor = GrepExecutor { pool };
-                executor
+                let executor = GrepExecutor { pool, exec_options };
+                let result = executor
                     .execute_grep_on_host(&name, &entry, &cmd, timeout)
-                    .await
+                    .await;
+                (name, result)
             }
         });
 
         let results: Vec<_> = stream::iter(futures).buffer_unordered(10).collect().await;
 
-        let mut grep_results: Vec<GrepResult> =
-            results.into_iter().filter_map(|r| r.ok()).collect();
-        grep_results.sort_by(|a, b| b.match_count.cmp(&a.match_count));
+        let mut grep_results: Vec<GrepResult> = results
+            .into_iter()
+            .filter_map(|(host_name, result)| {
+                result
+                    .map_err(|e| eprintln!("  ❌ {} - Erreur: {}", host_name, e))
+                    .ok()
+            })
+            .collect();
+        grep_results.sort_by_key(|r| std::cmp::Reverse(r.match_count));
 
         self.pool.close_all().await;
         Ok(grep_results)
@@ -175,12 +210,13 @@ impl GrepExecutor {
             let name = host_name.clone();
             let entry = (*host_entry).clone();
             let pool = self.pool.clone();
+            let exec_options = self.exec_options.clone();
             let tx = result_tx.clone();
             let mut stop = stop_rx.clone();
             let stop_sender = Arc::clone(&stop_tx);
 
             set.spawn(async move {
-                let executor = GrepExecutor { pool };
+                let executor = GrepExecutor { pool, exec_options };
                 tokio::select! {
                     result = executor.execute_grep_on_host(&name, &entry, &cmd, timeout) => {
                         if let Ok(grep_result) = result {
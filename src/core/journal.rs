@@ -0,0 +1,187 @@
+// Journal local des téléversements avec sauvegarde (--backup)
+//
+// Chaque exécution écrit `<run-id>.json` dans ~/.local/share/xsshend/runs/ :
+// les fichiers remplacés, serveur par serveur, avec le chemin de leur copie.
+// `xsshend rollback <run-id>` s'en sert pour restaurer les versions précédentes.
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Fichier remplacé sur un serveur
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Nom complet du serveur ("Env:Région:Type:Nom")
+    pub host: String,
+    /// Chemin distant écrit
+    pub remote: String,
+    /// Copie de la version précédente; `None` si le fichier n'existait pas
+    pub backup: Option<String>,
+}
+
+/// Journal d'une exécution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunJournal {
+    pub run_id: String,
+    pub started_at: DateTime<Local>,
+    /// Utilisateur de --become, réutilisé pour la restauration
+    pub become_user: Option<String>,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl RunJournal {
+    pub fn new(run_id: String, become_user: Option<String>) -> Self {
+        Self {
+            run_id,
+            started_at: Local::now(),
+            become_user,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Identifiant d'exécution: date et heure, plus un suffixe contre les collisions
+    pub fn new_run_id() -> String {
+        let now = Local::now();
+        let suffix = (now.timestamp_subsec_nanos() ^ std::process::id()) & 0xffff;
+        format!("{}-{:04x}", now.format("%Y%m%d-%H%M%S"), suffix)
+    }
+
+    /// Répertoire des journaux
+    pub fn journal_dir() -> Result<PathBuf> {
+        let base = dirs::data_local_dir()
+            .context("Impossible de déterminer le répertoire de données local")?;
+        Ok(base.join("xsshend").join("runs"))
+    }
+
+    /// Serveurs touchés par l'exécution, dans l'ordre du journal
+    pub fn hosts(&self) -> Vec<&str> {
+        let mut hosts: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            if !hosts.contains(&entry.host.as_str()) {
+                hosts.push(&entry.host);
+            }
+        }
+        hosts
+    }
+
+    /// Enregistrer le journal; renvoie le chemin du fichier écrit
+    pub fn save(&self) -> Result<PathBuf> {
+        self.save_in(&Self::journal_dir()?)
+    }
+
+    pub fn save_in(&self, dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Impossible de créer {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", self.run_id));
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&path, content)
+            .with_context(|| format!("Impossible d'écrire le journal {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Charger le journal d'une exécution
+    pub fn load(run_id: &str) -> Result<Self> {
+        Self::load_from(&Self::journal_dir()?, run_id)
+    }
+
+    pub fn load_from(dir: &Path, run_id: &str) -> Result<Self> {
+        if run_id.is_empty() || run_id.contains(['/', '\\']) || run_id.starts_with('.') {
+            anyhow::bail!("Identifiant d'exécution invalide: {}", run_id);
+        }
+        let path = dir.join(format!("{}.json", run_id));
+        let content = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Aucun journal pour l'exécution {} ({})",
+                run_id,
+                path.display()
+            )
+        })?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Journal illisible: {}", path.display()))
+    }
+
+    /// Journaux disponibles, du plus récent au plus ancien
+    pub fn list() -> Result<Vec<Self>> {
+        Self::list_in(&Self::journal_dir()?)
+    }
+
+    pub fn list_in(dir: &Path) -> Result<Vec<Self>> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut journals = Vec::new();
+        for entry in
+            fs::read_dir(dir).with_context(|| format!("Impossible de lire {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                match fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+                {
+                    Some(journal) => journals.push(journal),
+                    None => log::warn!("⚠️  Journal illisible ignoré: {}", path.display()),
+                }
+            }
+        }
+        journals.sort_by_key(|journal| std::cmp::Reverse(journal.started_at));
+        Ok(journals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(host: &str, remote: &str, backup: Option<&str>) -> JournalEntry {
+        JournalEntry {
+            host: host.to_string(),
+            remote: remote.to_string(),
+            backup: backup.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_save_load_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let mut journal = RunJournal::new("20261017-101500-1a2b".to_string(), None);
+        journal.entries = vec![
+            entry(
+                "Production:A:Web:WEB_01",
+                "/etc/app.conf",
+                Some("/etc/app.conf.x.bak"),
+            ),
+            entry("Production:A:Web:WEB_02", "/etc/app.conf", None),
+            entry("Production:A:Web:WEB_01", "/etc/other.conf", None),
+        ];
+        journal.save_in(temp.path()).unwrap();
+
+        let loaded = RunJournal::load_from(temp.path(), "20261017-101500-1a2b").unwrap();
+        assert_eq!(loaded.entries, journal.entries);
+        assert_eq!(
+            loaded.hosts(),
+            vec!["Production:A:Web:WEB_01", "Production:A:Web:WEB_02"]
+        );
+        assert_eq!(RunJournal::list_in(temp.path()).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_or_unsafe_ids() {
+        let temp = TempDir::new().unwrap();
+        assert!(RunJournal::load_from(temp.path(), "20990101-000000-0000").is_err());
+        assert!(RunJournal::load_from(temp.path(), "../hosts").is_err());
+        assert!(RunJournal::list_in(&temp.path().join("absent"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_run_id_format() {
+        let run_id = RunJournal::new_run_id();
+        // AAAAMMJJ-HHMMSS-xxxx
+        assert_eq!(run_id.len(), 20);
+        assert!(run_id.chars().all(|c| c.is_ascii_hexdigit() || c == '-'));
+    }
+}
@@ -1,5 +1,11 @@
 // Module core - Logique métier principale
+pub mod downloader;
 pub mod executor;
 pub mod grep;
+pub mod journal;
+pub mod output;
+pub mod sources;
+pub mod sync;
+pub mod tree;
 pub mod uploader;
 pub mod validator;
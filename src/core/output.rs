@@ -0,0 +1,224 @@
+// Affichage en direct de la sortie des commandes distantes
+//
+// Chaque ligne est préfixée par le nom du serveur, avec une couleur par serveur.
+// stdout est écrit sur stdout, stderr sur stderr.
+use crate::ssh::client::OutputStream;
+use console::{style, Color};
+use indicatif::ProgressBar;
+
+/// Couleurs attribuées aux serveurs, dans l'ordre de la liste des cibles
+const HOST_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
+/// Paramètres d'affichage communs à tous les serveurs d'une exécution
+#[derive(Debug, Clone)]
+pub struct LiveOutput {
+    /// Largeur des préfixes, pour aligner les lignes de tous les serveurs
+    width: usize,
+    /// Préfixer par le chemin complet quand deux serveurs ont le même nom court
+    full_names: bool,
+    /// Afficher aussi stderr (--capture-stderr)
+    show_stderr: bool,
+}
+
+impl LiveOutput {
+    pub fn new(host_names: &[&str], show_stderr: bool) -> Self {
+        let mut short_names: Vec<&str> = host_names.iter().map(|name| short_name(name)).collect();
+        short_names.sort_unstable();
+        short_names.dedup();
+        let full_names = short_names.len() < host_names.len();
+
+        let mut live = Self {
+            width: 0,
+            full_names,
+            show_stderr,
+        };
+        live.width = host_names
+            .iter()
+            .map(|name| live.label(name).chars().count())
+            .max()
+            .unwrap_or(0);
+        live
+    }
+
+    fn label<'a>(&self, host_name: &'a str) -> &'a str {
+        if self.full_names {
+            host_name
+        } else {
+            short_name(host_name)
+        }
+    }
+
+    /// Afficheur dédié à un serveur (`index` = rang dans la liste, pour la couleur)
+    pub fn for_host(
+        &self,
+        host_name: &str,
+        index: usize,
+        progress: Option<ProgressBar>,
+    ) -> HostPrinter {
+        HostPrinter {
+            label: format!("{:<width$}", self.label(host_name), width = self.width),
+            color: HOST_COLORS[index % HOST_COLORS.len()],
+            show_stderr: self.show_stderr,
+            stdout: LineBuffer::default(),
+            stderr: LineBuffer::default(),
+            progress,
+        }
+    }
+}
+
+/// Nom court d'un serveur : dernier segment de `Env:Région:Type:NOM`
+pub fn short_name(host_name: &str) -> &str {
+    host_name.rsplit(':').next().unwrap_or(host_name)
+}
+
+/// Affiche la sortie d'un serveur ligne par ligne
+pub struct HostPrinter {
+    label: String,
+    color: Color,
+    show_stderr: bool,
+    stdout: LineBuffer,
+    stderr: LineBuffer,
+    /// Barre de progression à suspendre pendant l'affichage (mode séquentiel)
+    progress: Option<ProgressBar>,
+}
+
+impl HostPrinter {
+    /// Données reçues du serveur : seules les lignes complètes sont affichées
+    pub fn push(&mut self, stream: OutputStream, data: &[u8]) {
+        if stream == OutputStream::Stderr && !self.show_stderr {
+            return;
+        }
+        let lines = self.buffer(stream).push(data);
+        for line in lines {
+            self.print(stream, &line);
+        }
+    }
+
+    /// Fin de la commande : afficher les lignes sans retour à la ligne final
+    pub fn finish(&mut self) {
+        for stream in [OutputStream::Stdout, OutputStream::Stderr] {
+            if let Some(line) = self.buffer(stream).flush() {
+                self.print(stream, &line);
+            }
+        }
+    }
+
+    fn buffer(&mut self, stream: OutputStream) -> &mut LineBuffer {
+        match stream {
+            OutputStream::Stdout => &mut self.stdout,
+            OutputStream::Stderr => &mut self.stderr,
+        }
+    }
+
+    fn print(&self, stream: OutputStream, line: &str) {
+        let emit = || match stream {
+            OutputStream::Stdout => {
+                let prefix = style(format!("{} │", self.label)).fg(self.color);
+                println!("{} {}", prefix, line);
+            }
+            OutputStream::Stderr => {
+                let prefix = style(format!("{} ‼", self.label))
+                    .fg(self.color)
+                    .for_stderr();
+                eprintln!("{} {}", prefix, line);
+            }
+        };
+
+        match &self.progress {
+            Some(pb) => pb.suspend(emit),
+            None => emit(),
+        }
+    }
+}
+
+/// Découpe un flux d'octets en lignes complètes
+#[derive(Debug, Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Ajouter des données, renvoyer les lignes terminées par `\n`
+    fn push(&mut self, data: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(data);
+
+        let mut lines = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            lines.push(decode_line(&line[..pos]));
+        }
+        lines
+    }
+
+    /// Reste sans retour à la ligne final
+    fn flush(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let line = decode_line(&self.pending);
+        self.pending.clear();
+        Some(line)
+    }
+}
+
+fn decode_line(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_buffer_splits_chunks() {
+        let mut buffer = LineBuffer::default();
+        assert!(buffer.push(b"Deploy").is_empty());
+        assert_eq!(
+            buffer.push(b"ing...\r\nstep 1\nstep"),
+            vec!["Deploying...", "step 1"]
+        );
+        assert_eq!(buffer.push(b" 2\n"), vec!["step 2"]);
+        assert_eq!(buffer.flush(), None);
+
+        assert!(buffer.push(b"done").is_empty());
+        assert_eq!(buffer.flush().as_deref(), Some("done"));
+        assert_eq!(buffer.flush(), None);
+    }
+
+    #[test]
+    fn test_line_buffer_utf8_across_chunks() {
+        let mut buffer = LineBuffer::default();
+        let text = "déploiement terminé\n".as_bytes();
+        assert!(buffer.push(&text[..2]).is_empty());
+        assert_eq!(buffer.push(&text[2..]), vec!["déploiement terminé"]);
+    }
+
+    #[test]
+    fn test_labels_aligned_and_colored_per_host() {
+        let hosts = ["Production:Region-A:Public:WEB_01", "Staging:Local:API"];
+        let live = LiveOutput::new(&hosts, false);
+
+        let web = live.for_host(hosts[0], 0, None);
+        let api = live.for_host(hosts[1], 1, None);
+        assert_eq!(web.label, "WEB_01");
+        assert_eq!(api.label, "API   ");
+        assert_ne!(web.color, api.color);
+        assert_eq!(
+            live.for_host(hosts[0], HOST_COLORS.len(), None).color,
+            web.color
+        );
+
+        // Noms courts identiques: chemin complet
+        let hosts = ["Production:Local:Web:WEB_01", "Staging:Local:Web:WEB_01"];
+        let live = LiveOutput::new(&hosts, false);
+        assert_eq!(live.for_host(hosts[1], 1, None).label.trim_end(), hosts[1]);
+    }
+}
@@ -0,0 +1,404 @@
+// Sources de téléversement: chemins, motifs et listes de fichiers
+//
+// Les motifs sont développés ici plutôt que par le shell, pour qu'ils fonctionnent
+// aussi entre guillemets et dans une liste (--files-from) :
+// - `*` et `?` à l'intérieur d'un nom, sans franchir de `/`
+// - `**` comme composant entier: zéro ou plusieurs répertoires
+// - les noms commençant par `.` ne correspondent qu'à un motif commençant par `.`
+//
+// Les doublons (même chemin, ou chemin déjà inclus par un répertoire retenu) sont
+// retirés, et tous les chemins et motifs sans correspondance signalés ensemble.
+use crate::utils::pattern::wildcard_match;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Component, Path, PathBuf};
+
+/// Valeur de --files-from désignant l'entrée standard
+pub const STDIN: &str = "-";
+
+/// Développer les arguments et la liste --files-from en sources à téléverser,
+/// dans l'ordre d'apparition. `base`: répertoire dont les chemins distants sont relatifs.
+pub fn collect(
+    args: &[String],
+    files_from: Option<&Path>,
+    base: Option<&Path>,
+) -> Result<Vec<PathBuf>> {
+    let mut patterns = args.to_vec();
+    if let Some(list) = files_from {
+        patterns.extend(read_list(list)?);
+    }
+    if patterns.is_empty() {
+        anyhow::bail!("❌ Aucun fichier à téléverser");
+    }
+
+    let mut sources = Vec::new();
+    let mut missing = Vec::new();
+    for pattern in &patterns {
+        let matches = expand(pattern)?;
+        if matches.is_empty() {
+            missing.push(if is_pattern(pattern) {
+                format!("{} (aucune correspondance)", pattern)
+            } else {
+                format!("{} (introuvable)", pattern)
+            });
+        }
+        sources.extend(matches);
+    }
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "❌ {} source(s) introuvable(s):\n   • {}",
+            missing.len(),
+            missing.join("\n   • ")
+        );
+    }
+
+    let sources = dedup(sources);
+    check_remote_paths(&sources, base)?;
+    Ok(sources)
+}
+
+/// Chemin distant d'une source, relatif à la destination: relatif à `base`,
+/// ou à défaut son seul nom (comme `scp`)
+pub fn remote_root(source: &Path, base: Option<&Path>) -> Result<String> {
+    let Some(base) = base else {
+        // `.` ou `..` n'ont pas de nom: prendre celui du répertoire réel
+        let name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .or_else(|| {
+                absolute(source)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+            })
+            .with_context(|| format!("Nom de source invalide: {}", source.display()))?;
+        return Ok(name);
+    };
+
+    let relative = absolute(source)
+        .strip_prefix(absolute(base))
+        .map(Path::to_path_buf)
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "{} n'est pas dans le répertoire de base {}",
+                source.display(),
+                base.display()
+            )
+        })?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if parts.is_empty() {
+        anyhow::bail!(
+            "{} est le répertoire de base lui-même: indiquer son contenu (ex: {}/*)",
+            source.display(),
+            base.display()
+        );
+    }
+    Ok(parts.join("/"))
+}
+
+/// Le chemin contient-il des caractères génériques ?
+fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?'])
+}
+
+/// Lire une liste de chemins ou de motifs, un par ligne (`-`: entrée standard).
+/// Les lignes vides et les commentaires (`#`) sont ignorés.
+fn read_list(list: &Path) -> Result<Vec<String>> {
+    if list == Path::new(STDIN) {
+        return parse_list(std::io::stdin().lock())
+            .context("Impossible de lire la liste de fichiers sur l'entrée standard");
+    }
+    let file = fs::File::open(list).with_context(|| {
+        format!(
+            "Impossible de lire la liste de fichiers: {}",
+            list.display()
+        )
+    })?;
+    parse_list(BufReader::new(file)).with_context(|| {
+        format!(
+            "Impossible de lire la liste de fichiers: {}",
+            list.display()
+        )
+    })
+}
+
+fn parse_list(reader: impl BufRead) -> std::io::Result<Vec<String>> {
+    let mut entries = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let entry = line.trim();
+        if !entry.is_empty() && !entry.starts_with('#') {
+            entries.push(entry.to_string());
+        }
+    }
+    Ok(entries)
+}
+
+/// Chemins existants désignés par `pattern` (lui-même s'il n'a pas de caractère générique)
+fn expand(pattern: &str) -> Result<Vec<PathBuf>> {
+    if !is_pattern(pattern) {
+        let path = PathBuf::from(pattern);
+        return Ok(if fs::symlink_metadata(&path).is_ok() {
+            vec![path]
+        } else {
+            Vec::new()
+        });
+    }
+
+    let (start, rest) = match pattern.strip_prefix('/') {
+        Some(rest) => (PathBuf::from("/"), rest),
+        None => (PathBuf::new(), pattern),
+    };
+    let components: Vec<&str> = rest
+        .split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect();
+
+    let mut matches = Vec::new();
+    walk(&start, &components, &mut matches)
+        .with_context(|| format!("Développement du motif échoué: {}", pattern))?;
+    Ok(matches)
+}
+
+fn walk(path: &Path, components: &[&str], matches: &mut Vec<PathBuf>) -> Result<()> {
+    let Some((component, rest)) = components.split_first() else {
+        if !path.as_os_str().is_empty() {
+            matches.push(path.to_path_buf());
+        }
+        return Ok(());
+    };
+
+    if *component == "**" {
+        // Zéro répertoire, puis chaque sous-répertoire (liens non suivis, contre les boucles)
+        walk(path, rest, matches)?;
+        for (name, child) in children(path)? {
+            let is_dir = fs::symlink_metadata(&child).is_ok_and(|m| m.is_dir());
+            if is_dir && !name.starts_with('.') {
+                walk(&child, components, matches)?;
+            }
+        }
+    } else if !is_pattern(component) {
+        let child = path.join(component);
+        if fs::symlink_metadata(&child).is_ok() {
+            walk(&child, rest, matches)?;
+        }
+    } else {
+        for (name, child) in children(path)? {
+            if name.starts_with('.') && !component.starts_with('.') {
+                continue;
+            }
+            if wildcard_match(component, &name) && (rest.is_empty() || child.is_dir()) {
+                walk(&child, rest, matches)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Entrées d'un répertoire triées par nom (aucune si ce n'est pas un répertoire)
+fn children(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let listed = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    if !listed.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut children: Vec<_> = fs::read_dir(listed)
+        .with_context(|| format!("Impossible de lire le répertoire: {}", listed.display()))?
+        .map(|entry| {
+            entry.map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let child = dir.join(&name);
+                (name, child)
+            })
+        })
+        .collect::<std::io::Result<_>>()
+        .with_context(|| format!("Impossible de lire le répertoire: {}", listed.display()))?;
+    children.sort();
+    Ok(children)
+}
+
+/// Retirer les doublons et les chemins déjà inclus par un répertoire retenu
+fn dedup(sources: Vec<PathBuf>) -> Vec<PathBuf> {
+    let absolutes: Vec<PathBuf> = sources.iter().map(|source| absolute(source)).collect();
+    let dirs: Vec<&PathBuf> = sources
+        .iter()
+        .zip(&absolutes)
+        .filter(|(source, _)| source.is_dir())
+        .map(|(_, absolute)| absolute)
+        .collect();
+
+    let mut seen = Vec::new();
+    let mut kept = Vec::new();
+    for (source, absolute) in sources.iter().zip(&absolutes) {
+        let covered = dirs
+            .iter()
+            .any(|dir| absolute != *dir && absolute.starts_with(dir));
+        if covered || seen.contains(absolute) {
+            log::debug!("Source en double ignorée: {}", source.display());
+            continue;
+        }
+        seen.push(absolute.clone());
+        kept.push(source.clone());
+    }
+    kept
+}
+
+/// Deux sources ne peuvent pas aboutir au même chemin distant
+fn check_remote_paths(sources: &[PathBuf], base: Option<&Path>) -> Result<()> {
+    let mut remotes: HashMap<String, &PathBuf> = HashMap::new();
+    for source in sources {
+        let remote = remote_root(source, base)?;
+        if let Some(other) = remotes.insert(remote.clone(), source) {
+            anyhow::bail!(
+                "❌ {} et {} seraient téléversés au même endroit ({}): utilisez --base",
+                other.display(),
+                source.display(),
+                remote
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Chemin absolu normalisé sans résoudre les liens (`.` et `..` retirés)
+fn absolute(path: &Path) -> PathBuf {
+    let joined = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn build_dir() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let build = temp.path().join("build");
+        fs::create_dir_all(build.join("libs/ext")).unwrap();
+        fs::create_dir_all(build.join(".cache")).unwrap();
+        fs::write(build.join("app.jar"), "app").unwrap();
+        fs::write(build.join("libs/core.jar"), "core").unwrap();
+        fs::write(build.join("libs/ext/plugin.jar"), "plugin").unwrap();
+        fs::write(build.join("libs/README"), "readme").unwrap();
+        fs::write(build.join(".cache/tmp.jar"), "tmp").unwrap();
+        temp
+    }
+
+    fn relative_to(root: &Path, paths: &[PathBuf]) -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_recursive_glob() {
+        let temp = build_dir();
+        let root = temp.path();
+
+        let jars = expand(&format!("{}/build/**/*.jar", root.display())).unwrap();
+        // `**` ne descend pas dans les répertoires cachés
+        assert_eq!(
+            relative_to(root, &jars),
+            vec![
+                "build/app.jar",
+                "build/libs/core.jar",
+                "build/libs/ext/plugin.jar"
+            ]
+        );
+
+        let direct = expand(&format!("{}/build/*/*", root.display())).unwrap();
+        assert_eq!(
+            relative_to(root, &direct),
+            vec!["build/libs/README", "build/libs/core.jar", "build/libs/ext"]
+        );
+        assert!(expand(&format!("{}/build/*.war", root.display()))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_collect_dedups_and_reports_missing() {
+        let temp = build_dir();
+        let build = temp.path().join("build");
+        let arg = |p: &str| build.join(p).to_string_lossy().into_owned();
+
+        // Doublon exact, et fichier déjà inclus par le répertoire `libs`
+        let sources = collect(
+            &[
+                arg("libs"),
+                arg("app.jar"),
+                arg("libs/core.jar"),
+                arg("app.jar"),
+            ],
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(sources, vec![build.join("libs"), build.join("app.jar")]);
+
+        let error = collect(
+            &[arg("*.war"), arg("missing.txt"), arg("app.jar")],
+            None,
+            None,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("2 source(s) introuvable(s)"));
+        assert!(error.contains("*.war (aucune correspondance)"));
+        assert!(error.contains("missing.txt (introuvable)"));
+    }
+
+    #[test]
+    fn test_remote_paths_relative_to_base() {
+        let temp = build_dir();
+        let build = temp.path().join("build");
+
+        assert_eq!(
+            remote_root(&build.join("libs/ext/plugin.jar"), Some(&build)).unwrap(),
+            "libs/ext/plugin.jar"
+        );
+        assert_eq!(
+            remote_root(&build.join("libs/./ext/../core.jar"), None).unwrap(),
+            "core.jar"
+        );
+        assert!(remote_root(&build.join("app.jar"), Some(&build.join("libs"))).is_err());
+        assert!(remote_root(&build, Some(&build)).is_err());
+
+        // Sans --base, deux fichiers de même nom entreraient en collision
+        let jars = [format!("{}/**/*.jar", build.display())];
+        assert!(collect(&jars, None, None).is_ok());
+        fs::write(build.join("libs/ext/app.jar"), "other").unwrap();
+        assert!(collect(&jars, None, None)
+            .unwrap_err()
+            .to_string()
+            .contains("--base"));
+        assert_eq!(collect(&jars, None, Some(&build)).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_parse_list_skips_blanks_and_comments() {
+        let list = "build/app.jar\n\n# commentaire\n  build/**/*.so  \r\n";
+        assert_eq!(
+            parse_list(list.as_bytes()).unwrap(),
+            vec!["build/app.jar", "build/**/*.so"]
+        );
+    }
+}
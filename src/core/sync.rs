@@ -0,0 +1,509 @@
+// Synchronisation miroir d'un répertoire local (xsshend sync)
+//
+// Le contenu du répertoire local est comparé, serveur par serveur, à celui de la
+// destination : fichiers à ajouter, à mettre à jour (taille différente ou copie
+// distante plus ancienne que le fichier local) et éléments distants absents en
+// local, supprimés seulement avec --delete. Un élément distant d'un autre type que
+// l'élément local (fichier devenu répertoire...) est supprimé avant l'envoi avec
+// --delete, signalé en conflit sinon. Les chemins exclus ne sont jamais touchés.
+use crate::core::tree::{TreeEntry, TreeOptions, UploadTree};
+use crate::ssh::client::{RemoteEntry, RemoteKind};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// Changement appliqué à un élément distant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncAction {
+    Add,
+    Update,
+    Delete,
+    Conflict,
+}
+
+impl SyncAction {
+    /// Symbole affiché devant le chemin dans le plan
+    pub fn symbol(self) -> char {
+        match self {
+            SyncAction::Add => '+',
+            SyncAction::Update => '~',
+            SyncAction::Delete => '-',
+            SyncAction::Conflict => '!',
+        }
+    }
+}
+
+impl fmt::Display for SyncAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncAction::Add => write!(f, "ajout"),
+            SyncAction::Update => write!(f, "mise à jour"),
+            SyncAction::Delete => write!(f, "suppression"),
+            SyncAction::Conflict => write!(f, "conflit"),
+        }
+    }
+}
+
+/// Changements à appliquer sur un serveur
+#[derive(Debug, Clone, Default)]
+pub struct SyncPlan {
+    /// Éléments à créer ou remplacer, chemins relatifs à la destination,
+    /// dans l'ordre de création (un répertoire avant son contenu)
+    pub uploads: Vec<(SyncAction, TreeEntry)>,
+    /// Éléments distants d'un autre type que l'élément local, supprimés avant
+    /// les envois (--delete), le contenu avant son répertoire
+    pub replaced: Vec<RemoteEntry>,
+    /// Chemins d'un type différent en local et sur le serveur, laissés en
+    /// place faute de --delete
+    pub conflicts: Vec<String>,
+    /// Éléments distants absents en local, le contenu avant son répertoire
+    pub extraneous: Vec<RemoteEntry>,
+    /// Fichiers déjà à jour
+    pub unchanged: usize,
+}
+
+impl SyncPlan {
+    /// Comparer l'arborescence locale au contenu distant de la destination.
+    /// Avec `delete`, un élément distant d'un autre type est remplacé.
+    pub fn build(
+        tree: &UploadTree,
+        remote: &[RemoteEntry],
+        options: &TreeOptions,
+        delete: bool,
+    ) -> Self {
+        let remote_kinds: HashMap<&str, &RemoteKind> = remote
+            .iter()
+            .map(|entry| (entry.path.as_str(), &entry.kind))
+            .collect();
+        let mut plan = Self::default();
+        let mut local_paths = HashSet::new();
+        // Chemins dont le type change: remplacés (--delete) ou en conflit
+        let mut retyped: Vec<String> = Vec::new();
+
+        // Un répertoire contenant un élément protégé ne peut pas être remplacé
+        let mut protected_dirs = HashSet::new();
+        for entry in remote {
+            if options.is_protected(&entry.path, entry.kind == RemoteKind::Dir) {
+                for (end, _) in entry.path.match_indices('/') {
+                    protected_dirs.insert(&entry.path[..end]);
+                }
+            }
+        }
+
+        for entry in &tree.entries {
+            let entry = relative_entry(entry);
+            let path = entry.remote();
+            // La racine est la destination elle-même
+            if path.is_empty() {
+                continue;
+            }
+            // Contenu d'un répertoire local en conflit: rien ne peut être créé dessous
+            if is_within(path, &plan.conflicts) {
+                continue;
+            }
+            local_paths.insert(path.to_string());
+
+            let action = match (&entry, remote_kinds.get(path)) {
+                (_, None) => Some(SyncAction::Add),
+                (_, Some(kind)) if !same_kind(&entry, kind) => {
+                    retyped.push(path.to_string());
+                    if delete && !protected_dirs.contains(path) {
+                        Some(SyncAction::Add)
+                    } else {
+                        plan.conflicts.push(path.to_string());
+                        continue;
+                    }
+                }
+                (TreeEntry::Dir { .. }, Some(RemoteKind::Dir)) => None,
+                (
+                    TreeEntry::File { local, size, .. },
+                    Some(RemoteKind::File {
+                        size: remote_size,
+                        mtime,
+                    }),
+                ) => is_outdated(local, *size, *remote_size, *mtime).then_some(SyncAction::Update),
+                (
+                    TreeEntry::Symlink { target, .. },
+                    Some(RemoteKind::Symlink {
+                        target: remote_target,
+                    }),
+                ) => (target != remote_target).then_some(SyncAction::Update),
+                _ => Some(SyncAction::Update),
+            };
+            match action {
+                Some(action) => plan.uploads.push((action, entry)),
+                None if matches!(entry, TreeEntry::File { .. }) => plan.unchanged += 1,
+                None => {}
+            }
+        }
+
+        // Un répertoire contenant un élément conservé (local ou protégé) reste en place
+        let mut kept_dirs = HashSet::new();
+        for entry in remote {
+            let is_dir = entry.kind == RemoteKind::Dir;
+            if local_paths.contains(&entry.path) || options.is_protected(&entry.path, is_dir) {
+                for (end, _) in entry.path.match_indices('/') {
+                    kept_dirs.insert(&entry.path[..end]);
+                }
+            }
+        }
+
+        // Ordre inverse: "a/b" avant "a", le contenu est supprimé avant son répertoire
+        for entry in remote.iter().rev() {
+            let is_dir = entry.kind == RemoteKind::Dir;
+            if is_within(&entry.path, &retyped) {
+                // Remplacé avec tout son contenu, ou laissé tel quel si en conflit
+                if !is_within(&entry.path, &plan.conflicts) {
+                    plan.replaced.push(entry.clone());
+                }
+                continue;
+            }
+            if local_paths.contains(&entry.path)
+                || options.is_protected(&entry.path, is_dir)
+                || (is_dir && kept_dirs.contains(entry.path.as_str()))
+            {
+                continue;
+            }
+            plan.extraneous.push(entry.clone());
+        }
+        plan
+    }
+
+    /// Nombre d'éléments concernés par `action`
+    pub fn count(&self, action: SyncAction) -> usize {
+        self.paths(action).len()
+    }
+
+    /// Chemins relatifs concernés par `action`
+    pub fn paths(&self, action: SyncAction) -> Vec<&str> {
+        match action {
+            SyncAction::Delete => self
+                .replaced
+                .iter()
+                .chain(&self.extraneous)
+                .map(|entry| entry.path.as_str())
+                .collect(),
+            SyncAction::Conflict => self.conflicts.iter().map(String::as_str).collect(),
+            _ => self
+                .uploads
+                .iter()
+                .filter(|(entry_action, _)| *entry_action == action)
+                .map(|(_, entry)| entry.remote())
+                .collect(),
+        }
+    }
+
+    /// Octets à envoyer
+    pub fn upload_size(&self) -> u64 {
+        self.uploads
+            .iter()
+            .map(|(_, entry)| match entry {
+                TreeEntry::File { size, .. } => *size,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Rien à faire (les suppressions ne comptent qu'avec --delete)
+    pub fn is_empty(&self, delete: bool) -> bool {
+        self.uploads.is_empty()
+            && self.replaced.is_empty()
+            && self.conflicts.is_empty()
+            && (!delete || self.extraneous.is_empty())
+    }
+}
+
+/// Même type d'élément en local et sur le serveur
+fn same_kind(entry: &TreeEntry, kind: &RemoteKind) -> bool {
+    matches!(
+        (entry, kind),
+        (TreeEntry::Dir { .. }, RemoteKind::Dir)
+            | (TreeEntry::File { .. }, RemoteKind::File { .. })
+            | (TreeEntry::Symlink { .. }, RemoteKind::Symlink { .. })
+    )
+}
+
+/// `path` est l'un des chemins `roots` ou se trouve sous l'un d'eux
+fn is_within(path: &str, roots: &[String]) -> bool {
+    roots.iter().any(|root| {
+        path.strip_prefix(root.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Élément avec un chemin relatif à la destination: `dist/a/b` devient `a/b`
+fn relative_entry(entry: &TreeEntry) -> TreeEntry {
+    let relative = entry
+        .remote()
+        .split_once('/')
+        .map(|(_, rest)| rest)
+        .unwrap_or_default()
+        .to_string();
+    match entry.clone() {
+        TreeEntry::Dir { .. } => TreeEntry::Dir { remote: relative },
+        TreeEntry::File { local, size, .. } => TreeEntry::File {
+            local,
+            remote: relative,
+            size,
+        },
+        TreeEntry::Symlink { target, .. } => TreeEntry::Symlink {
+            remote: relative,
+            target,
+        },
+    }
+}
+
+/// Taille différente, ou copie distante plus ancienne que le fichier local
+fn is_outdated(local: &Path, size: u64, remote_size: u64, remote_mtime: Option<u32>) -> bool {
+    let local_mtime = std::fs::metadata(local)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|mtime| mtime.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|mtime| mtime.as_secs());
+    match (local_mtime, remote_mtime) {
+        (Some(local_mtime), Some(remote_mtime)) => {
+            size != remote_size || u64::from(remote_mtime) < local_mtime
+        }
+        _ => true,
+    }
+}
+
+/// Résultat d'un changement sur un serveur
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncRecord {
+    pub host: String,
+    /// Absent si le serveur n'a pas pu être comparé
+    pub action: Option<SyncAction>,
+    /// Chemin distant complet
+    pub remote: Option<String>,
+    pub success: bool,
+    pub bytes: u64,
+    pub error: Option<String>,
+}
+
+impl SyncRecord {
+    pub fn succeeded(host: &str, action: SyncAction, remote: String, bytes: u64) -> Self {
+        Self {
+            host: host.to_string(),
+            action: Some(action),
+            remote: Some(remote),
+            success: true,
+            bytes,
+            error: None,
+        }
+    }
+
+    pub fn failed(
+        host: &str,
+        action: Option<SyncAction>,
+        remote: Option<String>,
+        error: &anyhow::Error,
+    ) -> Self {
+        Self {
+            host: host.to_string(),
+            action,
+            remote,
+            success: false,
+            bytes: 0,
+            error: Some(format!("{:#}", error)),
+        }
+    }
+}
+
+/// Décompte des changements appliqués
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub failed: usize,
+    pub bytes: u64,
+}
+
+impl SyncSummary {
+    pub fn of(records: &[SyncRecord]) -> Self {
+        let mut summary = Self::default();
+        for record in records {
+            match (record.success, record.action) {
+                (false, _) | (true, None) | (true, Some(SyncAction::Conflict)) => {
+                    summary.failed += 1
+                }
+                (true, Some(SyncAction::Add)) => summary.added += 1,
+                (true, Some(SyncAction::Update)) => summary.updated += 1,
+                (true, Some(SyncAction::Delete)) => summary.deleted += 1,
+            }
+            summary.bytes += record.bytes;
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn remote_file(path: &str, size: u64, mtime: u32) -> RemoteEntry {
+        RemoteEntry {
+            path: path.to_string(),
+            kind: RemoteKind::File {
+                size,
+                mtime: Some(mtime),
+            },
+        }
+    }
+
+    fn remote_dir(path: &str) -> RemoteEntry {
+        RemoteEntry {
+            path: path.to_string(),
+            kind: RemoteKind::Dir,
+        }
+    }
+
+    #[test]
+    fn test_plan_adds_updates_and_deletes() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("site");
+        fs::create_dir_all(root.join("css")).unwrap();
+        fs::write(root.join("index.html"), "<html>").unwrap();
+        fs::write(root.join("about.html"), "about").unwrap();
+        fs::write(root.join("css/site.css"), "body{}").unwrap();
+        let options = TreeOptions {
+            exclude: vec!["uploads".to_string()],
+            ..Default::default()
+        };
+        let tree = UploadTree::scan(&root, &options).unwrap();
+
+        let future = u32::MAX;
+        let mut remote = vec![
+            remote_dir("css"),
+            remote_file("css/site.css", 6, future),
+            remote_file("index.html", 3, future),
+            remote_dir("old"),
+            remote_file("old/page.html", 1, future),
+            remote_file("stale.html", 1, future),
+            remote_dir("uploads"),
+            remote_file("uploads/photo.jpg", 9, future),
+        ];
+        remote.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let plan = SyncPlan::build(&tree, &remote, &options, false);
+        assert_eq!(plan.paths(SyncAction::Add), vec!["about.html"]);
+        assert_eq!(plan.paths(SyncAction::Update), vec!["index.html"]);
+        assert_eq!(plan.unchanged, 1);
+        // Contenu avant répertoire, chemins exclus conservés
+        assert_eq!(
+            plan.paths(SyncAction::Delete),
+            vec!["stale.html", "old/page.html", "old"]
+        );
+        assert_eq!(plan.upload_size(), 5 + 6);
+        assert!(!plan.is_empty(false));
+    }
+
+    #[test]
+    fn test_plan_keeps_dirs_holding_protected_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("plugins");
+        fs::create_dir_all(&root).unwrap();
+        let options = TreeOptions {
+            exclude: vec!["*.local".to_string()],
+            ..Default::default()
+        };
+        let tree = UploadTree::scan(&root, &options).unwrap();
+
+        let remote = vec![
+            remote_dir("legacy"),
+            remote_file("legacy/settings.local", 1, 0),
+            remote_file("legacy/x.jar", 1, 0),
+        ];
+        let plan = SyncPlan::build(&tree, &remote, &options, false);
+        assert_eq!(plan.paths(SyncAction::Delete), vec!["legacy/x.jar"]);
+        assert!(plan.is_empty(false));
+    }
+
+    #[test]
+    fn test_plan_replaces_changed_types_only_with_delete() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("app");
+        fs::create_dir_all(root.join("conf")).unwrap();
+        fs::write(root.join("conf/app.yml"), "a: 1").unwrap();
+        fs::write(root.join("logs"), "").unwrap();
+        let options = TreeOptions::default();
+        let tree = UploadTree::scan(&root, &options).unwrap();
+
+        // conf était un fichier, logs un répertoire
+        let remote = vec![
+            remote_file("conf", 1, 0),
+            remote_dir("logs"),
+            remote_file("logs/app.log", 1, 0),
+        ];
+
+        let plan = SyncPlan::build(&tree, &remote, &options, true);
+        assert_eq!(
+            plan.paths(SyncAction::Add),
+            vec!["conf", "conf/app.yml", "logs"]
+        );
+        // Suppressions prévues avant les envois, contenu avant son répertoire
+        assert_eq!(
+            plan.replaced
+                .iter()
+                .map(|entry| entry.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["logs/app.log", "logs", "conf"]
+        );
+        assert!(plan.extraneous.is_empty());
+        assert!(plan.conflicts.is_empty());
+
+        // Sans --delete: conflits, rien d'envoyé ni de supprimé à ces chemins
+        let plan = SyncPlan::build(&tree, &remote, &options, false);
+        assert_eq!(plan.paths(SyncAction::Conflict), vec!["conf", "logs"]);
+        assert!(plan.uploads.is_empty());
+        assert!(plan.paths(SyncAction::Delete).is_empty());
+        assert!(!plan.is_empty(false));
+    }
+
+    #[test]
+    fn test_plan_never_replaces_dir_holding_protected_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("site");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("uploads"), "").unwrap();
+        let options = TreeOptions {
+            exclude: vec!["*.jpg".to_string()],
+            ..Default::default()
+        };
+        let tree = UploadTree::scan(&root, &options).unwrap();
+
+        let remote = vec![
+            remote_dir("uploads"),
+            remote_file("uploads/photo.jpg", 9, 0),
+        ];
+        let plan = SyncPlan::build(&tree, &remote, &options, true);
+        assert_eq!(plan.paths(SyncAction::Conflict), vec!["uploads"]);
+        assert!(plan.replaced.is_empty());
+        assert!(plan.uploads.is_empty());
+    }
+
+    #[test]
+    fn test_summary_counts_actions() {
+        let error = anyhow::anyhow!("Permission denied");
+        let records = vec![
+            SyncRecord::succeeded("WEB_01", SyncAction::Add, "/srv/a".to_string(), 10),
+            SyncRecord::succeeded("WEB_01", SyncAction::Delete, "/srv/b".to_string(), 0),
+            SyncRecord::failed("WEB_02", None, None, &error),
+        ];
+        assert_eq!(
+            SyncSummary::of(&records),
+            SyncSummary {
+                added: 1,
+                updated: 0,
+                deleted: 1,
+                failed: 1,
+                bytes: 10,
+            }
+        );
+    }
+}
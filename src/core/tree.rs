@@ -0,0 +1,420 @@
+// Parcours récursif des répertoires à téléverser
+//
+// Un répertoire source `dist` est recréé sous la destination (`/srv/app/dist/...`),
+// comme avec `scp -r`. Les chemins produits sont relatifs à la destination et
+// utilisent toujours `/`.
+use crate::utils::pattern::wildcard_match;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Traitement des liens symboliques rencontrés dans l'arborescence
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Téléverser la cible du lien (fichier ou répertoire)
+    #[default]
+    Follow,
+    /// Recréer le lien sur le serveur, avec la même cible
+    Link,
+    /// Ignorer les liens
+    Skip,
+}
+
+impl fmt::Display for SymlinkPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymlinkPolicy::Follow => write!(f, "follow"),
+            SymlinkPolicy::Link => write!(f, "link"),
+            SymlinkPolicy::Skip => write!(f, "skip"),
+        }
+    }
+}
+
+impl FromStr for SymlinkPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "follow" => Ok(SymlinkPolicy::Follow),
+            "link" => Ok(SymlinkPolicy::Link),
+            "skip" => Ok(SymlinkPolicy::Skip),
+            other => Err(format!(
+                "politique de liens inconnue '{}' (follow, link ou skip)",
+                other
+            )),
+        }
+    }
+}
+
+/// Options de parcours: liens symboliques et filtres
+#[derive(Debug, Clone, Default)]
+pub struct TreeOptions {
+    pub symlinks: SymlinkPolicy,
+    /// Seuls les fichiers correspondant à l'un de ces motifs sont envoyés (si non vide)
+    pub include: Vec<String>,
+    /// Fichiers et répertoires ignorés
+    pub exclude: Vec<String>,
+}
+
+impl TreeOptions {
+    /// Un motif sans `/` porte sur le nom, sinon sur le chemin relatif à la racine
+    fn matches(pattern: &str, relative: &str) -> bool {
+        if pattern.contains('/') {
+            wildcard_match(pattern.trim_start_matches('/'), relative)
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            wildcard_match(pattern, name)
+        }
+    }
+
+    fn is_excluded(&self, relative: &str) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| Self::matches(pattern, relative))
+    }
+
+    fn is_included(&self, relative: &str) -> bool {
+        self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| Self::matches(pattern, relative))
+    }
+
+    /// Chemin hors du périmètre des filtres: exclu (lui ou un parent), fichier non
+    /// retenu par `include`, ou répertoire quand `include` est utilisé
+    pub fn is_protected(&self, relative: &str, is_dir: bool) -> bool {
+        let excluded = relative
+            .match_indices('/')
+            .map(|(end, _)| &relative[..end])
+            .chain(std::iter::once(relative))
+            .any(|prefix| self.is_excluded(prefix));
+        let included = if is_dir {
+            self.include.is_empty()
+        } else {
+            self.is_included(relative)
+        };
+        excluded || !included
+    }
+}
+
+/// Élément à créer sur le serveur
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeEntry {
+    Dir {
+        remote: String,
+    },
+    File {
+        local: PathBuf,
+        remote: String,
+        size: u64,
+    },
+    Symlink {
+        remote: String,
+        target: String,
+    },
+}
+
+impl TreeEntry {
+    /// Chemin relatif à la destination
+    pub fn remote(&self) -> &str {
+        match self {
+            TreeEntry::Dir { remote }
+            | TreeEntry::File { remote, .. }
+            | TreeEntry::Symlink { remote, .. } => remote,
+        }
+    }
+}
+
+/// Arborescence d'un répertoire source, prête à être recréée
+#[derive(Debug, Clone)]
+pub struct UploadTree {
+    /// Dans l'ordre de création: un répertoire précède toujours son contenu
+    pub entries: Vec<TreeEntry>,
+}
+
+impl UploadTree {
+    /// Parcourir `root` selon `options`
+    pub fn scan(root: &Path, options: &TreeOptions) -> Result<Self> {
+        // `.` ou `..` n'ont pas de nom: prendre celui du répertoire réel
+        let name = root
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .or_else(|| {
+                root.canonicalize()
+                    .ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            })
+            .with_context(|| format!("Nom de répertoire invalide: {}", root.display()))?;
+        Self::scan_as(root, &name, options)
+    }
+
+    /// Parcourir `root` en le recréant sous le chemin relatif `name` (--base)
+    pub fn scan_as(root: &Path, name: &str, options: &TreeOptions) -> Result<Self> {
+        let mut walker = Walker {
+            options,
+            entries: Vec::new(),
+            ancestors: Vec::new(),
+        };
+        walker.visit_dir(root, name, "")?;
+
+        Ok(Self {
+            entries: walker.entries,
+        })
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files().count()
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.files().map(|(_, _, size)| size).sum()
+    }
+
+    /// Fichiers à envoyer: (local, distant relatif, taille)
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &str, u64)> {
+        self.entries.iter().filter_map(|entry| match entry {
+            TreeEntry::File {
+                local,
+                remote,
+                size,
+            } => Some((local.as_path(), remote.as_str(), *size)),
+            _ => None,
+        })
+    }
+}
+
+struct Walker<'a> {
+    options: &'a TreeOptions,
+    entries: Vec<TreeEntry>,
+    /// Répertoires en cours de parcours (chemins canoniques), contre les boucles de liens
+    ancestors: Vec<PathBuf>,
+}
+
+impl Walker<'_> {
+    /// `remote`: chemin relatif à la destination, `relative`: chemin relatif à la racine
+    fn visit_dir(&mut self, dir: &Path, remote: &str, relative: &str) -> Result<()> {
+        let canonical = dir
+            .canonicalize()
+            .with_context(|| format!("Répertoire inaccessible: {}", dir.display()))?;
+        if self.ancestors.contains(&canonical) {
+            log::warn!("⚠️  Boucle de liens symboliques ignorée: {}", dir.display());
+            return Ok(());
+        }
+
+        // Sans filtre d'inclusion, les répertoires vides sont recréés aussi
+        if self.options.include.is_empty() {
+            self.entries.push(TreeEntry::Dir {
+                remote: remote.to_string(),
+            });
+        }
+
+        let mut children: Vec<_> = fs::read_dir(dir)
+            .with_context(|| format!("Impossible de lire le répertoire: {}", dir.display()))?
+            .collect::<std::io::Result<_>>()
+            .with_context(|| format!("Impossible de lire le répertoire: {}", dir.display()))?;
+        children.sort_by_key(|child| child.file_name());
+
+        self.ancestors.push(canonical);
+        for child in children {
+            let name = child.file_name().to_string_lossy().into_owned();
+            let child_remote = format!("{}/{}", remote, name);
+            let child_relative = if relative.is_empty() {
+                name
+            } else {
+                format!("{}/{}", relative, name)
+            };
+            self.visit(&child.path(), &child_remote, &child_relative)?;
+        }
+        self.ancestors.pop();
+
+        Ok(())
+    }
+
+    fn visit(&mut self, path: &Path, remote: &str, relative: &str) -> Result<()> {
+        if self.options.is_excluded(relative) {
+            log::debug!("Exclu: {}", relative);
+            return Ok(());
+        }
+
+        let link_metadata = fs::symlink_metadata(path)
+            .with_context(|| format!("Impossible de lire: {}", path.display()))?;
+
+        if link_metadata.file_type().is_symlink() {
+            match self.options.symlinks {
+                SymlinkPolicy::Skip => {
+                    log::debug!("Lien ignoré: {}", relative);
+                    return Ok(());
+                }
+                SymlinkPolicy::Link => {
+                    if self.options.is_included(relative) {
+                        let target = fs::read_link(path)
+                            .with_context(|| format!("Lien illisible: {}", path.display()))?;
+                        self.entries.push(TreeEntry::Symlink {
+                            remote: remote.to_string(),
+                            target: target.to_string_lossy().into_owned(),
+                        });
+                    }
+                    return Ok(());
+                }
+                SymlinkPolicy::Follow => {}
+            }
+        }
+
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                log::warn!("⚠️  Lien cassé ignoré: {} ({})", path.display(), e);
+                return Ok(());
+            }
+        };
+
+        if metadata.is_dir() {
+            self.visit_dir(path, remote, relative)
+        } else if metadata.is_file() {
+            if self.options.is_included(relative) {
+                self.entries.push(TreeEntry::File {
+                    local: path.to_path_buf(),
+                    remote: remote.to_string(),
+                    size: metadata.len(),
+                });
+            }
+            Ok(())
+        } else {
+            log::warn!("⚠️  Fichier spécial ignoré: {}", path.display());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_tree() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("dist");
+        fs::create_dir_all(root.join("assets/img")).unwrap();
+        fs::create_dir_all(root.join("node_modules/lib")).unwrap();
+        fs::create_dir_all(root.join("empty")).unwrap();
+        fs::write(root.join("index.html"), "<html>").unwrap();
+        fs::write(root.join("assets/app.js"), "js").unwrap();
+        fs::write(root.join("assets/app.js.map"), "map").unwrap();
+        fs::write(root.join("assets/img/logo.png"), "png").unwrap();
+        fs::write(root.join("node_modules/lib/x.js"), "x").unwrap();
+        temp
+    }
+
+    fn remotes(tree: &UploadTree) -> Vec<&str> {
+        tree.entries.iter().map(TreeEntry::remote).collect()
+    }
+
+    #[test]
+    fn test_scan_recreates_tree_under_root_name() {
+        let temp = sample_tree();
+        let tree = UploadTree::scan(&temp.path().join("dist"), &TreeOptions::default()).unwrap();
+
+        assert_eq!(
+            remotes(&tree),
+            vec![
+                "dist",
+                "dist/assets",
+                "dist/assets/app.js",
+                "dist/assets/app.js.map",
+                "dist/assets/img",
+                "dist/assets/img/logo.png",
+                "dist/empty",
+                "dist/index.html",
+                "dist/node_modules",
+                "dist/node_modules/lib",
+                "dist/node_modules/lib/x.js",
+            ]
+        );
+        assert_eq!(tree.file_count(), 5);
+        assert_eq!(tree.total_size(), 6 + 2 + 3 + 3 + 1);
+    }
+
+    #[test]
+    fn test_include_and_exclude_filters() {
+        let temp = sample_tree();
+        let options = TreeOptions {
+            include: vec!["*.js".to_string(), "assets/img/*".to_string()],
+            exclude: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+        let tree = UploadTree::scan(&temp.path().join("dist"), &options).unwrap();
+
+        // Avec --include, seuls les fichiers retenus (et leurs parents) sont créés
+        assert_eq!(
+            remotes(&tree),
+            vec!["dist/assets/app.js", "dist/assets/img/logo.png"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policies() {
+        let temp = sample_tree();
+        let root = temp.path().join("dist");
+        std::os::unix::fs::symlink("index.html", root.join("home.html")).unwrap();
+        std::os::unix::fs::symlink("..", root.join("assets/up")).unwrap();
+
+        let scan = |symlinks| {
+            let options = TreeOptions {
+                symlinks,
+                exclude: vec!["node_modules".to_string(), "empty".to_string()],
+                ..Default::default()
+            };
+            UploadTree::scan(&root, &options).unwrap()
+        };
+
+        let skipped = scan(SymlinkPolicy::Skip);
+        assert!(!remotes(&skipped).contains(&"dist/home.html"));
+
+        let linked = scan(SymlinkPolicy::Link);
+        assert!(linked.entries.contains(&TreeEntry::Symlink {
+            remote: "dist/home.html".to_string(),
+            target: "index.html".to_string(),
+        }));
+
+        // Suivi: le lien devient un fichier, la boucle vers le parent est ignorée
+        let followed = scan(SymlinkPolicy::Follow);
+        assert!(followed
+            .files()
+            .any(|(_, remote, _)| remote == "dist/home.html"));
+        assert!(!remotes(&followed)
+            .iter()
+            .any(|remote| remote.starts_with("dist/assets/up")));
+    }
+
+    #[test]
+    fn test_protected_paths() {
+        let options = TreeOptions {
+            exclude: vec!["uploads".to_string(), "*.log".to_string()],
+            ..Default::default()
+        };
+        assert!(options.is_protected("uploads", true));
+        assert!(options.is_protected("uploads/2026/photo.jpg", false));
+        assert!(options.is_protected("logs/app.log", false));
+        assert!(!options.is_protected("index.html", false));
+        assert!(!options.is_protected("assets", true));
+
+        let included = TreeOptions {
+            include: vec!["*.html".to_string()],
+            ..Default::default()
+        };
+        assert!(!included.is_protected("blog/post.html", false));
+        assert!(included.is_protected("blog/post.css", false));
+        assert!(included.is_protected("blog", true));
+    }
+
+    #[test]
+    fn test_symlink_policy_parsing() {
+        assert_eq!("link".parse::<SymlinkPolicy>(), Ok(SymlinkPolicy::Link));
+        assert!("copy".parse::<SymlinkPolicy>().is_err());
+        assert_eq!(SymlinkPolicy::default().to_string(), "follow");
+    }
+}
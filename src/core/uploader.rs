@@ -3,120 +3,530 @@
 // Changements v0.6.0 :
 //   - ConnectionPool : les connexions SSH sont réutilisées entre les fichiers
 //     5 fichiers → 3 serveurs = 3 connexions au lieu de 15
-//   - Buffer SFTP 256KB (était 64KB) : meilleur débit sur connexions à haute latence
+//   - Écritures SFTP par blocs de 64KB; depuis, plusieurs blocs en vol à la fois
+//     (ssh::pipeline, --requests / --chunk-size) pour les liens à haute latence
+//
+// Chaque couple serveur × fichier est un travail : les serveurs avancent chacun à
+// leur rythme, avec plusieurs fichiers à la fois par serveur (--jobs-per-host),
+// dans la limite globale du pool (--jobs).
 
-use crate::config::HostEntry;
+use crate::config::{HostEntry, HostOptions};
+use crate::core::journal::{JournalEntry, RunJournal};
+use crate::core::output::short_name;
+use crate::core::sources;
+use crate::core::sync::{SyncAction, SyncPlan, SyncRecord, SyncSummary};
+use crate::core::tree::{TreeEntry, TreeOptions, UploadTree};
 use crate::core::validator::Validator;
+use crate::ssh::client::{RemoteEntry, RemoteKind, UploadOptions, UploadedFile};
 use crate::ssh::keys::PassphraseCache;
-use crate::ssh::pool::ConnectionPool;
+use crate::ssh::password::PasswordCache;
+use crate::ssh::pool::{Concurrency, ConnectionPool};
+use crate::ssh::retry::{FailureKind, RetryPolicy};
+use crate::ssh::target::{self, SshTarget};
+use crate::utils::interrupt;
 use crate::utils::path_expansion;
+use crate::utils::progress::{TransferDisplay, TransferProgress};
 use anyhow::{Context, Result};
 use futures::stream::{self, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
-use std::path::Path;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 pub struct Uploader {
     pool: ConnectionPool,
+    /// Installer les fichiers en tant que cet utilisateur via sudo (--become)
+    become_user: Option<String>,
+    /// Parcours des répertoires: liens symboliques et filtres
+    tree_options: TreeOptions,
+    /// Écriture des fichiers distants (atomique par défaut)
+    upload_options: UploadOptions,
+    /// Nouvelles tentatives sur erreur réseau passagère (--retries)
+    retry: RetryPolicy,
+    /// Transferts simultanés, au total et par serveur (--jobs, --jobs-per-host)
+    concurrency: Concurrency,
+    /// Répertoire local dont les chemins distants sont relatifs (--base)
+    base: Option<PathBuf>,
+    /// Pas de messages sur stdout (sortie JSON)
+    quiet: bool,
+}
+
+/// Résultat d'un fichier sur un serveur
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadRecord {
+    pub host: String,
+    /// Chemin local du fichier
+    pub file: String,
+    /// Chemin distant (absent si le serveur n'a pas pu être joint)
+    pub remote: Option<String>,
+    pub success: bool,
+    pub bytes: u64,
+    /// SHA-256 du contenu envoyé
+    pub sha256: Option<String>,
+    /// Empreinte recalculée sur le serveur et identique (--verify)
+    pub verified: bool,
+    /// Déjà identique sur le serveur, non renvoyé (--skip-unchanged)
+    pub skipped: bool,
+    /// Copie de la version remplacée (--backup)
+    pub backup: Option<String>,
+    /// Durée du transfert, connexion et nouvelles tentatives comprises
+    pub duration_ms: u64,
+    /// Nature de l'échec
+    pub error_kind: Option<FailureKind>,
+    pub error: Option<String>,
+    /// Début de la mesure, pour la durée réelle par serveur
+    #[serde(skip)]
+    started: Option<Instant>,
+}
+
+impl UploadRecord {
+    fn succeeded(host: &str, file: &Path, remote: String, uploaded: UploadedFile) -> Self {
+        Self {
+            host: host.to_string(),
+            file: file.display().to_string(),
+            remote: Some(remote),
+            success: true,
+            bytes: uploaded.bytes,
+            sha256: Some(uploaded.sha256).filter(|digest| !digest.is_empty()),
+            verified: uploaded.verified,
+            skipped: uploaded.skipped,
+            backup: uploaded.backup,
+            duration_ms: 0,
+            error_kind: None,
+            error: None,
+            started: None,
+        }
+    }
+
+    fn failed(host: &str, file: &Path, remote: Option<String>, error: &anyhow::Error) -> Self {
+        Self {
+            host: host.to_string(),
+            file: file.display().to_string(),
+            remote,
+            success: false,
+            bytes: 0,
+            sha256: None,
+            verified: false,
+            skipped: false,
+            backup: None,
+            duration_ms: 0,
+            error_kind: Some(FailureKind::of(error)),
+            error: Some(format!("{:#}", error)),
+            started: None,
+        }
+    }
+
+    /// Durée mesurée depuis `started`
+    fn timed(mut self, started: Instant) -> Self {
+        self.duration_ms = started.elapsed().as_millis() as u64;
+        self.started = Some(started);
+        self
+    }
+
+    /// Ligne affichée au-dessus de la barre de progression
+    fn status_line(&self) -> String {
+        match &self.error {
+            None if self.skipped => format!("  ⏭️  {} (inchangé)", self.host),
+            None if self.verified => format!("  ✅ {} (SHA-256 vérifié)", self.host),
+            None => format!("  ✅ {}", self.host),
+            Some(error) => match self.error_kind {
+                Some(kind) => format!("  ❌ {} [{}] : {}", self.host, kind, error),
+                None => format!("  ❌ {} : {}", self.host, error),
+            },
+        }
+    }
+}
+
+/// Bilan d'un serveur sur l'ensemble des fichiers
+#[derive(Debug, Clone, Serialize)]
+pub struct HostOutcome {
+    pub host: String,
+    /// Tous les fichiers envoyés (ou déjà à jour)
+    pub success: bool,
+    pub files: usize,
+    pub failed: usize,
+    pub bytes: u64,
+    /// Temps réel écoulé du premier envoi commencé au dernier terminé
+    /// (les transferts parallèles se chevauchent, leurs durées ne s'additionnent pas)
+    pub duration_ms: u64,
+    /// Nature du premier échec
+    pub error_kind: Option<FailureKind>,
+}
+
+/// Rapport d'un téléversement: résultat de chaque couple serveur × fichier
+#[derive(Debug, Clone, Default)]
+pub struct UploadReport {
+    pub records: Vec<UploadRecord>,
+}
+
+impl UploadReport {
+    pub fn summary(&self) -> UploadSummary {
+        UploadSummary::of(&self.records)
+    }
+
+    /// Bilan par serveur, dans l'ordre de première apparition
+    pub fn hosts(&self) -> Vec<HostOutcome> {
+        let mut hosts: Vec<HostOutcome> = Vec::new();
+        // Première mise en route et dernière fin de chaque serveur
+        let mut spans: Vec<Option<(Instant, Instant)>> = Vec::new();
+        for record in &self.records {
+            let index = match hosts.iter().position(|host| host.host == record.host) {
+                Some(index) => index,
+                None => {
+                    hosts.push(HostOutcome {
+                        host: record.host.clone(),
+                        success: true,
+                        files: 0,
+                        failed: 0,
+                        bytes: 0,
+                        duration_ms: 0,
+                        error_kind: None,
+                    });
+                    spans.push(None);
+                    hosts.len() - 1
+                }
+            };
+            let host = &mut hosts[index];
+            host.files += 1;
+            host.bytes += record.bytes;
+            host.duration_ms = host.duration_ms.max(record.duration_ms);
+            if let Some(started) = record.started {
+                let ended = started + Duration::from_millis(record.duration_ms);
+                spans[index] = Some(match spans[index] {
+                    Some((first, last)) => (first.min(started), last.max(ended)),
+                    None => (started, ended),
+                });
+            }
+            if !record.success {
+                host.success = false;
+                host.failed += 1;
+                host.error_kind = host.error_kind.or(record.error_kind);
+            }
+        }
+        for (host, span) in hosts.iter_mut().zip(spans) {
+            if let Some((first, last)) = span {
+                host.duration_ms = host.duration_ms.max((last - first).as_millis() as u64);
+            }
+        }
+        hosts
+    }
+
+    /// Vérifier le résultat: tout doit réussir, ou au moins `min_success`
+    /// serveurs sans aucun échec
+    pub fn check(&self, min_success: Option<MinSuccess>) -> Result<()> {
+        let hosts = self.hosts();
+        let succeeded = hosts.iter().filter(|host| host.success).count();
+        match min_success {
+            None => {
+                let failed = self.summary().failed;
+                if failed > 0 {
+                    anyhow::bail!(
+                        "❌ {} téléversement(s) en échec sur {} ({} serveur(s) sur {} sans erreur)",
+                        failed,
+                        self.records.len(),
+                        succeeded,
+                        hosts.len()
+                    );
+                }
+            }
+            Some(min_success) => {
+                let required = min_success.required(hosts.len());
+                if succeeded < required {
+                    anyhow::bail!(
+                        "❌ {} serveur(s) sur {} sans erreur, minimum requis: {} (--min-success {})",
+                        succeeded,
+                        hosts.len(),
+                        required,
+                        min_success
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Nombre minimal de serveurs entièrement réussis (--min-success): `N` ou `N%`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MinSuccess {
+    Hosts(usize),
+    Percent(u8),
+}
+
+impl MinSuccess {
+    /// Serveurs requis parmi `total` (pourcentage arrondi au supérieur)
+    pub fn required(self, total: usize) -> usize {
+        match self {
+            MinSuccess::Hosts(count) => count,
+            MinSuccess::Percent(percent) => (total * usize::from(percent)).div_ceil(100),
+        }
+    }
+}
+
+impl fmt::Display for MinSuccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinSuccess::Hosts(count) => write!(f, "{}", count),
+            MinSuccess::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl FromStr for MinSuccess {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "seuil invalide '{}' (nombre de serveurs, ou pourcentage 0-100%)",
+                value
+            )
+        };
+        match value.strip_suffix('%') {
+            Some(percent) => percent
+                .parse::<u8>()
+                .ok()
+                .filter(|percent| *percent <= 100)
+                .map(MinSuccess::Percent)
+                .ok_or_else(invalid),
+            None => value
+                .parse::<usize>()
+                .map(MinSuccess::Hosts)
+                .map_err(|_| invalid()),
+        }
+    }
+}
+
+/// Résultat de la restauration d'un fichier (xsshend rollback)
+#[derive(Debug, Clone)]
+pub struct RollbackRecord {
+    pub entry: JournalEntry,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl RollbackRecord {
+    fn new(entry: &JournalEntry, result: &Result<()>) -> Self {
+        Self {
+            entry: entry.clone(),
+            success: result.is_ok(),
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        }
+    }
+}
+
+/// Plan de synchronisation d'un serveur (xsshend sync)
+pub struct HostSyncPlan {
+    pub host: String,
+    /// Destination développée (`~`, `$HOME`) sur ce serveur
+    pub destination: String,
+    pub plan: Result<SyncPlan>,
+}
+
+/// Décompte des couples serveur × fichier d'une exécution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UploadSummary {
+    pub total: usize,
+    pub sent: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// Octets effectivement transférés
+    pub bytes: u64,
+}
+
+impl UploadSummary {
+    pub fn of(records: &[UploadRecord]) -> Self {
+        let mut summary = Self {
+            total: records.len(),
+            ..Default::default()
+        };
+        for record in records {
+            if !record.success {
+                summary.failed += 1;
+            } else if record.skipped {
+                summary.skipped += 1;
+            } else {
+                summary.sent += 1;
+            }
+            summary.bytes += record.bytes;
+        }
+        summary
+    }
 }
 
 impl Uploader {
     pub fn new() -> Self {
+        Self::with_overrides(HostOptions::default())
+    }
+
+    /// Créer avec des options de connexion imposées par la CLI (--key, ...)
+    pub fn with_overrides(overrides: HostOptions) -> Self {
         Uploader {
-            pool: ConnectionPool::new(PassphraseCache::new()),
+            pool: ConnectionPool::new(PassphraseCache::new()).with_overrides(overrides),
+            become_user: None,
+            tree_options: TreeOptions::default(),
+            upload_options: UploadOptions::default(),
+            retry: RetryPolicy::default(),
+            concurrency: Concurrency::default(),
+            base: None,
+            quiet: false,
         }
     }
 
-    /// Téléverse plusieurs fichiers vers plusieurs serveurs (connexions poolées)
+    /// Partager le cache de mots de passe de l'exécution (--password-stdin, ...)
+    pub fn with_password_cache(mut self, password_cache: PasswordCache) -> Self {
+        self.pool = self.pool.with_password_cache(password_cache);
+        self
+    }
+
+    /// Écrire dans un fichier temporaire puis installer via sudo (--become)
+    pub fn with_become(mut self, user: Option<String>) -> Self {
+        self.become_user = user;
+        self
+    }
+
+    /// Options de parcours des répertoires (--symlinks, --include, --exclude)
+    pub fn with_tree_options(mut self, options: TreeOptions) -> Self {
+        self.tree_options = options;
+        self
+    }
+
+    /// Options d'écriture des fichiers distants (--in-place, ...)
+    pub fn with_upload_options(mut self, options: UploadOptions) -> Self {
+        self.upload_options = options;
+        self
+    }
+
+    /// Nouvelles tentatives par hôte sur erreur réseau passagère
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Transferts simultanés au total et par serveur
+    pub fn with_concurrency(mut self, concurrency: Concurrency) -> Self {
+        self.concurrency = concurrency.at_least_one();
+        self.pool = self.pool.with_concurrency(self.concurrency);
+        self
+    }
+
+    /// Chemins distants relatifs à ce répertoire plutôt que le seul nom des sources
+    pub fn with_base(mut self, base: Option<PathBuf>) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Sortie JSON: n'afficher ni récapitulatif ni messages de progression sur stdout
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Téléverse plusieurs fichiers ou répertoires vers plusieurs serveurs (connexions poolées).
+    /// Renvoie le résultat de chaque couple serveur × fichier; les échecs n'en font
+    /// pas une erreur, voir `UploadReport::check`.
     pub async fn upload_files(
         &self,
         files: &[&Path],
         hosts: &[(String, &HostEntry)],
         destination: &str,
-    ) -> Result<()> {
+    ) -> Result<UploadReport> {
         for file in files {
-            Validator::validate_file(file)
+            Validator::validate_source(file)
                 .with_context(|| format!("Validation échouée pour {}", file.display()))?;
         }
 
-        println!(
-            "🚀 Début du téléversement: {} fichier(s) vers {} serveur(s)",
-            files.len(),
-            hosts.len()
-        );
-        println!("📂 Destination: {}", destination);
-        println!("🎯 Serveurs ciblés:");
-        for (host_name, host_entry) in hosts {
+        if !self.quiet {
             println!(
-                "   • {} → {} ({})",
-                host_name, host_entry.alias, host_entry.env
+                "🚀 Début du téléversement: {} source(s) vers {} serveur(s)",
+                files.len(),
+                hosts.len()
             );
+            println!("📂 Destination: {}", destination);
+            if let Some(user) = &self.become_user {
+                println!("👤 Installation via sudo en tant que {}", user);
+            }
+            if self.upload_options.verify {
+                println!("🔒 Vérification SHA-256 après transfert");
+            }
+            if self.upload_options.preserve {
+                println!("🕒 Mode et date des fichiers locaux conservés");
+            }
+            if let Some(check) = self.upload_options.skip_unchanged {
+                println!("⏭️  Fichiers inchangés ignorés (comparaison: {})", check);
+            }
+            if let Some(backup) = &self.upload_options.backup {
+                println!(
+                    "💾 Sauvegarde des fichiers remplacés (exécution {})",
+                    backup.run_id
+                );
+            }
+            println!("🎯 Serveurs ciblés:");
+            for (host_name, host_entry) in hosts {
+                println!(
+                    "   • {} → {} ({})",
+                    host_name, host_entry.alias, host_entry.env
+                );
+            }
         }
 
-        let mut failed_files = Vec::new();
-
+        // Éléments à créer sur chaque serveur, relatifs à la destination
+        let mut entries = Vec::new();
         for file in files {
-            println!("\n📤 Téléversement de {} en cours...", file.display());
-
-            let progress = ProgressBar::new(hosts.len() as u64);
-            progress.set_style(ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("#>-"));
-
-            let progress_arc = Arc::new(Mutex::new(progress));
-
-            let upload_futures = hosts.iter().map(|(host_name, host_entry)| {
-                let file = file.to_owned();
-                let host_name = host_name.clone();
-                let host_entry = (*host_entry).clone();
-                let destination = destination.to_owned();
-                let pool = self.pool.clone(); // Arc clone — pool partagé
-                let progress_clone = Arc::clone(&progress_arc);
-
-                async move {
-                    {
-                        let progress = progress_clone.lock().await;
-                        progress.set_message(format!("→ {}", host_name));
-                    }
-
-                    let result =
-                        Self::upload_to_single_host_pooled(pool, file, &host_entry, &destination)
-                            .await;
-
-                    {
-                        let progress = progress_clone.lock().await;
-                        match &result {
-                            Ok(_) => progress.println(format!("  ✅ {}", host_name)),
-                            Err(e) => progress.println(format!("  ❌ {} : {}", host_name, e)),
-                        }
-                        progress.inc(1);
-                    }
-
-                    (host_name, result)
+            let remote = sources::remote_root(file, self.base.as_deref())?;
+            if file.is_dir() {
+                let tree = UploadTree::scan_as(file, &remote, &self.tree_options)
+                    .with_context(|| format!("Parcours échoué pour {}", file.display()))?;
+                if !self.quiet {
+                    println!(
+                        "📁 {} : {} fichier(s), {}",
+                        file.display(),
+                        tree.file_count(),
+                        Validator::format_file_size(tree.total_size())
+                    );
                 }
-            });
-
-            let results: Vec<_> = stream::iter(upload_futures)
-                .buffer_unordered(10)
-                .collect()
-                .await;
-
-            let file_success = results.iter().all(|(_, result)| result.is_ok());
-
-            {
-                let progress = progress_arc.lock().await;
-                progress.finish();
-            }
-
-            if file_success {
-                println!("✅ Fichier {} téléversé avec succès", file.display());
+                entries.extend(tree.entries);
             } else {
-                println!("❌ Échec partiel pour {}", file.display());
-                failed_files.push(file.display().to_string());
+                entries.push(TreeEntry::File {
+                    local: file.to_path_buf(),
+                    remote,
+                    size: Validator::get_file_size(file)?,
+                });
             }
         }
+        let tree = UploadTree { entries };
+
+        if !self.quiet {
+            println!(
+                "\n📤 {} fichier(s) ({}) vers {} serveur(s): {} transfert(s) simultané(s) au plus, {} par serveur",
+                tree.file_count(),
+                Validator::format_file_size(tree.total_size()),
+                hosts.len(),
+                self.concurrency.global,
+                self.concurrency.per_host
+            );
+        }
+        let display = TransferDisplay::new(tree.total_size() * hosts.len() as u64, self.quiet);
+        let host_uploads = hosts.iter().map(|(host_name, host_entry)| {
+            self.upload_to_host(&tree, host_name, host_entry, destination, &display)
+        });
+        let records: Vec<UploadRecord> = futures::future::join_all(host_uploads)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        display.finish();
+
+        let mut failed_files: Vec<&str> = records
+            .iter()
+            .filter(|record| !record.success)
+            .map(|record| record.file.as_str())
+            .collect();
+        failed_files.sort_unstable();
+        failed_files.dedup();
+        let failed_files: Vec<String> = failed_files.into_iter().map(str::to_string).collect();
 
         // Fermer proprement les connexions poolées après tous les transferts
         self.pool.close_all().await;
@@ -125,6 +535,14 @@ impl Uploader {
             self.pool.active_connections()
         );
 
+        if let Some(backup) = &self.upload_options.backup {
+            self.save_journal(&backup.run_id, &records);
+        }
+
+        let report = UploadReport { records };
+        if self.quiet {
+            return Ok(report);
+        }
         if failed_files.is_empty() {
             println!("\n✅ Téléversement terminé avec succès!");
         } else {
@@ -135,73 +553,732 @@ impl Uploader {
             );
             println!(
                 "📊 {} fichier(s) sur {} réussi(s)",
-                files.len() - failed_files.len(),
-                files.len()
+                tree.file_count() - failed_files.len(),
+                tree.file_count()
             );
         }
-        Ok(())
+        let summary = report.summary();
+        println!(
+            "📊 Serveur × fichier: {} envoyé(s), {} inchangé(s), {} en échec",
+            summary.sent, summary.skipped, summary.failed
+        );
+        let hosts = report.hosts();
+        println!(
+            "🖥️  Serveurs sans erreur: {}/{}",
+            hosts.iter().filter(|host| host.success).count(),
+            hosts.len()
+        );
+        Ok(report)
+    }
+
+    /// Enregistrer les fichiers remplacés dans le journal local (--backup)
+    fn save_journal(&self, run_id: &str, records: &[UploadRecord]) {
+        let mut journal = RunJournal::new(run_id.to_string(), self.become_user.clone());
+        journal.entries = records
+            .iter()
+            .filter(|record| record.success && !record.skipped)
+            .filter_map(|record| {
+                Some(JournalEntry {
+                    host: record.host.clone(),
+                    remote: record.remote.clone()?,
+                    backup: record.backup.clone(),
+                })
+            })
+            .collect();
+
+        match journal.save() {
+            Ok(path) => {
+                log::debug!("Journal enregistré: {}", path.display());
+                if !self.quiet {
+                    println!(
+                        "💾 Journal {} ({} fichier(s)) — annulation: xsshend rollback {}",
+                        run_id,
+                        journal.entries.len(),
+                        run_id
+                    );
+                }
+            }
+            Err(e) => eprintln!("⚠️  Journal de l'exécution non enregistré: {:#}", e),
+        }
     }
 
-    /// Upload d'un fichier vers un hôte via le pool de connexions.
-    /// Réutilise la connexion SSH si elle existe déjà pour cet hôte.
-    async fn upload_to_single_host_pooled(
-        pool: ConnectionPool,
-        file: &Path,
+    /// Restaure les versions sauvegardées par une exécution (xsshend rollback):
+    /// recopie chaque sauvegarde sur son fichier, supprime les fichiers créés.
+    /// `hosts`: serveurs de hosts.json correspondant à ceux du journal.
+    pub async fn rollback(
+        &self,
+        journal: &RunJournal,
+        hosts: &[(String, &HostEntry)],
+    ) -> Vec<RollbackRecord> {
+        let progress = ProgressBar::new(journal.entries.len() as u64);
+        progress.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}",
+                )
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+
+        let rollback_futures = journal.hosts().into_iter().map(|host_name| {
+            let entries: Vec<JournalEntry> = journal
+                .entries
+                .iter()
+                .filter(|entry| entry.host == host_name)
+                .cloned()
+                .collect();
+            let host_entry = hosts
+                .iter()
+                .find(|(name, _)| name == host_name)
+                .map(|(_, entry)| (*entry).clone());
+            let pool = self.pool.clone();
+            let become_user = self.become_user.clone();
+            let retry = self.retry;
+            let progress = progress.clone();
+
+            async move {
+                let target = match host_entry {
+                    Some(host_entry) => pool.resolve(&host_entry),
+                    None => Err(anyhow::anyhow!("Serveur absent de hosts.json")),
+                };
+                let mut records = Vec::new();
+                for entry in &entries {
+                    let result = match &target {
+                        Ok(target) => {
+                            retry
+                                .run(&entry.host, || {
+                                    Self::restore_once(&pool, target, entry, become_user.as_deref())
+                                })
+                                .await
+                        }
+                        Err(e) => Err(anyhow::anyhow!("{:#}", e)),
+                    };
+                    let record = RollbackRecord::new(entry, &result);
+                    match &record.error {
+                        None => progress.println(format!("  ✅ {} : {}", entry.host, entry.remote)),
+                        Some(error) => progress.println(format!(
+                            "  ❌ {} : {} ({})",
+                            entry.host, entry.remote, error
+                        )),
+                    }
+                    progress.inc(1);
+                    records.push(record);
+                }
+                records
+            }
+        });
+
+        let records: Vec<Vec<RollbackRecord>> = stream::iter(rollback_futures)
+            .buffer_unordered(self.concurrency.global)
+            .collect()
+            .await;
+        progress.finish();
+        self.pool.close_all().await;
+
+        records.into_iter().flatten().collect()
+    }
+
+    /// Compare `tree` au contenu de `destination` sur chaque serveur (xsshend sync).
+    /// Les plans sont renvoyés dans l'ordre de `hosts`; avec `delete`, un élément
+    /// distant d'un autre type que l'élément local est prévu en suppression.
+    pub async fn plan_sync(
+        &self,
+        tree: &UploadTree,
+        hosts: &[(String, &HostEntry)],
+        destination: &str,
+        delete: bool,
+    ) -> Vec<HostSyncPlan> {
+        let plan_futures = hosts.iter().map(|(host_name, host_entry)| async move {
+            let listed = async {
+                let target = self.pool.resolve(host_entry)?;
+                self.retry
+                    .run(host_name, || {
+                        Self::list_tree_once(&self.pool, &target, destination)
+                    })
+                    .await
+            }
+            .await;
+            match listed {
+                Ok((expanded, remote)) => HostSyncPlan {
+                    host: host_name.clone(),
+                    destination: expanded,
+                    plan: Ok(SyncPlan::build(tree, &remote, &self.tree_options, delete)),
+                },
+                Err(e) => HostSyncPlan {
+                    host: host_name.clone(),
+                    destination: destination.to_string(),
+                    plan: Err(e),
+                },
+            }
+        });
+
+        stream::iter(plan_futures)
+            .buffered(self.concurrency.global)
+            .collect()
+            .await
+    }
+
+    /// Applique les plans de synchronisation: envoi des ajouts et mises à jour,
+    /// puis, avec `delete` et si tous les envois ont réussi, suppression des
+    /// éléments distants en trop. Un serveur sans plan est compté en échec.
+    pub async fn sync(
+        &self,
+        plans: &[HostSyncPlan],
+        hosts: &[(String, &HostEntry)],
+        delete: bool,
+    ) -> Vec<SyncRecord> {
+        let total: u64 = plans
+            .iter()
+            .filter_map(|host_plan| host_plan.plan.as_ref().ok())
+            .map(SyncPlan::upload_size)
+            .sum();
+        let display = TransferDisplay::new(total, self.quiet);
+
+        let sync_futures = plans.iter().map(|host_plan| {
+            let host_entry = hosts
+                .iter()
+                .find(|(name, _)| *name == host_plan.host)
+                .map(|(_, entry)| *entry);
+            let display = display.clone();
+
+            async move {
+                let plan = match (&host_plan.plan, host_entry) {
+                    (Ok(plan), Some(host_entry)) => (plan, host_entry),
+                    (Err(e), _) => {
+                        let e = anyhow::anyhow!("Comparaison impossible: {:#}", e);
+                        display.println(&format!("  ❌ {} : {:#}", host_plan.host, e));
+                        return vec![SyncRecord::failed(&host_plan.host, None, None, &e)];
+                    }
+                    (Ok(_), None) => {
+                        let e = anyhow::anyhow!("Serveur absent de la sélection");
+                        return vec![SyncRecord::failed(&host_plan.host, None, None, &e)];
+                    }
+                };
+                let transfer = display.start(short_name(&host_plan.host), plan.0.upload_size());
+                let records = self
+                    .sync_host(host_plan, plan.0, plan.1, delete, &transfer)
+                    .await;
+                transfer.finish();
+
+                match records.iter().find(|record| !record.success) {
+                    None => display.println(&format!("  ✅ {}", host_plan.host)),
+                    Some(record) => display.println(&format!(
+                        "  ❌ {} : {}",
+                        host_plan.host,
+                        record.error.as_deref().unwrap_or_default()
+                    )),
+                }
+                records
+            }
+        });
+
+        let records: Vec<Vec<SyncRecord>> = stream::iter(sync_futures)
+            .buffer_unordered(self.concurrency.global)
+            .collect()
+            .await;
+        display.finish();
+        self.pool.close_all().await;
+
+        let records: Vec<SyncRecord> = records.into_iter().flatten().collect();
+        if !self.quiet {
+            let summary = SyncSummary::of(&records);
+            if summary.failed == 0 {
+                println!("\n✅ Synchronisation terminée avec succès!");
+            } else {
+                println!("\n⚠️ Synchronisation terminée avec des erreurs");
+            }
+            println!(
+                "📊 {} ajout(s), {} mise(s) à jour, {} suppression(s), {} en échec",
+                summary.added, summary.updated, summary.deleted, summary.failed
+            );
+        }
+        records
+    }
+
+    /// Applique le plan d'un serveur; s'arrête au premier envoi (ou remplacement)
+    /// en échec: les éléments restants et les suppressions sont comptés en échec.
+    /// Les conflits de type sont comptés en échec sans rien modifier.
+    async fn sync_host(
+        &self,
+        host_plan: &HostSyncPlan,
+        plan: &SyncPlan,
         host_entry: &HostEntry,
+        delete: bool,
+        progress: &TransferProgress,
+    ) -> Vec<SyncRecord> {
+        let host_name = host_plan.host.as_str();
+        let base = host_plan.destination.trim_end_matches('/');
+        let become_user = self.become_user.as_deref();
+        let mut records = Vec::new();
+
+        let prepared = async {
+            let target = self.pool.resolve(host_entry)?;
+            let upload_options = self
+                .upload_options
+                .with_host_defaults(&host_entry.options)?;
+            // La destination elle-même, si elle n'existe pas encore
+            let root = TreeEntry::Dir {
+                remote: String::new(),
+            };
+            self.retry
+                .run(host_name, || {
+                    Self::apply_entry(
+                        &self.pool,
+                        &target,
+                        &root,
+                        base,
+                        become_user,
+                        &upload_options,
+                        progress,
+                    )
+                })
+                .await?;
+            Ok::<_, anyhow::Error>((target, upload_options))
+        }
+        .await;
+        let (target, upload_options) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => return vec![SyncRecord::failed(host_name, None, None, &e)],
+        };
+
+        let remote_path = |relative: &str| format!("{}/{}", base, relative);
+        // Éléments restants et suppressions non effectués: même cause
+        let fail_remaining = |records: &mut Vec<SyncRecord>,
+                              uploads: &[(SyncAction, TreeEntry)],
+                              e: &anyhow::Error| {
+            for (action, entry) in uploads {
+                if !matches!(entry, TreeEntry::Dir { .. }) {
+                    records.push(SyncRecord::failed(
+                        host_name,
+                        Some(*action),
+                        Some(remote_path(entry.remote())),
+                        e,
+                    ));
+                }
+            }
+            if delete {
+                for entry in &plan.extraneous {
+                    records.push(SyncRecord::failed(
+                        host_name,
+                        Some(SyncAction::Delete),
+                        Some(remote_path(&entry.path)),
+                        e,
+                    ));
+                }
+            }
+        };
+
+        for path in &plan.conflicts {
+            let e = anyhow::anyhow!(
+                "Type différent en local et sur le serveur (--delete pour le remplacer)"
+            );
+            records.push(SyncRecord::failed(
+                host_name,
+                Some(SyncAction::Conflict),
+                Some(remote_path(path)),
+                &e,
+            ));
+        }
+
+        // Élément distant d'un autre type: supprimé avant l'envoi qui le remplace
+        for (index, entry) in plan.replaced.iter().enumerate() {
+            let remote = remote_path(&entry.path);
+            let is_dir = entry.kind == RemoteKind::Dir;
+            let result = self
+                .retry
+                .run(host_name, || {
+                    Self::remove_once(&self.pool, &target, &remote, is_dir, become_user)
+                })
+                .await;
+            match result {
+                Ok(()) => records.push(SyncRecord::succeeded(
+                    host_name,
+                    SyncAction::Delete,
+                    remote,
+                    0,
+                )),
+                Err(e) => {
+                    records.push(SyncRecord::failed(
+                        host_name,
+                        Some(SyncAction::Delete),
+                        Some(remote),
+                        &e,
+                    ));
+                    for entry in &plan.replaced[index + 1..] {
+                        records.push(SyncRecord::failed(
+                            host_name,
+                            Some(SyncAction::Delete),
+                            Some(remote_path(&entry.path)),
+                            &e,
+                        ));
+                    }
+                    fail_remaining(&mut records, &plan.uploads, &e);
+                    return records;
+                }
+            }
+        }
+
+        let mut uploads = plan.uploads.iter();
+        while let Some((action, entry)) = uploads.next() {
+            let remote = remote_path(entry.remote());
+            let result = if interrupt::is_interrupted() {
+                Err(anyhow::anyhow!(
+                    "Synchronisation interrompue avant {}",
+                    remote
+                ))
+            } else {
+                if let TreeEntry::File { .. } = entry {
+                    progress.next_file(entry.remote());
+                }
+                self.retry
+                    .run(host_name, || {
+                        Self::apply_entry(
+                            &self.pool,
+                            &target,
+                            entry,
+                            &remote,
+                            become_user,
+                            &upload_options,
+                            progress,
+                        )
+                    })
+                    .await
+            };
+
+            match result {
+                Ok(uploaded) => {
+                    if let TreeEntry::File { size, .. } = entry {
+                        progress.complete_file(*size);
+                    }
+                    if !matches!(entry, TreeEntry::Dir { .. }) {
+                        let bytes = uploaded.map(|uploaded| uploaded.bytes).unwrap_or(0);
+                        records.push(SyncRecord::succeeded(host_name, *action, remote, bytes));
+                    }
+                }
+                Err(e) => {
+                    let e = e.context(remote.clone());
+                    records.push(SyncRecord::failed(
+                        host_name,
+                        Some(*action),
+                        Some(remote),
+                        &e,
+                    ));
+                    fail_remaining(&mut records, uploads.as_slice(), &e);
+                    return records;
+                }
+            }
+        }
+
+        if !delete {
+            return records;
+        }
+        for entry in &plan.extraneous {
+            let remote = remote_path(&entry.path);
+            let is_dir = entry.kind == RemoteKind::Dir;
+            let result = self
+                .retry
+                .run(host_name, || {
+                    Self::remove_once(&self.pool, &target, &remote, is_dir, become_user)
+                })
+                .await;
+            records.push(match result {
+                Ok(()) => SyncRecord::succeeded(host_name, SyncAction::Delete, remote, 0),
+                Err(e) => SyncRecord::failed(host_name, Some(SyncAction::Delete), Some(remote), &e),
+            });
+        }
+        records
+    }
+
+    /// Contenu distant de la destination (développée avec le HOME de l'hôte)
+    async fn list_tree_once(
+        pool: &ConnectionPool,
+        target: &SshTarget,
         destination: &str,
-    ) -> Result<()> {
-        let (username, host) = Self::parse_server_alias(&host_entry.alias)?;
-        let host_key = format!("{}@{}", username, host);
+    ) -> Result<(String, Vec<RemoteEntry>)> {
+        let (client_arc, _permit) = pool.acquire(target).await?;
+        let mut client = client_arc.lock().await;
+        let result = async {
+            let destination = path_expansion::expand_path(
+                destination,
+                &target.username,
+                client.get_remote_home(),
+            )
+            .context("Erreur lors de l'expansion du chemin de destination")?;
+            let remote = client.list_remote_tree(&destination).await?;
+            Ok((destination, remote))
+        }
+        .await;
+        if let Err(e) = &result {
+            pool.invalidate_on_error(&target.pool_key(), &client, e);
+        }
+        result
+    }
 
-        let (client_arc, _permit) = pool.acquire(&host_key, username, host).await?;
+    /// Une tentative de suppression d'un élément distant en trop
+    async fn remove_once(
+        pool: &ConnectionPool,
+        target: &SshTarget,
+        remote: &str,
+        is_dir: bool,
+        become_user: Option<&str>,
+    ) -> Result<()> {
+        let (client_arc, _permit) = pool.acquire(target).await?;
         let mut client = client_arc.lock().await;
+        let result = client
+            .remove_remote(remote, is_dir, become_user)
+            .await
+            .with_context(|| format!("Suppression de {} impossible", remote));
+        if let Err(e) = &result {
+            pool.invalidate_on_error(&target.pool_key(), &client, e);
+        }
+        result
+    }
 
-        let file_name = file.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    /// Une tentative de restauration d'un fichier du journal
+    async fn restore_once(
+        pool: &ConnectionPool,
+        target: &SshTarget,
+        entry: &JournalEntry,
+        become_user: Option<&str>,
+    ) -> Result<()> {
+        let (client_arc, _permit) = pool.acquire(target).await?;
+        let mut client = client_arc.lock().await;
+        let result = client
+            .restore_backup(&entry.remote, entry.backup.as_deref(), become_user)
+            .await;
+        if let Err(e) = &result {
+            pool.invalidate_on_error(&target.pool_key(), &client, e);
+        }
+        result
+    }
 
-        let expanded_destination =
-            path_expansion::expand_path(destination, username, client.get_remote_home())
-                .context("Erreur lors de l'expansion du chemin de destination")?;
+    /// Envoie l'arborescence à un serveur: répertoires et liens d'abord, dans
+    /// l'ordre, puis les fichiers, jusqu'à `per_host` à la fois (une session SFTP
+    /// chacun). Les autres serveurs avancent à leur rythme.
+    async fn upload_to_host(
+        &self,
+        tree: &UploadTree,
+        host_name: &str,
+        host_entry: &HostEntry,
+        destination: &str,
+        display: &TransferDisplay,
+    ) -> Vec<UploadRecord> {
+        let transfer = display.start(short_name(host_name), tree.total_size());
+        let become_user = self.become_user.as_deref();
+        let started = Instant::now();
 
-        let full_destination = if expanded_destination.ends_with('/') {
-            format!("{}{}", expanded_destination, file_name)
-        } else {
-            format!("{}/{}", expanded_destination, file_name)
+        let prepared = async {
+            let target = self.pool.resolve(host_entry)?;
+            let upload_options = self
+                .upload_options
+                .with_host_defaults(&host_entry.options)?;
+            let destination = self
+                .retry
+                .run(host_name, || {
+                    Self::expand_destination(&self.pool, &target, destination)
+                })
+                .await?;
+            Ok::<_, anyhow::Error>((target, upload_options, destination))
+        }
+        .await;
+        let (target, upload_options, destination) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                transfer.finish();
+                display.println(&format!(
+                    "  ❌ {} [{}] : {:#}",
+                    host_name,
+                    FailureKind::of(&e),
+                    e
+                ));
+                return tree
+                    .files()
+                    .map(|(local, _, _)| {
+                        UploadRecord::failed(host_name, local, None, &e).timed(started)
+                    })
+                    .collect();
+            }
         };
+        let base = destination.trim_end_matches('/');
+        let remote_path = |entry: &TreeEntry| format!("{}/{}", base, entry.remote());
 
-        match client.upload_file(file, &full_destination).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                drop(client);
-                pool.invalidate(&host_key);
-                Err(e)
+        let (files, others): (Vec<&TreeEntry>, Vec<&TreeEntry>) = tree
+            .entries
+            .iter()
+            .partition(|entry| matches!(entry, TreeEntry::File { .. }));
+
+        for entry in others {
+            let remote = remote_path(entry);
+            let result = if interrupt::is_interrupted() {
+                Err(anyhow::anyhow!("Téléversement interrompu avant {}", remote))
+            } else {
+                self.retry
+                    .run(host_name, || {
+                        Self::apply_entry(
+                            &self.pool,
+                            &target,
+                            entry,
+                            &remote,
+                            become_user,
+                            &upload_options,
+                            &transfer,
+                        )
+                    })
+                    .await
+            };
+            if let Err(e) = result {
+                // Sans ses répertoires, aucun fichier ne peut être envoyé à cet hôte
+                let e = e.context(remote);
+                transfer.finish();
+                display.println(&format!(
+                    "  ❌ {} [{}] : {:#}",
+                    host_name,
+                    FailureKind::of(&e),
+                    e
+                ));
+                return files
+                    .iter()
+                    .filter_map(|entry| match entry {
+                        TreeEntry::File { local, .. } => Some(
+                            UploadRecord::failed(host_name, local, Some(remote_path(entry)), &e)
+                                .timed(started),
+                        ),
+                        _ => None,
+                    })
+                    .collect();
             }
         }
-    }
 
-    /// Parse un alias serveur au format "user@host"
-    pub fn parse_server_alias(alias: &str) -> Result<(&str, &str)> {
-        if let Some(at_pos) = alias.find('@') {
-            if at_pos == 0 || at_pos == alias.len() - 1 {
-                anyhow::bail!(
-                    "Alias serveur invalide '{}' - format attendu: user@host",
-                    alias
-                );
+        let jobs = files.into_iter().enumerate().map(|(index, entry)| {
+            let remote = remote_path(entry);
+            let upload_options = &upload_options;
+            let target = &target;
+            let transfer = &transfer;
+            async move {
+                let TreeEntry::File { local, size, .. } = entry else {
+                    unreachable!("seuls les fichiers sont envoyés en parallèle");
+                };
+                let started = Instant::now();
+                let progress = transfer.file(entry.remote());
+                let result = if interrupt::is_interrupted() {
+                    Err(anyhow::anyhow!("Téléversement interrompu avant {}", remote))
+                } else {
+                    self.retry
+                        .run(host_name, || {
+                            Self::apply_entry(
+                                &self.pool,
+                                target,
+                                entry,
+                                &remote,
+                                become_user,
+                                upload_options,
+                                &progress,
+                            )
+                        })
+                        .await
+                };
+                let record = match result {
+                    Ok(uploaded) => {
+                        progress.complete_file(*size);
+                        UploadRecord::succeeded(
+                            host_name,
+                            local,
+                            remote,
+                            uploaded.unwrap_or_default(),
+                        )
+                    }
+                    Err(e) => UploadRecord::failed(host_name, local, Some(remote), &e),
+                }
+                .timed(started);
+                if !record.success {
+                    display.println(&record.status_line());
+                }
+                (index, record)
             }
-            let username = &alias[..at_pos];
-            let host = &alias[at_pos + 1..];
-            if username.is_empty() || host.is_empty() {
-                anyhow::bail!(
-                    "Alias serveur invalide '{}' - format attendu: user@host",
-                    alias
-                );
+        });
+        let mut records: Vec<(usize, UploadRecord)> = stream::iter(jobs)
+            .buffer_unordered(self.concurrency.per_host)
+            .collect()
+            .await;
+        transfer.finish();
+
+        // Résultats dans l'ordre de l'arborescence, quel que soit l'ordre de fin
+        records.sort_by_key(|(index, _)| *index);
+        let records: Vec<UploadRecord> = records.into_iter().map(|(_, record)| record).collect();
+        if records.iter().all(|record| record.success) {
+            let skipped = records.iter().filter(|record| record.skipped).count();
+            let mut line = format!("  ✅ {} : {} fichier(s)", host_name, records.len());
+            if skipped > 0 {
+                line.push_str(&format!(", {} inchangé(s)", skipped));
             }
-            Ok((username, host))
-        } else {
-            anyhow::bail!(
-                "Alias serveur invalide '{}' - format attendu: user@host",
-                alias
-            );
+            if upload_options.verify {
+                line.push_str(" (SHA-256 vérifié)");
+            }
+            display.println(&line);
         }
+        records
+    }
+
+    /// Destination développée (`~`, `$HOME`) avec le HOME réel de l'hôte
+    async fn expand_destination(
+        pool: &ConnectionPool,
+        target: &SshTarget,
+        destination: &str,
+    ) -> Result<String> {
+        let (client, _permit) = pool.acquire_lane(target).await?;
+        path_expansion::expand_path(destination, &target.username, client.get_remote_home())
+            .context("Erreur lors de l'expansion du chemin de destination")
+    }
+
+    /// Créer un élément de l'arborescence sur l'hôte; `Some` pour un fichier envoyé
+    async fn apply_entry(
+        pool: &ConnectionPool,
+        target: &SshTarget,
+        entry: &TreeEntry,
+        remote: &str,
+        become_user: Option<&str>,
+        upload_options: &UploadOptions,
+        progress: &TransferProgress,
+    ) -> Result<Option<UploadedFile>> {
+        let (mut client, _permit) = pool.acquire_lane(target).await?;
+
+        let result = match entry {
+            TreeEntry::Dir { .. } => client
+                .create_dir_all(remote, become_user)
+                .await
+                .map(|_| None),
+            TreeEntry::File { local, .. } => match become_user {
+                Some(user) => {
+                    client
+                        .upload_file_as(local, remote, user, upload_options, progress)
+                        .await
+                }
+                None => {
+                    client
+                        .upload_file(local, remote, upload_options, progress)
+                        .await
+                }
+            }
+            .map(Some),
+            TreeEntry::Symlink { target, .. } => client
+                .create_symlink(remote, target, become_user)
+                .await
+                .map(|_| None),
+        };
+
+        if let Err(e) = &result {
+            pool.invalidate_on_error(&target.pool_key(), &client, e);
+        }
+        result
+    }
+
+    /// Parse un alias serveur: "user@host[:port]", "user@[ipv6]:port" ou "ssh://user@host:port"
+    ///
+    /// La résolution des connexions passe par `ConnectionPool::resolve`; cette fonction
+    /// est conservée pour l'API publique de la bibliothèque et les tests.
+    #[allow(dead_code)]
+    pub fn parse_server_alias(alias: &str) -> Result<(&str, &str, Option<u16>)> {
+        target::parse_alias(alias)
     }
 
     /// Mode dry-run : simulation sans transfert réel
@@ -215,15 +1292,41 @@ impl Uploader {
 
         println!("📁 Fichiers à téléverser:");
         for file in files {
-            Validator::validate_file(file)
+            Validator::validate_source(file)
                 .with_context(|| format!("Validation échouée pour {}", file.display()))?;
 
+            let remote = sources::remote_root(file, self.base.as_deref())?;
+            if file.is_dir() {
+                let tree = UploadTree::scan_as(file, &remote, &self.tree_options)?;
+                println!(
+                    "   • {}/ ({} fichier(s), {})",
+                    file.display(),
+                    tree.file_count(),
+                    Validator::format_file_size(tree.total_size())
+                );
+                for entry in &tree.entries {
+                    match entry {
+                        TreeEntry::Dir { .. } => {}
+                        TreeEntry::File { remote, size, .. } => {
+                            println!("       {} ({})", remote, Validator::format_file_size(*size))
+                        }
+                        TreeEntry::Symlink { remote, target } => {
+                            println!("       {} -> {}", remote, target)
+                        }
+                    }
+                }
+                continue;
+            }
+
             let file_size = Validator::get_file_size(file)?;
             println!(
                 "   • {} ({})",
                 file.display(),
                 Validator::format_file_size(file_size)
             );
+            if self.base.is_some() {
+                println!("       → {}", remote);
+            }
         }
 
         println!("🎯 Serveurs cibles:");
@@ -243,3 +1346,153 @@ impl Default for Uploader {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_record_json_carries_digest() {
+        let uploaded = UploadedFile {
+            bytes: 3,
+            sha256: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string(),
+            verified: true,
+            ..Default::default()
+        };
+        let record = UploadRecord::succeeded(
+            "Production:Local:Web:WEB_01",
+            Path::new("abc.txt"),
+            "/srv/app/abc.txt".to_string(),
+            uploaded,
+        );
+        assert_eq!(
+            record.status_line(),
+            "  ✅ Production:Local:Web:WEB_01 (SHA-256 vérifié)"
+        );
+
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["remote"], "/srv/app/abc.txt");
+        assert_eq!(json["verified"], true);
+        assert_eq!(
+            json["sha256"],
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_failed_record_keeps_error_chain() {
+        let error = anyhow::anyhow!("locale aaa, distante bbb").context("/srv/app/abc.txt");
+        let record = UploadRecord::failed("WEB_01", Path::new("abc.txt"), None, &error);
+        assert!(!record.success);
+        assert_eq!(
+            record.error.as_deref(),
+            Some("/srv/app/abc.txt: locale aaa, distante bbb")
+        );
+        assert!(record.sha256.is_none());
+    }
+
+    #[test]
+    fn test_summary_counts_skipped_pairs() {
+        let skipped = UploadedFile {
+            skipped: true,
+            ..Default::default()
+        };
+        let sent = UploadedFile {
+            bytes: 10,
+            sha256: "aa".to_string(),
+            ..Default::default()
+        };
+        let error = anyhow::anyhow!("Permission refusée");
+        let records = vec![
+            UploadRecord::succeeded("WEB_01", Path::new("a"), "/srv/a".to_string(), skipped),
+            UploadRecord::succeeded("WEB_02", Path::new("a"), "/srv/a".to_string(), sent),
+            UploadRecord::failed("WEB_03", Path::new("a"), None, &error),
+        ];
+        assert_eq!(records[0].status_line(), "  ⏭️  WEB_01 (inchangé)");
+        // Comparé par taille et date: aucune empreinte à rapporter
+        assert!(records[0].sha256.is_none());
+
+        assert_eq!(
+            UploadSummary::of(&records),
+            UploadSummary {
+                total: 3,
+                sent: 1,
+                skipped: 1,
+                failed: 1,
+                bytes: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_report_groups_outcomes_per_host() {
+        let sent = UploadedFile {
+            bytes: 10,
+            ..Default::default()
+        };
+        let error = anyhow::anyhow!("Permission denied");
+        let report = UploadReport {
+            records: vec![
+                UploadRecord::succeeded("WEB_01", Path::new("a"), "/a".to_string(), sent.clone()),
+                UploadRecord::succeeded("WEB_01", Path::new("b"), "/b".to_string(), sent.clone()),
+                UploadRecord::succeeded("WEB_02", Path::new("a"), "/a".to_string(), sent),
+                UploadRecord::failed("WEB_02", Path::new("b"), None, &error),
+            ],
+        };
+        assert_eq!(report.records[3].error_kind, Some(FailureKind::Permission));
+
+        let hosts = report.hosts();
+        assert_eq!(hosts.len(), 2);
+        assert!(hosts[0].success);
+        assert_eq!((hosts[0].files, hosts[0].bytes), (2, 20));
+        assert!(!hosts[1].success);
+        assert_eq!(hosts[1].failed, 1);
+        assert_eq!(hosts[1].error_kind, Some(FailureKind::Permission));
+
+        // Sans seuil, le moindre échec fait échouer la commande
+        assert!(report.check(None).is_err());
+        assert!(report.check(Some(MinSuccess::Hosts(1))).is_ok());
+        assert!(report.check(Some(MinSuccess::Percent(50))).is_ok());
+        assert!(report.check(Some(MinSuccess::Percent(51))).is_err());
+    }
+
+    #[test]
+    fn test_host_duration_is_wall_clock_time() {
+        let start = Instant::now();
+        let record = |file: &str, offset: u64, duration_ms: u64| UploadRecord {
+            duration_ms,
+            started: Some(start + Duration::from_millis(offset)),
+            ..UploadRecord::succeeded(
+                "WEB_01",
+                Path::new(file),
+                format!("/{}", file),
+                UploadedFile::default(),
+            )
+        };
+        // Trois envois en parallèle: 0-300, 100-400 et 50-250 ms
+        let report = UploadReport {
+            records: vec![
+                record("a", 0, 300),
+                record("b", 100, 300),
+                record("c", 50, 200),
+            ],
+        };
+
+        let hosts = report.hosts();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].duration_ms, 400);
+    }
+
+    #[test]
+    fn test_min_success_parsing() {
+        assert_eq!("3".parse(), Ok(MinSuccess::Hosts(3)));
+        assert_eq!("80%".parse(), Ok(MinSuccess::Percent(80)));
+        assert!("101%".parse::<MinSuccess>().is_err());
+        assert!("abc".parse::<MinSuccess>().is_err());
+        assert!("-1".parse::<MinSuccess>().is_err());
+
+        assert_eq!(MinSuccess::Percent(80).required(4), 4);
+        assert_eq!(MinSuccess::Percent(75).required(4), 3);
+        assert_eq!(MinSuccess::Hosts(2).required(10), 2);
+    }
+}
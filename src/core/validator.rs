@@ -26,6 +26,16 @@ impl Validator {
         Ok(())
     }
 
+    /// Valide une source de téléversement: fichier lisible ou répertoire parcourable
+    pub fn validate_source(path: &Path) -> Result<()> {
+        if path.is_dir() {
+            fs::read_dir(path)
+                .with_context(|| format!("Impossible de lire le répertoire: {}", path.display()))?;
+            return Ok(());
+        }
+        Self::validate_file(path)
+    }
+
     /// Obtient la taille d'un fichier en octets
     pub fn get_file_size(file_path: &Path) -> Result<u64> {
         let metadata = fs::metadata(file_path).with_context(|| {
@@ -77,6 +87,14 @@ mod tests {
         assert!(Validator::validate_file(non_existent).is_err());
     }
 
+    #[test]
+    fn test_source_validation_accepts_directories() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        assert!(Validator::validate_source(temp_dir.path()).is_ok());
+        assert!(Validator::validate_file(temp_dir.path()).is_err());
+        assert!(Validator::validate_source(Path::new("/path/that/does/not/exist")).is_err());
+    }
+
     #[test]
     fn test_file_size_formatting() {
         assert_eq!(Validator::format_file_size(512), "512 B");
@@ -8,8 +8,18 @@ mod interactive;
 mod ssh;
 mod utils;
 
-use config::HostsConfig;
-use core::uploader::Uploader;
+use config::{HostOptions, HostsConfig};
+use core::downloader::{DownloadSummary, Downloader, PathTemplate, DEFAULT_TEMPLATE};
+use core::journal::RunJournal;
+use core::sync::{SyncAction, SyncSummary};
+use core::tree::{SymlinkPolicy, TreeOptions, UploadTree};
+use core::uploader::{HostSyncPlan, MinSuccess, Uploader};
+use ssh::client::{parse_mode, BackupOptions, UnchangedCheck, UploadOptions};
+use ssh::password::PasswordCache;
+use ssh::pipeline::{parse_size, Pipeline, DEFAULT_CHUNK_SIZE, DEFAULT_REQUESTS};
+use ssh::pool::{Concurrency, DEFAULT_JOBS, DEFAULT_JOBS_PER_HOST};
+use ssh::retry::{RetryPolicy, DEFAULT_RETRIES};
+use ssh::sudo::DEFAULT_BECOME_USER;
 
 /// Outil Rust de téléversement multi-SSH avec mode interactif
 #[derive(Parser)]
@@ -32,10 +42,27 @@ struct Cli {
     #[arg(short = 'y', long, global = true)]
     yes: bool,
 
-    /// Clé SSH spécifique à utiliser
+    /// Clé SSH spécifique à utiliser (essayée en premier)
     #[arg(long, global = true, value_name = "PATH")]
     key: Option<PathBuf>,
 
+    /// N'utiliser que la clé fournie (--key ou identity_file), comme IdentitiesOnly
+    #[arg(long, global = true)]
+    identities_only: bool,
+
+    /// Hôtes de rebond (ProxyJump), séparés par des virgules; "none" pour une connexion directe
+    #[arg(
+        short = 'J',
+        long,
+        global = true,
+        value_name = "[USER@]HOST[:PORT],..."
+    )]
+    jump: Option<String>,
+
+    /// Lire le mot de passe SSH sur stdin (authentification par mot de passe, sans invite)
+    #[arg(long, global = true)]
+    password_stdin: bool,
+
     /// Afficher les logs de debug
     #[arg(short = 'v', long, global = true)]
     verbose: bool,
@@ -45,9 +72,18 @@ struct Cli {
 enum Commands {
     /// Téléverse des fichiers vers plusieurs serveurs SSH
     Upload {
-        /// Fichiers à téléverser
-        #[arg(required = true, value_name = "FILE")]
-        files: Vec<PathBuf>,
+        /// Fichiers ou répertoires à téléverser (répertoires recréés récursivement),
+        /// ou motifs développés par xsshend: 'build/**/*.jar'
+        #[arg(required_unless_present = "files_from", value_name = "FILE")]
+        files: Vec<String>,
+
+        /// Lire les fichiers ou motifs à téléverser dans une liste, un par ligne (`-`: stdin)
+        #[arg(long, value_name = "LIST")]
+        files_from: Option<PathBuf>,
+
+        /// Chemins distants relatifs à ce répertoire (défaut: nom seul de chaque source)
+        #[arg(long, value_name = "DIR")]
+        base: Option<PathBuf>,
 
         /// Environnement (Production, Staging, Development, etc.)
         #[arg(long, value_name = "ENV")]
@@ -68,6 +104,224 @@ enum Commands {
         /// Simulation sans transfert réel
         #[arg(long)]
         dry_run: bool,
+
+        /// Installer les fichiers via sudo en tant que USER (`--become` seul: root, `--become=USER`)
+        #[arg(
+            long = "become",
+            value_name = "USER",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = DEFAULT_BECOME_USER
+        )]
+        become_user: Option<String>,
+
+        /// Liens symboliques des répertoires: follow (copier la cible), link (recréer le lien), skip
+        #[arg(long, value_name = "POLICY", default_value_t = SymlinkPolicy::Follow)]
+        symlinks: SymlinkPolicy,
+
+        /// Dans les répertoires, n'envoyer que les fichiers correspondant au motif (répétable)
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+
+        /// Dans les répertoires, ignorer les fichiers et répertoires correspondant au motif (répétable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Écrire directement dans la destination (défaut: fichier temporaire puis renommage atomique)
+        #[arg(long)]
+        in_place: bool,
+
+        /// Vérifier l'empreinte SHA-256 de chaque fichier sur le serveur après transfert
+        #[arg(long)]
+        verify: bool,
+
+        /// Reprendre les transferts interrompus à partir du fichier partiel distant
+        #[arg(long, conflicts_with = "become_user")]
+        resume: bool,
+
+        /// Ne pas renvoyer les fichiers déjà identiques sur le serveur:
+        /// mtime (taille et date, défaut) ou hash (empreinte SHA-256)
+        #[arg(
+            long,
+            value_name = "CHECK",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "mtime"
+        )]
+        skip_unchanged: Option<UnchangedCheck>,
+
+        /// Reporter le mode et la date de modification des fichiers locaux
+        #[arg(long)]
+        preserve: bool,
+
+        /// Mode des fichiers téléversés, en octal (ex: 0644), prioritaire sur --preserve
+        #[arg(long, value_name = "MODE", value_parser = parse_mode)]
+        chmod: Option<u32>,
+
+        /// Propriétaire des fichiers téléversés (droits requis, voir --become)
+        #[arg(long, value_name = "USER")]
+        chown: Option<String>,
+
+        /// Groupe des fichiers téléversés
+        #[arg(long, value_name = "GROUP")]
+        chgrp: Option<String>,
+
+        /// Sauvegarder chaque fichier distant remplacé (copie datée à côté du fichier)
+        /// et journaliser l'exécution pour `xsshend rollback`
+        #[arg(long)]
+        backup: bool,
+
+        /// Répertoire distant des sauvegardes (implique --backup)
+        #[arg(long, value_name = "DIR")]
+        backup_dir: Option<String>,
+
+        /// Nouvelles tentatives par serveur après une erreur réseau passagère
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_RETRIES)]
+        retries: u32,
+
+        /// Écritures SFTP en vol par fichier, comme `sftp -R` (1: une à la fois)
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_REQUESTS)]
+        requests: usize,
+
+        /// Taille de chaque écriture SFTP, comme `sftp -B` (ex: 32K, 255K au plus)
+        #[arg(long, value_name = "SIZE", default_value_t = DEFAULT_CHUNK_SIZE, value_parser = parse_size)]
+        chunk_size: usize,
+
+        /// Transferts simultanés au plus, tous serveurs confondus
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
+
+        /// Transferts simultanés au plus vers un même serveur (une session SFTP chacun)
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_JOBS_PER_HOST)]
+        jobs_per_host: usize,
+
+        /// Réussir si au moins N serveurs (ou N% des serveurs) reçoivent tous les fichiers
+        /// (défaut: le moindre échec fait échouer la commande)
+        #[arg(long, value_name = "N|N%")]
+        min_success: Option<MinSuccess>,
+
+        /// Format de sortie (text ou json)
+        #[arg(long, default_value = "text", value_name = "FORMAT")]
+        output_format: String,
+    },
+
+    /// Reproduit à l'identique le contenu d'un répertoire local sur plusieurs serveurs
+    ///
+    /// Exemple : publier un site statique, en supprimant les pages retirées
+    ///   xsshend sync ./public --dest /var/www/site --env Production --delete
+    Sync {
+        /// Répertoire local dont le contenu est reproduit dans la destination
+        #[arg(value_name = "LOCAL_DIR")]
+        local_dir: PathBuf,
+
+        /// Répertoire distant synchronisé
+        #[arg(long, short = 'd', value_name = "PATH")]
+        dest: String,
+
+        /// Environnement cible
+        #[arg(long, value_name = "ENV")]
+        env: Option<String>,
+
+        /// Région cible
+        #[arg(long, value_name = "REGION")]
+        region: Option<String>,
+
+        /// Type de serveur
+        #[arg(long, short = 't', value_name = "TYPE")]
+        server_type: Option<String>,
+
+        /// Supprimer les éléments distants absents en local (sauf chemins --exclude)
+        #[arg(long)]
+        delete: bool,
+
+        /// Afficher le plan sans rien modifier
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Écrire et supprimer via sudo en tant que USER (`--become` seul: root, `--become=USER`)
+        #[arg(
+            long = "become",
+            value_name = "USER",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = DEFAULT_BECOME_USER
+        )]
+        become_user: Option<String>,
+
+        /// Liens symboliques locaux: follow (copier la cible), link (recréer le lien), skip
+        #[arg(long, value_name = "POLICY", default_value_t = SymlinkPolicy::Follow)]
+        symlinks: SymlinkPolicy,
+
+        /// Ne synchroniser que les fichiers correspondant au motif (répétable)
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+
+        /// Ignorer ces chemins, en local comme sur le serveur: jamais supprimés (répétable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// Vérifier l'empreinte SHA-256 de chaque fichier envoyé
+        #[arg(long)]
+        verify: bool,
+
+        /// Nouvelles tentatives par serveur après une erreur réseau passagère
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_RETRIES)]
+        retries: u32,
+
+        /// Format de sortie (text ou json)
+        #[arg(long, default_value = "text", value_name = "FORMAT")]
+        output_format: String,
+    },
+
+    /// Télécharge des fichiers depuis plusieurs serveurs SSH
+    ///
+    /// Exemple : récupérer les core dumps de tous les serveurs web
+    ///   xsshend download "/var/crash/core.*" --env Production --type Web --into ./crash
+    Download {
+        /// Chemin distant (jokers shell `*`, `?`, `[...]` développés sur chaque serveur)
+        #[arg(value_name = "REMOTE_PATH")]
+        remote_path: String,
+
+        /// Environnement cible
+        #[arg(long, value_name = "ENV")]
+        env: Option<String>,
+
+        /// Région cible
+        #[arg(long, value_name = "REGION")]
+        region: Option<String>,
+
+        /// Type de serveur
+        #[arg(long, short = 't', value_name = "TYPE")]
+        server_type: Option<String>,
+
+        /// Répertoire local de destination
+        #[arg(long, short = 'o', value_name = "DIR", default_value = ".")]
+        into: PathBuf,
+
+        /// Chemin local de chaque fichier, relatif à --into:
+        /// {host}, {env}, {region}, {type}, {path} (chemin distant), {name}
+        #[arg(long, value_name = "TEMPLATE", default_value = DEFAULT_TEMPLATE)]
+        template: PathTemplate,
+
+        /// Télécharger aussi le contenu des répertoires
+        #[arg(long, short = 'r')]
+        recursive: bool,
+
+        /// Nouvelles tentatives par fichier après une erreur réseau passagère
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_RETRIES)]
+        retries: u32,
+
+        /// Transferts simultanés au plus, tous serveurs confondus
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
+
+        /// Transferts simultanés au plus depuis un même serveur (une session SFTP chacun)
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_JOBS_PER_HOST)]
+        jobs_per_host: usize,
+
+        /// Format de sortie (text ou json)
+        #[arg(long, default_value = "text", value_name = "FORMAT")]
+        output_format: String,
     },
 
     /// Exécute une commande SSH sur plusieurs serveurs
@@ -100,10 +354,28 @@ enum Commands {
         #[arg(long, default_value = "30", value_name = "SECS")]
         timeout: u64,
 
-        /// Afficher stderr séparément
+        /// Afficher aussi stderr (en direct, sur la sortie d'erreur)
         #[arg(long)]
         capture_stderr: bool,
 
+        /// Transmettre l'entrée standard locale à la commande sur chaque serveur
+        #[arg(long, conflicts_with = "become_user")]
+        stdin: bool,
+
+        /// Allouer un pseudo-terminal (stderr mêlé à stdout, SIGHUP à la déconnexion)
+        #[arg(long)]
+        pty: bool,
+
+        /// Exécuter via sudo en tant que USER (`--become` seul: root, `--become=USER`)
+        #[arg(
+            long = "become",
+            value_name = "USER",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = DEFAULT_BECOME_USER
+        )]
+        become_user: Option<String>,
+
         /// Format de sortie (text ou json)
         #[arg(long, default_value = "text", value_name = "FORMAT")]
         output_format: String,
@@ -154,10 +426,35 @@ enum Commands {
         /// Forcer sans confirmation
         #[arg(short = 'y', long)]
         yes: bool,
+
+        /// Lire les logs via sudo en tant que USER (`--become` seul: root, `--become=USER`)
+        #[arg(
+            long = "become",
+            value_name = "USER",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = DEFAULT_BECOME_USER
+        )]
+        become_user: Option<String>,
+    },
+
+    /// Restaure les fichiers remplacés par un upload --backup
+    Rollback {
+        /// Identifiant de l'exécution (affiché par upload --backup); sans valeur,
+        /// liste les exécutions journalisées
+        run_id: Option<String>,
+
+        /// Afficher les restaurations sans les effectuer
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Liste les serveurs disponibles
-    List,
+    List {
+        /// Afficher les paramètres de connexion effectifs (hosts.json + ~/.ssh/config + CLI)
+        #[arg(long)]
+        resolved: bool,
+    },
 
     /// Initialise la configuration xsshend
     Init {
@@ -205,25 +502,209 @@ async fn main() -> Result<()> {
         return Ok(());
     };
 
+    // Options de connexion imposées par la CLI, prioritaires sur hosts.json
+    let overrides = HostOptions {
+        identity_file: cli.key,
+        identities_only: cli.identities_only.then_some(true),
+        jump: cli.jump,
+        ..Default::default()
+    };
+
+    // Mots de passe SSH, saisis une seule fois par hôte pour toute l'exécution
+    if cli.password_stdin && matches!(command, Commands::Command { stdin: true, .. }) {
+        anyhow::bail!("❌ --stdin et --password-stdin ne peuvent pas être utilisés ensemble");
+    }
+    let password_cache = if cli.password_stdin {
+        PasswordCache::from_stdin()?
+    } else if cli.non_interactive {
+        PasswordCache::new().without_prompt()
+    } else {
+        PasswordCache::new()
+    };
+
+    if matches!(
+        command,
+        Commands::Upload { .. }
+            | Commands::Sync { .. }
+            | Commands::Download { .. }
+            | Commands::Command { .. }
+            | Commands::Grep { .. }
+    ) {
+        warn_expiring_certificates().await;
+    }
+
+    // Ctrl-C: arrêter proprement les commandes et transferts en cours
+    if matches!(
+        command,
+        Commands::Upload { .. }
+            | Commands::Sync { .. }
+            | Commands::Download { .. }
+            | Commands::Command { .. }
+            | Commands::Grep { .. }
+    ) {
+        crate::utils::interrupt::install();
+    }
+
     match command {
         Commands::Upload {
             files,
+            files_from,
+            base,
             env,
             region,
             server_type,
             dest,
             dry_run,
+            become_user,
+            symlinks,
+            include,
+            exclude,
+            in_place,
+            verify,
+            resume,
+            skip_unchanged,
+            preserve,
+            chmod,
+            chown,
+            chgrp,
+            backup,
+            backup_dir,
+            retries,
+            requests,
+            chunk_size,
+            jobs,
+            jobs_per_host,
+            min_success,
+            output_format,
         } => {
+            if cli.password_stdin
+                && files_from.as_deref() == Some(Path::new(crate::core::sources::STDIN))
+            {
+                anyhow::bail!(
+                    "❌ --files-from - et --password-stdin lisent tous deux l'entrée standard"
+                );
+            }
             handle_upload_command(UploadArgs {
-                files,
+                patterns: files,
+                files_from,
+                base,
                 env,
                 region,
                 server_type,
                 dest,
                 dry_run,
+                become_user,
+                tree_options: TreeOptions {
+                    symlinks,
+                    include,
+                    exclude,
+                },
+                upload_options: UploadOptions {
+                    in_place,
+                    verify,
+                    resume,
+                    skip_unchanged,
+                    preserve,
+                    mode: chmod,
+                    owner: chown,
+                    group: chgrp,
+                    backup: (backup || backup_dir.is_some()).then(|| BackupOptions {
+                        run_id: RunJournal::new_run_id(),
+                        dir: backup_dir,
+                    }),
+                    pipeline: Pipeline {
+                        requests,
+                        chunk_size,
+                    },
+                },
+                retry: RetryPolicy::new(retries),
+                concurrency: Concurrency {
+                    global: jobs,
+                    per_host: jobs_per_host,
+                },
+                min_success,
+                output_format,
+                non_interactive: cli.non_interactive,
+                yes: cli.yes,
+                overrides,
+                password_cache,
+            })
+            .await?;
+        }
+        Commands::Sync {
+            local_dir,
+            dest,
+            env,
+            region,
+            server_type,
+            delete,
+            dry_run,
+            become_user,
+            symlinks,
+            include,
+            exclude,
+            verify,
+            retries,
+            output_format,
+        } => {
+            handle_sync_command(SyncArgs {
+                local_dir,
+                dest,
+                env,
+                region,
+                server_type,
+                delete,
+                dry_run,
+                become_user,
+                tree_options: TreeOptions {
+                    symlinks,
+                    include,
+                    exclude,
+                },
+                upload_options: UploadOptions {
+                    verify,
+                    ..Default::default()
+                },
+                retry: RetryPolicy::new(retries),
+                output_format,
                 non_interactive: cli.non_interactive,
                 yes: cli.yes,
-                key: cli.key,
+                overrides,
+                password_cache,
+            })
+            .await?;
+        }
+        Commands::Download {
+            remote_path,
+            env,
+            region,
+            server_type,
+            into,
+            template,
+            recursive,
+            retries,
+            jobs,
+            jobs_per_host,
+            output_format,
+        } => {
+            handle_download_command(DownloadArgs {
+                remote_path,
+                env,
+                region,
+                server_type,
+                into,
+                template,
+                recursive,
+                retry: RetryPolicy::new(retries),
+                concurrency: Concurrency {
+                    global: jobs,
+                    per_host: jobs_per_host,
+                },
+                output_format,
+                non_interactive: cli.non_interactive,
+                yes: cli.yes,
+                overrides,
+                password_cache,
             })
             .await?;
         }
@@ -236,6 +717,9 @@ async fn main() -> Result<()> {
             parallel,
             timeout,
             capture_stderr,
+            stdin,
+            pty,
+            become_user,
             output_format,
         } => {
             handle_command_execution(CommandArgs {
@@ -247,9 +731,14 @@ async fn main() -> Result<()> {
                 parallel,
                 timeout,
                 capture_stderr,
+                stdin,
+                pty,
+                become_user,
                 output_format,
                 non_interactive: cli.non_interactive,
                 yes: cli.yes,
+                overrides,
+                password_cache,
             })
             .await?;
         }
@@ -264,6 +753,7 @@ async fn main() -> Result<()> {
             timeout,
             output_format,
             yes,
+            become_user,
         } => {
             handle_grep(GrepArgs {
                 pattern,
@@ -276,11 +766,25 @@ async fn main() -> Result<()> {
                 timeout,
                 output_format,
                 yes,
+                become_user,
+                non_interactive: cli.non_interactive,
+                overrides,
+                password_cache,
+            })
+            .await?;
+        }
+        Commands::Rollback { run_id, dry_run } => {
+            handle_rollback_command(RollbackArgs {
+                run_id,
+                dry_run,
                 non_interactive: cli.non_interactive,
+                yes: cli.yes,
+                overrides,
+                password_cache,
             })
             .await?;
         }
-        Commands::List => {
+        Commands::List { resolved } => {
             println!("🔍 Liste des cibles SSH disponibles:\n");
 
             let config = match HostsConfig::load() {
@@ -291,7 +795,11 @@ async fn main() -> Result<()> {
                 }
             };
 
-            config.display_all_targets();
+            if resolved {
+                display_resolved_targets(&config, overrides);
+            } else {
+                config.display_all_targets();
+            }
         }
         Commands::Init { force } => {
             init_setup(force)?;
@@ -301,17 +809,118 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Avertit si un certificat SSH (fichier ou ssh-agent) est expiré ou expire bientôt,
+/// ou si un certificat ssh-agent ne peut pas servir faute de clé privée sur disque
+async fn warn_expiring_certificates() {
+    use ssh::agent::SshAgentManager;
+    use ssh::keys::{keyless_certificate_warnings, SshKeyManager};
+
+    let manager = SshKeyManager::new().ok();
+    let mut warnings = manager
+        .as_ref()
+        .map(|manager| manager.certificate_warnings())
+        .unwrap_or_default();
+    if let Ok(identities) = SshAgentManager::list_all_identities().await {
+        warnings.extend(
+            identities
+                .certificates
+                .iter()
+                .filter_map(|certificate| certificate.expiry_warning()),
+        );
+        warnings.extend(keyless_certificate_warnings(
+            manager
+                .as_ref()
+                .map(|manager| manager.get_all_keys())
+                .unwrap_or_default(),
+            &identities.certificates,
+        ));
+    }
+
+    for warning in warnings {
+        eprintln!("⚠️  {}", warning);
+    }
+}
+
+/// Affiche, pour chaque cible, les paramètres de connexion effectifs
+fn display_resolved_targets(config: &HostsConfig, overrides: HostOptions) {
+    use ssh::keys::PassphraseCache;
+    use ssh::pool::ConnectionPool;
+
+    let pool = ConnectionPool::new(PassphraseCache::new()).with_overrides(overrides);
+    let mut hosts = config.filter_hosts(None, None, None);
+    hosts.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (name, host_entry) in &hosts {
+        println!("🖥️  {} ({})", name, host_entry.alias);
+        match pool.resolve(host_entry) {
+            Ok(target) => {
+                println!("     Cible        : {}", target.endpoint());
+                println!(
+                    "     Clé          : {}{}",
+                    target
+                        .options
+                        .identity_file
+                        .as_deref()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_else(|| "auto (ssh-agent / ~/.ssh)".to_string()),
+                    if target.options.identities_only {
+                        " (identities_only)"
+                    } else {
+                        ""
+                    }
+                );
+                println!(
+                    "     Rebonds      : {}",
+                    if target.jumps.is_empty() {
+                        "-".to_string()
+                    } else {
+                        target
+                            .jumps
+                            .iter()
+                            .map(|jump| jump.endpoint())
+                            .collect::<Vec<_>>()
+                            .join(" → ")
+                    }
+                );
+                println!(
+                    "     Timeout      : {}",
+                    target
+                        .options
+                        .connect_timeout
+                        .map(|t| format!("{}s", t.as_secs()))
+                        .unwrap_or_else(|| "défaut".to_string())
+                );
+                println!("     Clé d'hôte   : {}", target.options.host_key_policy);
+            }
+            Err(e) => println!("     ❌ {}", e),
+        }
+    }
+
+    println!("\n📊 Total: {} cibles résolues", hosts.len());
+}
+
 /// Arguments pour la commande upload
 struct UploadArgs {
-    files: Vec<PathBuf>,
+    /// Chemins ou motifs donnés en arguments
+    patterns: Vec<String>,
+    files_from: Option<PathBuf>,
+    base: Option<PathBuf>,
     env: Option<String>,
     region: Option<String>,
     server_type: Option<String>,
     dest: PathBuf,
     dry_run: bool,
+    become_user: Option<String>,
+    tree_options: TreeOptions,
+    upload_options: UploadOptions,
+    retry: RetryPolicy,
+    concurrency: Concurrency,
+    min_success: Option<MinSuccess>,
+    output_format: String,
     non_interactive: bool,
     yes: bool,
-    key: Option<PathBuf>,
+    overrides: HostOptions,
+    password_cache: PasswordCache,
 }
 
 // ─────────────────────────────────────────────────────────────────
@@ -329,7 +938,10 @@ struct GrepArgs {
     timeout: u64,
     output_format: String,
     yes: bool,
+    become_user: Option<String>,
     non_interactive: bool,
+    overrides: HostOptions,
+    password_cache: PasswordCache,
 }
 
 /// Gère la sous-commande `grep`
@@ -379,7 +991,9 @@ async fn handle_grep(args: GrepArgs) -> Result<()> {
     }
 
     println!();
-    let executor = GrepExecutor::new();
+    let executor = GrepExecutor::with_overrides(args.overrides)
+        .with_password_cache(args.password_cache)
+        .with_become(args.become_user);
     let timeout = std::time::Duration::from_secs(args.timeout);
 
     let results = executor
@@ -463,14 +1077,20 @@ struct CommandArgs {
     parallel: bool,
     timeout: u64,
     capture_stderr: bool,
+    stdin: bool,
+    pty: bool,
+    become_user: Option<String>,
     output_format: String,
     non_interactive: bool,
     yes: bool,
+    overrides: HostOptions,
+    password_cache: PasswordCache,
 }
 
 /// Gère l'exécution de commandes SSH
 async fn handle_command_execution(args: CommandArgs) -> Result<()> {
     use crate::core::executor::CommandExecutor;
+    use crate::core::output::LiveOutput;
     use crate::interactive::{is_interactive_mode, prompts, should_prompt};
     use anyhow::Context;
 
@@ -567,12 +1187,42 @@ async fn handle_command_execution(args: CommandArgs) -> Result<()> {
         }
     }
 
-    // 5. Exécuter les commandes
+    // 5. Lire l'entrée à transmettre (une seule fois pour tous les serveurs)
+    let input = if args.stdin {
+        use std::io::IsTerminal;
+        use tokio::io::AsyncReadExt;
+
+        if std::io::stdin().is_terminal() {
+            eprintln!("⌨️  Saisissez l'entrée à transmettre, terminez par Ctrl-D");
+        }
+        let mut input = Vec::new();
+        tokio::io::stdin()
+            .read_to_end(&mut input)
+            .await
+            .context("Impossible de lire l'entrée standard")?;
+        log::debug!("📥 {} octets lus sur stdin", input.len());
+        Some(input)
+    } else {
+        None
+    };
+
+    // 6. Exécuter les commandes
     if args.output_format != "json" {
         println!("\n🚀 Début de l'exécution...\n");
     }
 
-    let executor = CommandExecutor::new();
+    let mut executor = CommandExecutor::with_overrides(args.overrides)
+        .with_password_cache(args.password_cache)
+        .with_pty(args.pty)
+        .with_become(args.become_user);
+    if args.output_format != "json" {
+        // Sortie affichée en direct, préfixée par le serveur
+        let host_names: Vec<&str> = target_hosts.iter().map(|(name, _)| name.as_str()).collect();
+        executor = executor.with_live_output(LiveOutput::new(&host_names, args.capture_stderr));
+    }
+    if let Some(input) = input {
+        executor = executor.with_stdin(input);
+    }
     let results = executor
         .execute(
             &command,
@@ -582,7 +1232,7 @@ async fn handle_command_execution(args: CommandArgs) -> Result<()> {
         )
         .await?;
 
-    // 6. Afficher les résultats détaillés (seulement en mode text)
+    // 7. Afficher les résultats détaillés (seulement en mode text)
     if args.output_format != "json" {
         println!("\n📊 Résultats détaillés:");
         println!("{}", "=".repeat(80));
@@ -599,25 +1249,11 @@ async fn handle_command_execution(args: CommandArgs) -> Result<()> {
                     "❌ Échec"
                 }
             );
-
-            if !result.stdout.is_empty() {
-                println!("\n  📤 Stdout:");
-                for line in result.stdout.lines() {
-                    println!("    {}", line);
-                }
-            }
-
-            if args.capture_stderr && !result.stderr.is_empty() {
-                println!("\n  ⚠️  Stderr:");
-                for line in result.stderr.lines() {
-                    println!("    {}", line);
-                }
-            }
             println!("{}", "-".repeat(80));
         }
     }
 
-    // 7. Résumé final
+    // 8. Résumé final
     let success_count = results.iter().filter(|r| r.success).count();
     let total_count = results.len();
 
@@ -659,15 +1295,25 @@ async fn handle_command_execution(args: CommandArgs) -> Result<()> {
 
 /// Gère la commande upload avec mode interactif
 async fn handle_upload_command(args: UploadArgs) -> Result<()> {
+    use crate::core::sources;
     use crate::core::validator::Validator;
     use crate::interactive::{is_interactive_mode, prompts, should_prompt};
 
-    println!("🚀 xsshend - Téléversement Multi-SSH");
+    // En JSON, stdout ne contient que le rapport final
+    let json = args.output_format == "json";
+    if !json {
+        println!("🚀 xsshend - Téléversement Multi-SSH");
 
-    // 1. Validation des fichiers
-    println!("🔍 Validation des fichiers...");
-    for file in &args.files {
-        Validator::validate_file(file)
+        // 1. Validation des fichiers
+        println!("🔍 Validation des fichiers...");
+    }
+    let files = sources::collect(
+        &args.patterns,
+        args.files_from.as_deref(),
+        args.base.as_deref(),
+    )?;
+    for file in &files {
+        Validator::validate_source(file)
             .map_err(|e| anyhow::anyhow!("Validation échouée pour {}: {}", file.display(), e))?;
     }
 
@@ -723,27 +1369,39 @@ async fn handle_upload_command(args: UploadArgs) -> Result<()> {
     }
 
     // 4. Afficher le récapitulatif
-    println!("\n{}", "=".repeat(60));
-    println!("📋 RÉCAPITULATIF");
-    println!("{}", "=".repeat(60));
-    println!("📦 Fichiers: {}", args.files.len());
-    for file in &args.files {
-        if let Ok(metadata) = std::fs::metadata(file) {
-            println!("   • {} ({} octets)", file.display(), metadata.len());
-        }
-    }
-    println!("\n🎯 Environnement: {}", env.as_deref().unwrap_or("Tous"));
-    println!("📍 Région: {}", region.as_deref().unwrap_or("Toutes"));
-    println!("🖥️  Type: {}", server_type.as_deref().unwrap_or("Tous"));
-    println!("📂 Destination: {}", dest.display());
-    println!("🖥️  Serveurs ciblés: {}", target_hosts.len());
-    println!("{}", "=".repeat(60));
+    if !json {
+        println!("\n{}", "=".repeat(60));
+        println!("📋 RÉCAPITULATIF");
+        println!("{}", "=".repeat(60));
+        println!("📦 Fichiers: {}", files.len());
+        for file in &files {
+            if file.is_dir() {
+                match UploadTree::scan(file, &args.tree_options) {
+                    Ok(tree) => println!(
+                        "   • {}/ (répertoire, {} fichier(s), {} octets)",
+                        file.display(),
+                        tree.file_count(),
+                        tree.total_size()
+                    ),
+                    Err(_) => println!("   • {}/ (répertoire)", file.display()),
+                }
+            } else if let Ok(metadata) = std::fs::metadata(file) {
+                println!("   • {} ({} octets)", file.display(), metadata.len());
+            }
+        }
+        println!("\n🎯 Environnement: {}", env.as_deref().unwrap_or("Tous"));
+        println!("📍 Région: {}", region.as_deref().unwrap_or("Toutes"));
+        println!("🖥️  Type: {}", server_type.as_deref().unwrap_or("Tous"));
+        println!("📂 Destination: {}", dest.display());
+        println!("🖥️  Serveurs ciblés: {}", target_hosts.len());
+        println!("{}", "=".repeat(60));
+    }
 
     // 5. Confirmation
     if !args.dry_run && !args.yes {
         if !args.non_interactive && is_interactive_mode() {
             let confirmed = prompts::confirm_upload(
-                &args.files,
+                &files,
                 &target_hosts,
                 &dest,
                 env.as_deref().unwrap_or("Unknown"),
@@ -760,31 +1418,496 @@ async fn handle_upload_command(args: UploadArgs) -> Result<()> {
     }
 
     // 6. Upload
-    println!("\n🚀 Début du téléversement...\n");
+    if !json {
+        println!("\n🚀 Début du téléversement...\n");
 
-    let uploader = Uploader::new();
-    let file_refs: Vec<&std::path::Path> = args.files.iter().map(|p| p.as_path()).collect();
-    let dest_str = dest.to_str().unwrap_or("/tmp/");
-
-    // Si une clé SSH est fournie, l'indiquer (elle est lue ici pour éviter l'avertissement
-    // ; le comportement effectif d'utilisation peut être géré par d'autres modules)
-    if let Some(key_path) = &args.key {
-        println!(
-            "🔑 Utilisation de la clé SSH fournie: {}",
-            key_path.display()
-        );
+        if let Some(key_path) = &args.overrides.identity_file {
+            println!(
+                "🔑 Utilisation de la clé SSH fournie: {}",
+                key_path.display()
+            );
+        }
     }
 
+    let run_backup = args.upload_options.backup.clone();
+    let uploader = Uploader::with_overrides(args.overrides)
+        .with_password_cache(args.password_cache)
+        .with_become(args.become_user)
+        .with_tree_options(args.tree_options)
+        .with_upload_options(args.upload_options)
+        .with_retry(args.retry)
+        .with_concurrency(args.concurrency)
+        .with_base(args.base)
+        .with_quiet(json);
+    let file_refs: Vec<&std::path::Path> = files.iter().map(|p| p.as_path()).collect();
+    let dest_str = dest.to_str().unwrap_or("/tmp/");
+
     if args.dry_run {
         uploader
             .dry_run(&file_refs, &target_hosts, dest_str)
             .await?;
     } else {
-        uploader
+        let report = uploader
             .upload_files(&file_refs, &target_hosts, dest_str)
             .await?;
+
+        if json {
+            let summary = report.summary();
+            let hosts = report.hosts();
+            let hosts_succeeded = hosts.iter().filter(|host| host.success).count();
+            let mut json_output = serde_json::json!({
+                "summary": {
+                    "total": summary.total,
+                    "success": summary.sent + summary.skipped,
+                    "sent": summary.sent,
+                    "skipped": summary.skipped,
+                    "failed": summary.failed,
+                    "bytes": summary.bytes,
+                    "hosts_succeeded": hosts_succeeded,
+                    "hosts_failed": hosts.len() - hosts_succeeded,
+                },
+                "hosts": hosts,
+                "results": report.records,
+            });
+            if let Some(backup) = &run_backup {
+                json_output["run_id"] = serde_json::json!(backup.run_id);
+            }
+            println!("{}", serde_json::to_string_pretty(&json_output)?);
+        }
+
+        report.check(args.min_success)?;
+    }
+
+    Ok(())
+}
+
+struct SyncArgs {
+    local_dir: PathBuf,
+    dest: String,
+    env: Option<String>,
+    region: Option<String>,
+    server_type: Option<String>,
+    delete: bool,
+    dry_run: bool,
+    become_user: Option<String>,
+    tree_options: TreeOptions,
+    upload_options: UploadOptions,
+    retry: RetryPolicy,
+    output_format: String,
+    non_interactive: bool,
+    yes: bool,
+    overrides: HostOptions,
+    password_cache: PasswordCache,
+}
+
+/// Nombre de chemins affichés par action et par serveur dans le plan
+const SYNC_PLAN_PREVIEW: usize = 20;
+
+/// Gère la commande sync: plan par serveur, confirmation, puis application
+async fn handle_sync_command(args: SyncArgs) -> Result<()> {
+    use crate::interactive::is_interactive_mode;
+
+    let json = args.output_format == "json";
+    if !args.local_dir.is_dir() {
+        anyhow::bail!(
+            "❌ {} n'est pas un répertoire local",
+            args.local_dir.display()
+        );
+    }
+    if args.dest.trim_end_matches('/').is_empty() {
+        anyhow::bail!("❌ Refus de synchroniser la racine / du serveur");
+    }
+
+    let config = HostsConfig::load()?;
+    let target_hosts = config.filter_hosts(
+        args.env.as_ref(),
+        args.region.as_ref(),
+        args.server_type.as_ref(),
+    );
+    if target_hosts.is_empty() {
+        anyhow::bail!("❌ Aucun serveur trouvé avec les critères spécifiés");
+    }
+
+    let tree = UploadTree::scan(&args.local_dir, &args.tree_options)?;
+    if !json {
+        println!("🔄 xsshend - Synchronisation Multi-SSH");
+        println!(
+            "📁 {} ({} fichier(s)) → {} sur {} serveur(s){}",
+            args.local_dir.display(),
+            tree.file_count(),
+            args.dest,
+            target_hosts.len(),
+            if args.delete {
+                " | 🗑️  --delete"
+            } else {
+                ""
+            }
+        );
+        if !args.tree_options.exclude.is_empty() {
+            println!(
+                "🛡️  Chemins protégés: {}",
+                args.tree_options.exclude.join(", ")
+            );
+        }
+        println!("\n🔍 Comparaison avec les serveurs...\n");
+    }
+
+    let uploader = Uploader::with_overrides(args.overrides)
+        .with_password_cache(args.password_cache)
+        .with_become(args.become_user)
+        .with_tree_options(args.tree_options)
+        .with_upload_options(args.upload_options)
+        .with_retry(args.retry)
+        .with_quiet(json);
+    let plans = uploader
+        .plan_sync(&tree, &target_hosts, &args.dest, args.delete)
+        .await;
+
+    if !json {
+        for host_plan in &plans {
+            print_sync_plan(host_plan, args.delete);
+        }
+    }
+
+    let pending = plans.iter().any(|host_plan| {
+        host_plan
+            .plan
+            .as_ref()
+            .is_ok_and(|plan| !plan.is_empty(args.delete))
+    });
+    let plan_failures = plans
+        .iter()
+        .filter(|host_plan| host_plan.plan.is_err())
+        .count();
+
+    if args.dry_run || !pending {
+        if json {
+            let json_plans: Vec<_> = plans.iter().map(sync_plan_json).collect();
+            println!("{}", serde_json::to_string_pretty(&json_plans)?);
+        } else if args.dry_run {
+            println!("🔍 Mode dry-run - aucune modification effectuée");
+        } else if plan_failures == 0 {
+            println!("✅ Déjà synchronisé, rien à faire");
+        }
+        if plan_failures > 0 {
+            anyhow::bail!("❌ {} serveur(s) non comparé(s)", plan_failures);
+        }
+        return Ok(());
+    }
+
+    if !args.yes {
+        if !args.non_interactive && is_interactive_mode() {
+            let confirmed = dialoguer::Confirm::new()
+                .with_prompt(format!(
+                    "Appliquer la synchronisation sur {} serveur(s) ?",
+                    plans.len() - plan_failures
+                ))
+                .default(false)
+                .interact()?;
+            if !confirmed {
+                println!("❌ Synchronisation annulée");
+                return Ok(());
+            }
+        } else {
+            println!("⚠️  Utilisez --yes pour confirmer automatiquement en mode non-interactif");
+            anyhow::bail!("Confirmation requise");
+        }
+    }
+
+    let records = uploader.sync(&plans, &target_hosts, args.delete).await;
+    let summary = SyncSummary::of(&records);
+    if json {
+        let json_output = serde_json::json!({
+            "summary": {
+                "added": summary.added,
+                "updated": summary.updated,
+                "deleted": summary.deleted,
+                "failed": summary.failed,
+                "bytes": summary.bytes,
+            },
+            "results": records,
+        });
+        println!("{}", serde_json::to_string_pretty(&json_output)?);
+    }
+    if summary.failed > 0 {
+        anyhow::bail!("❌ {} changement(s) en échec", summary.failed);
+    }
+    Ok(())
+}
+
+/// Affiche le plan d'un serveur: décomptes puis chemins par action
+fn print_sync_plan(host_plan: &HostSyncPlan, delete: bool) {
+    let plan = match &host_plan.plan {
+        Ok(plan) => plan,
+        Err(e) => {
+            println!("❌ {} : {:#}\n", host_plan.host, e);
+            return;
+        }
+    };
+    println!(
+        "🖥️  {} → {} : {} ajout(s), {} mise(s) à jour, {} suppression(s){}, {} inchangé(s){}",
+        host_plan.host,
+        host_plan.destination,
+        plan.count(SyncAction::Add),
+        plan.count(SyncAction::Update),
+        plan.count(SyncAction::Delete),
+        if delete || plan.extraneous.is_empty() {
+            ""
+        } else {
+            " (ignorées sans --delete)"
+        },
+        plan.unchanged,
+        if plan.conflicts.is_empty() {
+            String::new()
+        } else {
+            format!(
+                ", {} conflit(s) de type (remplacés avec --delete)",
+                plan.conflicts.len()
+            )
+        }
+    );
+    for action in [
+        SyncAction::Add,
+        SyncAction::Update,
+        SyncAction::Delete,
+        SyncAction::Conflict,
+    ] {
+        let paths = plan.paths(action);
+        for path in paths.iter().take(SYNC_PLAN_PREVIEW) {
+            println!("   {} {}", action.symbol(), path);
+        }
+        if paths.len() > SYNC_PLAN_PREVIEW {
+            println!(
+                "   {} … et {} autre(s) ({})",
+                action.symbol(),
+                paths.len() - SYNC_PLAN_PREVIEW,
+                action
+            );
+        }
+    }
+    println!();
+}
+
+/// Plan d'un serveur pour --output-format json
+fn sync_plan_json(host_plan: &HostSyncPlan) -> serde_json::Value {
+    match &host_plan.plan {
+        Ok(plan) => serde_json::json!({
+            "host": host_plan.host,
+            "destination": host_plan.destination,
+            "add": plan.paths(SyncAction::Add),
+            "update": plan.paths(SyncAction::Update),
+            "delete": plan.paths(SyncAction::Delete),
+            "conflicts": plan.paths(SyncAction::Conflict),
+            "unchanged": plan.unchanged,
+        }),
+        Err(e) => serde_json::json!({
+            "host": host_plan.host,
+            "destination": host_plan.destination,
+            "error": format!("{:#}", e),
+        }),
+    }
+}
+
+struct DownloadArgs {
+    remote_path: String,
+    env: Option<String>,
+    region: Option<String>,
+    server_type: Option<String>,
+    into: PathBuf,
+    template: PathTemplate,
+    recursive: bool,
+    retry: RetryPolicy,
+    concurrency: Concurrency,
+    output_format: String,
+    non_interactive: bool,
+    yes: bool,
+    overrides: HostOptions,
+    password_cache: PasswordCache,
+}
+
+/// Gère la commande download: copie un chemin distant de chaque serveur en local
+async fn handle_download_command(args: DownloadArgs) -> Result<()> {
+    use crate::interactive::is_interactive_mode;
+
+    let json = args.output_format == "json";
+    let config = HostsConfig::load()?;
+    let target_hosts = config.filter_hosts(
+        args.env.as_ref(),
+        args.region.as_ref(),
+        args.server_type.as_ref(),
+    );
+
+    if target_hosts.is_empty() {
+        anyhow::bail!("❌ Aucun serveur trouvé avec les critères spécifiés");
+    }
+
+    if !json {
+        println!("📥 xsshend - Téléchargement Multi-SSH");
+        println!(
+            "🎯 {} serveur(s) ciblé(s) | chemin: {}{}",
+            target_hosts.len(),
+            args.remote_path,
+            if args.recursive { " | récursif" } else { "" }
+        );
+    }
+
+    if !args.yes && !args.non_interactive && !json && is_interactive_mode() {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Télécharger {} depuis {} serveur(s) vers {} ?",
+                args.remote_path,
+                target_hosts.len(),
+                args.into.display()
+            ))
+            .default(true)
+            .interact()?;
+        if !confirmed {
+            println!("❌ Téléchargement annulé");
+            return Ok(());
+        }
+    }
+
+    let downloader = Downloader::with_overrides(args.overrides)
+        .with_password_cache(args.password_cache)
+        .with_recursive(args.recursive)
+        .with_template(args.template)
+        .with_retry(args.retry)
+        .with_concurrency(args.concurrency)
+        .with_quiet(json);
+    let records = downloader
+        .download(&args.remote_path, &target_hosts, &args.into)
+        .await?;
+
+    if json {
+        let summary = DownloadSummary::of(&records);
+        let json_output = serde_json::json!({
+            "summary": {
+                "total": summary.total,
+                "success": summary.succeeded,
+                "failed": summary.failed,
+                "bytes": summary.bytes,
+            },
+            "results": records,
+        });
+        println!("{}", serde_json::to_string_pretty(&json_output)?);
+    }
+
+    Ok(())
+}
+
+struct RollbackArgs {
+    run_id: Option<String>,
+    dry_run: bool,
+    non_interactive: bool,
+    yes: bool,
+    overrides: HostOptions,
+    password_cache: PasswordCache,
+}
+
+/// Gère la commande rollback: restaure les versions sauvegardées par un upload --backup
+async fn handle_rollback_command(args: RollbackArgs) -> Result<()> {
+    use crate::interactive::is_interactive_mode;
+
+    let Some(run_id) = args.run_id else {
+        let journals = RunJournal::list()?;
+        if journals.is_empty() {
+            println!("📭 Aucune exécution journalisée (voir upload --backup)");
+            return Ok(());
+        }
+        println!("🗂️  Exécutions journalisées:");
+        for journal in journals {
+            println!(
+                "   • {} — {} — {} fichier(s) sur {} serveur(s)",
+                journal.run_id,
+                journal.started_at.format("%Y-%m-%d %H:%M:%S"),
+                journal.entries.len(),
+                journal.hosts().len()
+            );
+        }
+        return Ok(());
+    };
+
+    let journal = RunJournal::load(&run_id)?;
+    if journal.entries.is_empty() {
+        println!("📭 L'exécution {} n'a remplacé aucun fichier", run_id);
+        return Ok(());
+    }
+
+    let config = HostsConfig::load()?;
+    let all_hosts = config.filter_hosts(None, None, None);
+    let journal_hosts = journal.hosts();
+    let target_hosts: Vec<_> = all_hosts
+        .into_iter()
+        .filter(|(name, _)| journal_hosts.contains(&name.as_str()))
+        .collect();
+
+    println!("⏪ xsshend - Restauration de l'exécution {}", run_id);
+    println!(
+        "📅 Téléversement du {}",
+        journal.started_at.format("%Y-%m-%d %H:%M:%S")
+    );
+    if let Some(user) = &journal.become_user {
+        println!("👤 Restauration via sudo en tant que {}", user);
+    }
+    for entry in &journal.entries {
+        match &entry.backup {
+            Some(backup) => println!("   • {} : {} ← {}", entry.host, entry.remote, backup),
+            None => println!(
+                "   • {} : {} (créé, sera supprimé)",
+                entry.host, entry.remote
+            ),
+        }
+    }
+    for host in &journal_hosts {
+        if !target_hosts.iter().any(|(name, _)| name == host) {
+            println!("⚠️  {} absent de hosts.json", host);
+        }
+    }
+
+    if args.dry_run {
+        println!("🔍 Mode dry-run - aucune restauration effectuée");
+        return Ok(());
     }
 
+    if !args.yes {
+        if !args.non_interactive && is_interactive_mode() {
+            let confirmed = dialoguer::Confirm::new()
+                .with_prompt(format!(
+                    "Restaurer {} fichier(s) sur {} serveur(s) ?",
+                    journal.entries.len(),
+                    journal_hosts.len()
+                ))
+                .default(false)
+                .interact()?;
+            if !confirmed {
+                println!("❌ Restauration annulée");
+                return Ok(());
+            }
+        } else {
+            println!("⚠️  Utilisez --yes pour confirmer automatiquement en mode non-interactif");
+            anyhow::bail!("Confirmation requise");
+        }
+    }
+
+    println!();
+    let uploader = Uploader::with_overrides(args.overrides)
+        .with_password_cache(args.password_cache)
+        .with_become(journal.become_user.clone());
+    let records = uploader.rollback(&journal, &target_hosts).await;
+
+    let failed = records.iter().filter(|record| !record.success).count();
+    if failed > 0 {
+        println!("\n⚠️  Fichiers non restaurés:");
+        for record in records.iter().filter(|record| !record.success) {
+            println!("   • {} : {}", record.entry.host, record.entry.remote);
+        }
+        anyhow::bail!(
+            "❌ {} fichier(s) sur {} non restauré(s)",
+            failed,
+            records.len()
+        );
+    }
+    println!("\n✅ {} fichier(s) restauré(s)", records.len());
     Ok(())
 }
 
@@ -830,6 +1953,17 @@ fn init_setup(force: bool) -> Result<()> {
         if key_path.exists() {
             existing_keys.push(key_type);
             println!("✅ Clé trouvée: {}", key_type);
+
+            // Certificat signé par une CA (<clé>-cert.pub)
+            if let Some(certificate) = ssh::keys::SshKey::new(key_type.to_string(), key_path)
+                .ok()
+                .and_then(|key| key.certificate)
+            {
+                println!("   📜 {}", certificate.describe());
+                if let Some(warning) = certificate.expiry_warning() {
+                    println!("   ⚠️  {}", warning);
+                }
+            }
         }
     }
 
@@ -1,10 +1,26 @@
 // Module SSH Agent pour xsshend - Interface avec ssh-agent système
 use anyhow::{Context, Result};
 use russh::keys::agent::client::AgentClient;
-use russh::keys::ssh_key::PublicKey;
+use russh::keys::ssh_key::{Certificate, PublicKey};
 use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::Mutex;
 
+use super::keys::CertificateInfo;
+
+/// Messages du protocole ssh-agent (draft-miller-ssh-agent)
+const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+/// Taille maximale d'une réponse de l'agent acceptée
+const MAX_AGENT_RESPONSE: usize = 256 * 1024;
+
+/// Identités détenues par ssh-agent, certificats séparés des clés simples
+#[derive(Debug, Default)]
+pub struct AgentIdentities {
+    pub keys: Vec<PublicKey>,
+    pub certificates: Vec<CertificateInfo>,
+}
+
 /// Gestionnaire de l'agent SSH
 pub struct SshAgentManager {
     client: Option<Arc<Mutex<AgentClient<tokio::net::UnixStream>>>>,
@@ -33,18 +49,40 @@ impl SshAgentManager {
 
     /// Obtenir la liste des clés disponibles dans l'agent
     pub async fn list_identities(&self) -> Result<Vec<PublicKey>> {
-        if let Some(ref client) = self.client {
-            let mut client_guard = client.lock().await;
-            let identities = client_guard
-                .request_identities()
-                .await
-                .context("Impossible de récupérer les identités de ssh-agent")?;
-
-            log::debug!("🔑 {} clé(s) trouvée(s) dans ssh-agent", identities.len());
-            Ok(identities)
-        } else {
+        if self.client.is_none() {
             anyhow::bail!("ssh-agent n'est pas connecté");
         }
+        Ok(Self::list_all_identities().await?.keys)
+    }
+
+    /// Lister clés et certificats de l'agent.
+    ///
+    /// `AgentClient::request_identities` échoue dès que l'agent détient un certificat;
+    /// la requête est donc faite directement sur SSH_AUTH_SOCK.
+    pub async fn list_all_identities() -> Result<AgentIdentities> {
+        let socket = std::env::var("SSH_AUTH_SOCK").context("SSH_AUTH_SOCK non défini")?;
+        let mut stream = tokio::net::UnixStream::connect(&socket)
+            .await
+            .context("Impossible de se connecter à ssh-agent (SSH_AUTH_SOCK)")?;
+
+        stream
+            .write_all(&[0, 0, 0, 1, SSH_AGENTC_REQUEST_IDENTITIES])
+            .await?;
+        let len = stream.read_u32().await? as usize;
+        if len > MAX_AGENT_RESPONSE {
+            anyhow::bail!("Réponse ssh-agent trop volumineuse ({} octets)", len);
+        }
+        let mut response = vec![0u8; len];
+        stream.read_exact(&mut response).await?;
+
+        let identities = parse_identities_answer(&response)
+            .context("Réponse ssh-agent invalide (liste des identités)")?;
+        log::debug!(
+            "🔑 {} clé(s) et {} certificat(s) trouvé(s) dans ssh-agent",
+            identities.keys.len(),
+            identities.certificates.len()
+        );
+        Ok(identities)
     }
 
     /// Obtenir un clone du client pour l'authentification
@@ -68,6 +106,58 @@ impl SshAgentManager {
     }
 }
 
+/// Décode un message SSH_AGENT_IDENTITIES_ANSWER
+fn parse_identities_answer(mut data: &[u8]) -> Result<AgentIdentities> {
+    fn read_u32(data: &mut &[u8]) -> Result<u32> {
+        if data.len() < 4 {
+            anyhow::bail!("message tronqué");
+        }
+        let (head, rest) = data.split_at(4);
+        *data = rest;
+        Ok(u32::from_be_bytes([head[0], head[1], head[2], head[3]]))
+    }
+    fn read_string<'a>(data: &mut &'a [u8]) -> Result<&'a [u8]> {
+        let len = read_u32(data)? as usize;
+        if data.len() < len {
+            anyhow::bail!("message tronqué");
+        }
+        let (value, rest) = data.split_at(len);
+        *data = rest;
+        Ok(value)
+    }
+
+    match data.split_first() {
+        Some((&SSH_AGENT_IDENTITIES_ANSWER, rest)) => data = rest,
+        _ => anyhow::bail!("réponse inattendue"),
+    }
+
+    let mut identities = AgentIdentities::default();
+    let count = read_u32(&mut data)?;
+    for _ in 0..count {
+        let blob = read_string(&mut data)?;
+        let _comment = read_string(&mut data)?;
+
+        // Le blob commence par le nom de l'algorithme
+        let mut peek = blob;
+        let algorithm = String::from_utf8_lossy(read_string(&mut peek)?).to_string();
+        if algorithm.ends_with("-cert-v01@openssh.com") {
+            match Certificate::from_bytes(blob) {
+                Ok(certificate) => identities.certificates.push(CertificateInfo {
+                    path: None,
+                    certificate,
+                }),
+                Err(e) => log::debug!("Certificat ssh-agent ignoré ({}): {}", algorithm, e),
+            }
+        } else {
+            match PublicKey::from_bytes(blob) {
+                Ok(key) => identities.keys.push(key),
+                Err(e) => log::debug!("Clé ssh-agent ignorée ({}): {}", algorithm, e),
+            }
+        }
+    }
+    Ok(identities)
+}
+
 impl Default for SshAgentManager {
     fn default() -> Self {
         Self::new()
@@ -77,6 +167,46 @@ impl Default for SshAgentManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use russh::keys::ssh_key::public::KeyData;
+
+    const PLAIN_KEY: &str = "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIDlpPsahZlfxjZcHYT7/eQW4T7edyqY1apjoN5blk0Il alice@laptop";
+
+    /// Certificat de PLAIN_KEY signé par une CA de test
+    const CERT: &str = "ssh-ed25519-cert-v01@openssh.com AAAAIHNzaC1lZDI1NTE5LWNlcnQtdjAxQG9wZW5zc2guY29tAAAAIOzr3AyYt2efH9H43i4mvixpn7E3BesYOZzku4yz977cAAAAIDlpPsahZlfxjZcHYT7/eQW4T7edyqY1apjoN5blk0IlAAAAAAAAAAAAAAABAAAACmFsaWNlLTIwMjUAAAATAAAABWFsaWNlAAAABmRlcGxveQAAAABndIWAAAAAAI0TIwAAAAAAAAAAggAAABVwZXJtaXQtWDExLWZvcndhcmRpbmcAAAAAAAAAF3Blcm1pdC1hZ2VudC1mb3J3YXJkaW5nAAAAAAAAABZwZXJtaXQtcG9ydC1mb3J3YXJkaW5nAAAAAAAAAApwZXJtaXQtcHR5AAAAAAAAAA5wZXJtaXQtdXNlci1yYwAAAAAAAAAAAAAAMwAAAAtzc2gtZWQyNTUxOQAAACAucwE8Je/Wlzbnlj4CK/HY6NbP6R0MI3/VUZ4f54U+lAAAAFMAAAALc3NoLWVkMjU1MTkAAABAGwSxd+km48fjCrY5A/xUDggQRhr5CPr4Tn/RXF7HzlNyTOTDnKrCrizaq2PkiIhJj5OXfJeehTBU1Uvi1KayBA== alice@laptop";
+
+    fn push_string(buf: &mut Vec<u8>, value: &[u8]) {
+        buf.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        buf.extend_from_slice(value);
+    }
+
+    #[test]
+    fn test_parse_identities_with_certificate() {
+        let key = PublicKey::from_openssh(PLAIN_KEY).unwrap();
+        let cert = Certificate::from_openssh(CERT).unwrap();
+
+        let mut answer = vec![SSH_AGENT_IDENTITIES_ANSWER];
+        answer.extend_from_slice(&2u32.to_be_bytes());
+        push_string(&mut answer, &key.to_bytes().unwrap());
+        push_string(&mut answer, b"alice@laptop");
+        push_string(&mut answer, &cert.to_bytes().unwrap());
+        push_string(&mut answer, b"alice-cert");
+
+        let identities = parse_identities_answer(&answer).unwrap();
+        assert_eq!(identities.keys.len(), 1);
+        assert_eq!(identities.certificates.len(), 1);
+        assert_eq!(identities.certificates[0].key_id(), "alice-2025");
+        assert_eq!(
+            identities.certificates[0].certificate.public_key(),
+            &KeyData::from(key)
+        );
+    }
+
+    #[test]
+    fn test_parse_identities_rejects_truncated() {
+        let answer = [SSH_AGENT_IDENTITIES_ANSWER, 0, 0, 0, 1, 0, 0, 0, 9];
+        assert!(parse_identities_answer(&answer).is_err());
+        assert!(parse_identities_answer(&[5]).is_err());
+    }
 
     #[tokio::test]
     async fn test_agent_connection_no_panic() {
@@ -1,42 +1,166 @@
 // Client SSH/SFTP pour xsshend - Implémentation Pure Rust avec russh
 use anyhow::{Context, Result};
-use russh::client::{self, Handle};
+use data_encoding::HEXLOWER;
+use russh::client::{self, AuthResult, Handle, KeyboardInteractiveAuthResponse, Msg};
 use russh::keys::*;
-use russh_sftp::client::SftpSession;
-use std::path::Path;
+use russh::ChannelStream;
+use russh::MethodKind;
+use russh_sftp::protocol::{FileAttributes, OpenFlags, Packet, StatusCode};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
 
 use super::agent::SshAgentManager;
-use super::keys::{PassphraseCache, SshKey, SshKeyManager};
+use super::keys::{CertificateInfo, PassphraseCache, SshKey, SshKeyManager};
+use super::known_hosts::{HostKeyError, HostKeyPolicy, KnownHosts};
+use super::password::PasswordCache;
+use super::pipeline::Pipeline;
+use super::sftp::{Sftp, POSIX_RENAME};
+use super::sudo::{self, PromptFilter};
+use super::target::{self, DEFAULT_SSH_PORT};
+use crate::config::HostOptions;
+use crate::utils::interrupt::{self, RunningCommand};
+use crate::utils::path_expansion;
+use crate::utils::progress::TransferProgress;
+
+/// Nombre de mots de passe essayés par hôte, comme OpenSSH
+const MAX_PASSWORD_ATTEMPTS: usize = 3;
+/// Garde-fou contre un serveur keyboard-interactive qui ne conclut jamais
+const MAX_KEYBOARD_INTERACTIVE_ROUNDS: usize = 10;
+/// Durée maximale du calcul d'empreinte distant (fichiers de plusieurs Go)
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(600);
+/// Affiché par la commande de sauvegarde quand une copie a été faite
+const BACKUP_DONE: &str = "xsshend-backup-done";
+/// Mode des fichiers en cours d'écriture: illisibles des autres utilisateurs
+const STAGING_MODE: u32 = 0o600;
 
 /// Handler pour les événements du client SSH
-struct ClientHandler;
+struct ClientHandler {
+    host: String,
+    port: u16,
+    host_key_policy: HostKeyPolicy,
+}
 
 impl client::Handler for ClientHandler {
     type Error = anyhow::Error;
 
     async fn check_server_key(
         &mut self,
-        _server_public_key: &ssh_key::PublicKey,
+        server_public_key: &ssh_key::PublicKey,
     ) -> Result<bool, Self::Error> {
-        // Pour l'instant, accepter toutes les clés serveur
-        // TODO: Vérifier contre known_hosts
+        let known_hosts_path = KnownHosts::default_path()?;
+        KnownHosts::verify(
+            &known_hosts_path,
+            &self.host,
+            self.port,
+            server_public_key,
+            self.host_key_policy,
+        )?;
         Ok(true)
     }
 }
 
+/// Options de connexion résolues pour un hôte
+#[derive(Debug, Clone, Default)]
+pub struct ConnectOptions {
+    /// Politique de vérification de la clé d'hôte
+    pub host_key_policy: HostKeyPolicy,
+    /// Clé privée imposée (--key ou identity_file), essayée en premier
+    pub identity_file: Option<PathBuf>,
+    /// N'utiliser que `identity_file` (ni ssh-agent ni clés découvertes)
+    pub identities_only: bool,
+    /// Délai de connexion (ConnectTimeout de ~/.ssh/config), sinon celui du pool
+    pub connect_timeout: Option<Duration>,
+}
+
+impl ConnectOptions {
+    /// Construit les options à partir de celles déclarées dans hosts.json
+    pub fn from_host_options(options: &HostOptions) -> Self {
+        ConnectOptions {
+            host_key_policy: options.host_key_policy.unwrap_or_default(),
+            identity_file: options
+                .identity_file
+                .as_deref()
+                .map(path_expansion::expand_local_path),
+            identities_only: options.identities_only.unwrap_or(false),
+            connect_timeout: None,
+        }
+    }
+}
+
+/// Connexion SSH authentifiée, partagée entre plusieurs `SshClient`: chacun y
+/// ouvre sa propre session SFTP, pour envoyer plusieurs fichiers à la fois
+#[derive(Clone)]
+pub struct SharedConnection {
+    handle: Arc<Handle<ClientHandler>>,
+    host: String,
+    username: String,
+    port: u16,
+    passphrase_cache: PassphraseCache,
+    password_cache: PasswordCache,
+    options: ConnectOptions,
+    remote_home: Option<String>,
+}
+
+impl SharedConnection {
+    /// Nouveau client sur cette connexion, avec sa session SFTP
+    pub async fn open_client(&self) -> Result<SshClient> {
+        if self.handle.is_closed() {
+            anyhow::bail!("Connexion SSH fermée");
+        }
+        let sftp = open_sftp(&self.handle).await?;
+        Ok(SshClient {
+            handle: Some(Arc::clone(&self.handle)),
+            sftp: Some(sftp),
+            host: self.host.clone(),
+            username: self.username.clone(),
+            port: self.port,
+            passphrase_cache: self.passphrase_cache.clone(),
+            password_cache: self.password_cache.clone(),
+            options: self.options.clone(),
+            remote_home: self.remote_home.clone(),
+            known_dirs: HashSet::new(),
+        })
+    }
+}
+
+/// Ouvre un canal et y démarre le sous-système SFTP
+async fn open_sftp(handle: &Handle<ClientHandler>) -> Result<Sftp> {
+    let channel = handle.channel_open_session().await?;
+
+    // Demander le sous-système SFTP (étape cruciale !)
+    channel
+        .request_subsystem(true, "sftp")
+        .await
+        .context("Impossible de demander le sous-système SFTP")?;
+
+    Sftp::start(channel.into_stream())
+        .await
+        .context("Impossible de créer la session SFTP")
+}
+
 /// Client SSH/SFTP asynchrone avec support ssh-agent et cache de passphrases
 pub struct SshClient {
-    handle: Option<Handle<ClientHandler>>,
-    sftp: Option<SftpSession>,
+    /// Partagée avec les sessions SFTP supplémentaires (voir `SharedConnection`)
+    handle: Option<Arc<Handle<ClientHandler>>>,
+    /// Seule session SFTP du client: un canal par client
+    sftp: Option<Sftp>,
     host: String,
     username: String,
     port: u16,
     passphrase_cache: PassphraseCache,
+    password_cache: PasswordCache,
+    options: ConnectOptions,
     /// Répertoire HOME réel récupéré du serveur distant
     remote_home: Option<String>,
+    /// Répertoires distants déjà vérifiés ou créés sur cette connexion
+    known_dirs: HashSet<String>,
 }
 
 impl SshClient {
@@ -47,47 +171,48 @@ impl SshClient {
             sftp: None,
             host: host.to_string(),
             username: username.to_string(),
-            port: 22,
+            port: DEFAULT_SSH_PORT,
             passphrase_cache: cache,
+            password_cache: PasswordCache::new(),
+            options: ConnectOptions::default(),
             remote_home: None,
+            known_dirs: HashSet::new(),
         })
     }
 
-    /// Se connecter au serveur SSH avec timeout
-    pub async fn connect_with_timeout(&mut self, timeout: Duration) -> Result<()> {
-        let addr = format!("{}:{}", self.host, self.port);
-
-        log::debug!("Connexion à {}...", addr);
-
-        // Configuration du client SSH
-        let config = Arc::new(russh::client::Config::default());
-        let handler = ClientHandler;
-
-        // Connexion avec timeout
-        let mut session =
-            tokio::time::timeout(timeout, russh::client::connect(config, &addr, handler))
-                .await
-                .context("Timeout de connexion SSH")?
-                .context("Impossible de se connecter au serveur SSH")?;
+    /// Utiliser un port SSH spécifique
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
 
-        // Authentification
-        self.authenticate(&mut session).await?;
+    /// Partager le cache de mots de passe de l'exécution
+    pub fn with_password_cache(mut self, cache: PasswordCache) -> Self {
+        self.password_cache = cache;
+        self
+    }
 
-        // Créer le canal SFTP
-        let channel = session.channel_open_session().await?;
+    /// Appliquer des options de connexion spécifiques à l'hôte
+    pub fn with_options(mut self, options: ConnectOptions) -> Self {
+        self.options = options;
+        self
+    }
 
-        // Demander le sous-système SFTP (étape cruciale !)
-        channel
-            .request_subsystem(true, "sftp")
-            .await
-            .context("Impossible de demander le sous-système SFTP")?;
+    /// Se connecter au serveur SSH avec timeout
+    pub async fn connect_with_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.connect_via(None, timeout).await
+    }
 
-        // Créer la session SFTP
-        let sftp = SftpSession::new(channel.into_stream())
-            .await
-            .context("Impossible de créer la session SFTP")?;
+    /// Se connecter au serveur SSH, directement ou à travers un hôte de rebond déjà connecté
+    pub async fn connect_via(
+        &mut self,
+        jump: Option<&Mutex<SshClient>>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let session = self.open_session(jump, timeout).await?;
+        let sftp = open_sftp(&session).await?;
 
-        self.handle = Some(session);
+        self.handle = Some(Arc::new(session));
         self.sftp = Some(sftp);
 
         // Récupérer le répertoire HOME réel du serveur distant
@@ -102,12 +227,174 @@ impl SshClient {
         Ok(())
     }
 
+    /// Se connecter à un hôte de rebond: session authentifiée seule, sans SFTP
+    pub async fn connect_as_jump_host(
+        &mut self,
+        jump: Option<&Mutex<SshClient>>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let session = self.open_session(jump, timeout).await?;
+        self.handle = Some(Arc::new(session));
+        log::debug!(
+            "✅ Hôte de rebond connecté: {}@{}",
+            self.username,
+            target::format_host_port(&self.host, self.port)
+        );
+        Ok(())
+    }
+
+    /// Ouvre la connexion TCP (ou le tunnel direct-tcpip) puis authentifie
+    async fn open_session(
+        &mut self,
+        jump: Option<&Mutex<SshClient>>,
+        timeout: Duration,
+    ) -> Result<Handle<ClientHandler>> {
+        let addr = target::format_host_port(&self.host, self.port);
+
+        // Configuration du client SSH
+        let config = Arc::new(russh::client::Config::default());
+        let handler = ClientHandler {
+            host: self.host.clone(),
+            port: self.port,
+            host_key_policy: self.options.host_key_policy,
+        };
+
+        let connecting = async {
+            match jump {
+                Some(jump) => {
+                    log::debug!("Connexion à {} via rebond...", addr);
+                    let stream = jump
+                        .lock()
+                        .await
+                        .open_direct_tcpip(&self.host, self.port)
+                        .await?;
+                    russh::client::connect_stream(config, stream, handler).await
+                }
+                None => {
+                    log::debug!("Connexion à {}...", addr);
+                    russh::client::connect(config, (self.host.as_str(), self.port), handler).await
+                }
+            }
+        };
+
+        // Connexion avec timeout
+        let mut session = tokio::time::timeout(timeout, connecting)
+            .await
+            .context("Timeout de connexion SSH")?
+            .map_err(|e| {
+                // Une erreur de clé d'hôte reste visible telle quelle (empreintes)
+                if e.is::<HostKeyError>() {
+                    e
+                } else {
+                    e.context(format!(
+                        "Impossible de se connecter au serveur SSH {}",
+                        addr
+                    ))
+                }
+            })?;
+
+        // Authentification
+        self.authenticate(&mut session).await.map_err(|e| {
+            e.context(AuthenticationError {
+                user: self.username.clone(),
+                host: addr.clone(),
+            })
+        })?;
+        Ok(session)
+    }
+
+    /// Ouvre un tunnel direct-tcpip vers `host:port` à travers cette session
+    pub async fn open_direct_tcpip(&self, host: &str, port: u16) -> Result<ChannelStream<Msg>> {
+        let handle = self
+            .handle
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Session de rebond non établie"))?;
+        let channel = handle
+            .channel_open_direct_tcpip(host, port as u32, "127.0.0.1", 0)
+            .await
+            .with_context(|| {
+                format!(
+                    "Le rebond {}@{} n'a pas pu joindre {}",
+                    self.username,
+                    self.host,
+                    target::format_host_port(host, port)
+                )
+            })?;
+        Ok(channel.into_stream())
+    }
+
+    /// La session SSH est-elle toujours ouverte ?
+    pub fn is_connected(&self) -> bool {
+        self.handle.as_ref().is_some_and(|h| !h.is_closed())
+    }
+
+    /// Connexion établie, pour ouvrir d'autres sessions SFTP sans se réauthentifier
+    pub fn shared_connection(&self) -> Option<SharedConnection> {
+        Some(SharedConnection {
+            handle: Arc::clone(self.handle.as_ref()?),
+            host: self.host.clone(),
+            username: self.username.clone(),
+            port: self.port,
+            passphrase_cache: self.passphrase_cache.clone(),
+            password_cache: self.password_cache.clone(),
+            options: self.options.clone(),
+            remote_home: self.remote_home.clone(),
+        })
+    }
+
     /// Authentification SSH - Stratégie multi-niveaux
+    /// 0. Clé imposée (--key / identity_file), seule si identities_only
     /// 1. ssh-agent (si disponible)
     /// 2. Clés locales avec cache de passphrases
     /// 3. Demande interactive de passphrase
+    /// 4. Mot de passe (cache, --password-stdin ou saisie)
+    /// 5. keyboard-interactive
     async fn authenticate(&mut self, session: &mut Handle<ClientHandler>) -> Result<()> {
-        // Niveau 1: Essayer ssh-agent en premier
+        let key_error = match self.authenticate_with_keys(session).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+
+        // Niveaux 4 & 5: uniquement si le serveur les propose
+        log::debug!("🔑 Authentification par clé impossible: {:#}", key_error);
+        if self.try_password_methods(session).await? {
+            return Ok(());
+        }
+        Err(key_error)
+    }
+
+    /// Niveaux 0 à 3: authentification par clé
+    async fn authenticate_with_keys(&mut self, session: &mut Handle<ClientHandler>) -> Result<()> {
+        // Certificats détenus par ssh-agent: présentés avec la clé privée locale correspondante
+        let agent_certificates = SshAgentManager::list_all_identities()
+            .await
+            .map(|identities| identities.certificates)
+            .unwrap_or_default();
+
+        // Niveau 0: Clé explicitement choisie
+        if let Some(identity_file) = self.options.identity_file.clone() {
+            match self
+                .try_identity_file(session, &identity_file, &agent_certificates)
+                .await
+            {
+                Ok(()) => return Ok(()),
+                Err(e) if self.options.identities_only => {
+                    return Err(e.context(format!(
+                        "Authentification échouée avec la clé imposée {} (identities_only)",
+                        identity_file.display()
+                    )));
+                }
+                Err(e) => {
+                    log::debug!(
+                        "❌ Échec avec la clé imposée {}: {} — essai des autres méthodes",
+                        identity_file.display(),
+                        e
+                    );
+                }
+            }
+        }
+
+        // Niveau 1: Essayer ssh-agent
         log::debug!("🔐 Tentative d'authentification avec ssh-agent...");
         if self.try_ssh_agent_auth(session).await? {
             log::info!("✅ Authentification réussie via ssh-agent");
@@ -127,9 +414,15 @@ impl SshClient {
             // Essayer chaque clé jusqu'à ce qu'une fonctionne
             let mut last_error = None;
             for key in keys {
+                if self.options.identity_file.as_ref() == Some(&key.private_key_path) {
+                    continue; // Déjà essayée au niveau 0
+                }
                 log::debug!("Tentative d'authentification avec la clé: {}", key.name);
 
-                match self.authenticate_with_key(session, key).await {
+                match self
+                    .authenticate_with_key(session, key, &agent_certificates)
+                    .await
+                {
                     Ok(()) => {
                         log::info!("✅ Authentification réussie avec la clé: {}", key.name);
                         return Ok(());
@@ -156,6 +449,136 @@ impl SshClient {
         }
     }
 
+    /// Niveaux 4 et 5: mot de passe puis keyboard-interactive, selon les méthodes du serveur
+    async fn try_password_methods(&mut self, session: &mut Handle<ClientHandler>) -> Result<bool> {
+        let methods = match session.authenticate_none(&self.username).await? {
+            AuthResult::Success => return Ok(true),
+            AuthResult::Failure {
+                remaining_methods, ..
+            } => remaining_methods,
+        };
+        let host_key = format!(
+            "{}@{}",
+            self.username,
+            target::format_host_port(&self.host, self.port)
+        );
+
+        if methods.contains(&MethodKind::Password)
+            && self.try_password_auth(session, &host_key).await?
+        {
+            return Ok(true);
+        }
+        if methods.contains(&MethodKind::KeyboardInteractive)
+            && self
+                .try_keyboard_interactive_auth(session, &host_key)
+                .await?
+        {
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Authentification par mot de passe (jusqu'à 3 essais si saisi)
+    async fn try_password_auth(
+        &mut self,
+        session: &mut Handle<ClientHandler>,
+        host_key: &str,
+    ) -> Result<bool> {
+        for _ in 0..MAX_PASSWORD_ATTEMPTS {
+            let Some(password) = self.password_cache.obtain(host_key).await? else {
+                log::debug!("ℹ️  Aucun mot de passe disponible pour {}", host_key);
+                return Ok(false);
+            };
+
+            let auth_result = session
+                .authenticate_password(&self.username, password.as_str())
+                .await
+                .context("Erreur lors de l'authentification par mot de passe")?;
+            if auth_result.success() {
+                self.password_cache.set(host_key, password);
+                log::info!("✅ Authentification réussie par mot de passe");
+                return Ok(true);
+            }
+
+            log::warn!("❌ Mot de passe refusé pour {}", host_key);
+            self.password_cache.forget(host_key);
+        }
+        Ok(false)
+    }
+
+    /// Authentification keyboard-interactive (PAM, OTP...)
+    async fn try_keyboard_interactive_auth(
+        &mut self,
+        session: &mut Handle<ClientHandler>,
+        host_key: &str,
+    ) -> Result<bool> {
+        let mut response = session
+            .authenticate_keyboard_interactive_start(&self.username, None)
+            .await
+            .context("Erreur lors de l'authentification keyboard-interactive")?;
+        // Mot de passe envoyé pendant l'échange, à mémoriser en cas de succès
+        let mut sent_password = None;
+
+        for _ in 0..MAX_KEYBOARD_INTERACTIVE_ROUNDS {
+            let prompts = match response {
+                KeyboardInteractiveAuthResponse::Success => {
+                    if let Some(password) = sent_password {
+                        self.password_cache.set(host_key, password);
+                    }
+                    log::info!("✅ Authentification keyboard-interactive réussie");
+                    return Ok(true);
+                }
+                KeyboardInteractiveAuthResponse::Failure { .. } => {
+                    if sent_password.is_some() {
+                        log::warn!("❌ Mot de passe refusé pour {}", host_key);
+                        self.password_cache.forget(host_key);
+                    }
+                    return Ok(false);
+                }
+                KeyboardInteractiveAuthResponse::InfoRequest {
+                    instructions,
+                    prompts,
+                    ..
+                } => {
+                    if !instructions.trim().is_empty() {
+                        eprintln!("ℹ️  {}: {}", host_key, instructions.trim());
+                    }
+                    prompts
+                }
+            };
+
+            let mut answers = Vec::with_capacity(prompts.len());
+            for prompt in &prompts {
+                let lowered = prompt.prompt.to_lowercase();
+                let answer = if !prompt.echo
+                    && (lowered.contains("password") || lowered.contains("mot de passe"))
+                {
+                    let password = self.password_cache.obtain(host_key).await?;
+                    sent_password = password.clone();
+                    password
+                } else {
+                    self.password_cache
+                        .ask(host_key, &prompt.prompt, prompt.echo)
+                        .await?
+                };
+
+                match answer {
+                    Some(answer) => answers.push(answer),
+                    None => {
+                        log::debug!("ℹ️  Question keyboard-interactive sans réponse possible");
+                        return Ok(false);
+                    }
+                }
+            }
+
+            response = session
+                .authenticate_keyboard_interactive_respond(answers)
+                .await
+                .context("Erreur lors de l'authentification keyboard-interactive")?;
+        }
+        Ok(false)
+    }
+
     /// Essayer l'authentification via ssh-agent
     async fn try_ssh_agent_auth(&self, session: &mut Handle<ClientHandler>) -> Result<bool> {
         // Essayer de se connecter à ssh-agent
@@ -230,26 +653,90 @@ impl SshClient {
         Ok(false)
     }
 
-    /// Authentification avec une clé spécifique (utilise le cache de passphrases)
+    /// Authentification avec la clé imposée par --key ou identity_file
+    async fn try_identity_file(
+        &mut self,
+        session: &mut Handle<ClientHandler>,
+        identity_file: &Path,
+        agent_certificates: &[CertificateInfo],
+    ) -> Result<()> {
+        if !identity_file.is_file() {
+            anyhow::bail!("Clé SSH introuvable: {}", identity_file.display());
+        }
+
+        let name = identity_file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("identity")
+            .to_string();
+        let key = SshKey::new(name, identity_file.to_path_buf())?;
+
+        log::debug!("🔑 Tentative avec la clé imposée: {}", key.description());
+        self.authenticate_with_key(session, &key, agent_certificates)
+            .await?;
+        log::info!("✅ Authentification réussie avec la clé: {}", key.name);
+        Ok(())
+    }
+
+    /// Authentification avec une clé spécifique (utilise le cache de passphrases).
+    /// Si un certificat OpenSSH valide accompagne la clé, il est présenté en premier.
     async fn authenticate_with_key(
         &mut self,
         session: &mut Handle<ClientHandler>,
         key: &SshKey,
+        agent_certificates: &[CertificateInfo],
     ) -> Result<()> {
         // Charger la clé privée avec gestion de passphrase et cache
-        let key_pair = SshKeyManager::load_key_with_passphrase(
-            &key.private_key_path,
-            true,
-            Some(&self.passphrase_cache),
-        )
-        .context(format!("Impossible de charger la clé {}", key.name))?;
+        let key_pair = Arc::new(
+            SshKeyManager::load_key_with_passphrase(
+                &key.private_key_path,
+                true,
+                Some(&self.passphrase_cache),
+            )
+            .context(format!("Impossible de charger la clé {}", key.name))?,
+        );
+
+        // Certificat: <clé>-cert.pub, sinon un certificat ssh-agent pour la même clé publique
+        let certificate = key.certificate.as_ref().or_else(|| {
+            agent_certificates
+                .iter()
+                .find(|c| c.certificate.public_key() == key_pair.public_key().key_data())
+        });
+        if let Some(certificate) = certificate {
+            if certificate.is_usable() {
+                let auth_result = session
+                    .authenticate_openssh_cert(
+                        &self.username,
+                        Arc::clone(&key_pair),
+                        certificate.certificate.clone(),
+                    )
+                    .await
+                    .context(format!(
+                        "Authentification échouée avec le certificat de {}",
+                        key.name
+                    ))?;
+                if auth_result.success() {
+                    log::info!(
+                        "✅ Authentification réussie avec le {}",
+                        certificate.describe()
+                    );
+                    return Ok(());
+                }
+                log::debug!(
+                    "❌ Certificat de {} refusé, essai de la clé seule",
+                    key.name
+                );
+            } else if let Some(warning) = certificate.expiry_warning() {
+                log::warn!("⚠️  {} — ignoré", warning);
+            }
+        }
 
         // Authentification avec la clé
         let auth_result = session
             .authenticate_publickey(
                 &self.username,
                 PrivateKeyWithHashAlg::new(
-                    Arc::new(key_pair),
+                    key_pair,
                     session.best_supported_rsa_hash().await?.flatten(),
                 ),
             )
@@ -267,9 +754,32 @@ impl SshClient {
         Ok(())
     }
 
-    /// Téléverser un fichier par streaming (optimisé mémoire)
-    pub async fn upload_file(&mut self, local_path: &Path, remote_path: &str) -> Result<u64> {
-        use tokio::io::{AsyncReadExt, BufReader};
+    /// Téléverser un fichier par streaming (optimisé mémoire).
+    ///
+    /// Par défaut le contenu est écrit dans un fichier caché du même répertoire,
+    /// synchronisé puis renommé sur la destination: un lecteur ne voit jamais
+    /// de fichier tronqué. En cas d'échec, le fichier temporaire est supprimé.
+    /// Une destination existante garde son mode et, si possible, son propriétaire:
+    /// le fichier caché est créé en 0600 puis reçoit ceux de la destination.
+    pub async fn upload_file(
+        &mut self,
+        local_path: &Path,
+        remote_path: &str,
+        options: &UploadOptions,
+        progress: &TransferProgress,
+    ) -> Result<UploadedFile> {
+        // Ctrl-C interrompt le transfert au lieu de quitter: le nettoyage a lieu
+        let _running = RunningCommand::start();
+
+        if let Some(check) = options.skip_unchanged {
+            if let Some(skipped) = self.check_unchanged(local_path, remote_path, check).await {
+                return Ok(skipped);
+            }
+        }
+        let backup = match &options.backup {
+            Some(backup) => self.backup_existing(remote_path, backup, None).await?,
+            None => None,
+        };
 
         // S'assurer que le répertoire distant existe
         if let Some(parent_dir) = Path::new(remote_path).parent() {
@@ -277,115 +787,1129 @@ impl SshClient {
                 .await?;
         }
 
-        // Obtenir la session SFTP
-        let sftp = self
-            .sftp
-            .as_mut()
-            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+        let write_path = if options.in_place {
+            remote_path.to_string()
+        } else {
+            partial_path(remote_path)
+        };
+        // En écriture directe, le fichier tronqué garde déjà ses attributs
+        let replaced = if options.in_place {
+            None
+        } else {
+            self.existing_file(remote_path).await
+        };
 
-        // Créer le fichier distant
-        let mut remote_file = sftp
-            .create(remote_path)
-            .await
-            .with_context(|| format!("Impossible de créer le fichier distant: {}", remote_path))?;
+        let resume = if options.resume {
+            self.resume_point(local_path, &write_path).await
+        } else {
+            None
+        };
+        let flags = match resume {
+            Some(_) => OpenFlags::CREATE | OpenFlags::WRITE,
+            None => OpenFlags::CREATE | OpenFlags::TRUNCATE | OpenFlags::WRITE,
+        };
 
-        // Ouvrir le fichier local et créer un BufReader pour lecture par chunks
-        let file = tokio::fs::File::open(local_path)
+        let mut uploaded = match self
+            .send_file(
+                local_path,
+                &write_path,
+                OpenMode {
+                    flags,
+                    mode: replaced.as_ref().map(|_| STAGING_MODE),
+                },
+                resume,
+                options.pipeline,
+                progress,
+            )
             .await
-            .with_context(|| format!("Impossible de lire le fichier local: {:?}", local_path))?;
-
-        let mut reader = BufReader::new(file);
-        let mut buffer = vec![0u8; 65536]; // Buffer réutilisable de 64KB
-        let mut total_bytes = 0u64;
-
-        // Lire et transférer par chunks
-        loop {
-            let n = reader
-                .read(&mut buffer)
-                .await
-                .context("Erreur lors de la lecture du fichier local")?;
-
-            if n == 0 {
-                break; // EOF atteint
+        {
+            Ok(uploaded) => uploaded,
+            Err(e) => {
+                // Avec --resume, le fichier partiel est conservé pour la prochaine tentative
+                if !options.in_place && !options.resume {
+                    self.remove_quietly(&write_path).await;
+                }
+                return Err(e);
             }
+        };
 
-            remote_file
-                .write_all(&buffer[..n])
-                .await
-                .context("Erreur lors de l'écriture du fichier distant")?;
-
-            total_bytes += n as u64;
+        // Vérifié avant le renommage: un fichier corrompu n'est jamais installé
+        let finished = async {
+            if options.verify {
+                self.verify_remote(&write_path, &uploaded.sha256).await?;
+            }
+            // --chmod / --preserve s'appliquent ensuite et l'emportent
+            if let Some(replaced) = &replaced {
+                self.inherit_attributes(&write_path, replaced).await?;
+            }
+            self.apply_attributes(local_path, &write_path, options)
+                .await?;
+            if !options.in_place {
+                self.rename_over(&write_path, remote_path).await?;
+            }
+            Ok(())
         }
+        .await;
 
-        remote_file
-            .shutdown()
-            .await
-            .context("Erreur lors de la fermeture du fichier distant")?;
-
-        log::debug!(
-            "Fichier téléversé: {} -> {} ({} octets)",
-            local_path.display(),
-            remote_path,
-            total_bytes
-        );
-
-        Ok(total_bytes)
-    }
-
-    /// Récupérer le répertoire HOME réel du serveur distant
-    async fn fetch_remote_home(&mut self) -> Result<()> {
-        // Essayer d'abord avec 'pwd' (répertoire de connexion = HOME)
-        if let Ok(output) = self.execute_command("pwd", Duration::from_secs(5)).await {
-            if output.exit_code == 0 {
-                let home = output.stdout.trim();
-                if !home.is_empty() {
-                    self.remote_home = Some(home.to_string());
-                    log::debug!("📂 HOME détecté via pwd: {}", home);
-                    return Ok(());
+        match finished {
+            Ok(()) => {
+                uploaded.verified = options.verify;
+                uploaded.backup = backup;
+                Ok(uploaded)
+            }
+            Err(e) => {
+                if !options.in_place {
+                    self.remove_quietly(&write_path).await;
                 }
+                Err(e)
             }
         }
+    }
 
-        // Fallback avec 'echo $HOME'
-        if let Ok(output) = self
-            .execute_command("echo $HOME", Duration::from_secs(5))
-            .await
-        {
-            if output.exit_code == 0 {
-                let home = output.stdout.trim();
-                if !home.is_empty() && home != "$HOME" {
-                    self.remote_home = Some(home.to_string());
-                    log::debug!("📂 HOME détecté via $HOME: {}", home);
-                    return Ok(());
-                }
-            }
+    /// Fichier distant déjà identique au fichier local (--skip-unchanged) ?
+    /// Dans le doute (fichier absent, illisible...), le fichier est renvoyé.
+    async fn check_unchanged(
+        &mut self,
+        local_path: &Path,
+        remote_path: &str,
+        check: UnchangedCheck,
+    ) -> Option<UploadedFile> {
+        let sftp = self.sftp.as_mut()?;
+        let remote = sftp.metadata(remote_path).await.ok()?;
+        let local = std::fs::metadata(local_path).ok()?;
+        if !remote.file_type().is_file() || remote.size != Some(local.len()) {
+            return None;
         }
 
-        // Dernier recours : supposer /home/username
-        let fallback_home = format!("/home/{}", self.username);
-        self.remote_home = Some(fallback_home.clone());
-        log::warn!(
-            "⚠️  Impossible de détecter HOME, utilisation de fallback: {}",
-            fallback_home
-        );
+        let sha256 = match check {
+            UnchangedCheck::Mtime => {
+                let local_mtime = local
+                    .modified()
+                    .ok()?
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .ok()?
+                    .as_secs();
+                // Plus ancien que le fichier local: modifié depuis le dernier envoi
+                if u64::from(remote.mtime?) < local_mtime {
+                    return None;
+                }
+                String::new()
+            }
+            UnchangedCheck::Hash => {
+                let hasher = hash_prefix(local_path, local.len()).await.ok()?;
+                let local_digest = HEXLOWER.encode(&hasher.finalize());
+                match self.remote_sha256(remote_path, None).await {
+                    Ok(digest) if digest == local_digest => local_digest,
+                    Ok(_) => return None,
+                    Err(e) => {
+                        log::debug!("Empreinte distante indisponible ({}): {:#}", remote_path, e);
+                        return None;
+                    }
+                }
+            }
+        };
 
-        Ok(())
+        log::debug!("⏭️  Inchangé ({}): {}", check, remote_path);
+        Some(UploadedFile {
+            bytes: 0,
+            verified: check == UnchangedCheck::Hash,
+            sha256,
+            skipped: true,
+            backup: None,
+        })
     }
 
-    /// Obtenir le répertoire HOME réel du serveur distant
-    pub fn get_remote_home(&self) -> Option<&str> {
-        self.remote_home.as_deref()
-    }
+    /// Point de reprise d'un transfert interrompu (--resume): taille déjà écrite
+    /// dans `remote_path`, si ce contenu est bien le début du fichier local.
+    /// Renvoie aussi l'empreinte en cours de ce début, pour le SHA-256 final.
+    async fn resume_point(
+        &mut self,
+        local_path: &Path,
+        remote_path: &str,
+    ) -> Option<(u64, Sha256)> {
+        let sftp = self.sftp.as_mut()?;
+        let partial_size = sftp.metadata(remote_path).await.ok()?.size?;
+        let local_size = std::fs::metadata(local_path).ok()?.len();
+        if partial_size == 0 || partial_size > local_size {
+            return None;
+        }
 
-    /// S'assurer que le répertoire distant existe
+        let hasher = match hash_prefix(local_path, partial_size).await {
+            Ok(hasher) => hasher,
+            Err(e) => {
+                log::debug!("Reprise impossible pour {}: {:#}", remote_path, e);
+                return None;
+            }
+        };
+        let local_digest = HEXLOWER.encode(&hasher.clone().finalize());
+
+        match self.remote_sha256(remote_path, Some(partial_size)).await {
+            Ok(digest) if digest == local_digest => {
+                log::info!(
+                    "⏩ Reprise de {} à partir de {} octets sur {}",
+                    remote_path,
+                    partial_size,
+                    local_size
+                );
+                Some((partial_size, hasher))
+            }
+            Ok(_) => {
+                log::warn!(
+                    "⚠️  {} ne correspond pas au début du fichier local: envoi complet",
+                    remote_path
+                );
+                None
+            }
+            Err(e) => {
+                log::debug!("Reprise impossible pour {}: {:#}", remote_path, e);
+                None
+            }
+        }
+    }
+
+    /// Téléverser un fichier en tant qu'un autre utilisateur (--become).
+    ///
+    /// Le fichier est écrit par SFTP dans un fichier temporaire de /tmp (mode 0600),
+    /// puis installé via sudo (propriétaire `user`, mode du fichier local) à côté de
+    /// la destination et renommé sur celle-ci, sauf avec `in_place`.
+    pub async fn upload_file_as(
+        &mut self,
+        local_path: &Path,
+        remote_path: &str,
+        user: &str,
+        options: &UploadOptions,
+        progress: &TransferProgress,
+    ) -> Result<UploadedFile> {
+        let _running = RunningCommand::start();
+
+        if let Some(check) = options.skip_unchanged {
+            if let Some(skipped) = self.check_unchanged(local_path, remote_path, check).await {
+                return Ok(skipped);
+            }
+        }
+        let backup = match &options.backup {
+            Some(backup) => {
+                self.backup_existing(remote_path, backup, Some(user))
+                    .await?
+            }
+            None => None,
+        };
+
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let temp_path = format!("/tmp/.xsshend-{}-{}", std::process::id(), nonce);
+
+        // Création exclusive: ne jamais suivre un lien posé à l'avance dans /tmp
+        let sent = async {
+            let mut uploaded = self
+                .send_file(
+                    local_path,
+                    &temp_path,
+                    OpenMode {
+                        flags: OpenFlags::CREATE | OpenFlags::EXCLUDE | OpenFlags::WRITE,
+                        mode: Some(STAGING_MODE),
+                    },
+                    None,
+                    options.pipeline,
+                    progress,
+                )
+                .await?;
+            if options.verify {
+                self.verify_remote(&temp_path, &uploaded.sha256).await?;
+                uploaded.verified = true;
+            }
+            if options.preserve {
+                // Date reprise par `install -p`
+                self.set_times(local_path, &temp_path).await?;
+            }
+            Ok(uploaded)
+        }
+        .await;
+        let mut uploaded = match sent {
+            Ok(uploaded) => uploaded,
+            Err(e) => {
+                self.remove_quietly(&temp_path).await;
+                return Err(e);
+            }
+        };
+
+        let parent_dir = Path::new(remote_path)
+            .parent()
+            .and_then(|p| p.to_str())
+            .filter(|p| !p.is_empty())
+            .unwrap_or("/");
+        let install = become_install_command(
+            parent_dir,
+            remote_path,
+            &install_args(
+                options.mode.unwrap_or_else(|| local_mode(local_path)),
+                options,
+            ),
+            options.in_place,
+        );
+        // Le fichier temporaire reste en 0600: le shell de connexion le branche
+        // sur l'entrée standard d'`install`, lancé en tant que l'utilisateur cible
+        let exec_options = ExecOptions {
+            become_user: Some(user.to_string()),
+            input_file: Some(temp_path.clone()),
+            ..Default::default()
+        };
+        let result = self
+            .run_checked_with(&install, &exec_options)
+            .await
+            .with_context(|| {
+                format!(
+                    "Installation de {} en tant que {} échouée",
+                    remote_path, user
+                )
+            });
+
+        // Le fichier temporaire appartient à l'utilisateur de connexion
+        self.remove_quietly(&temp_path).await;
+
+        result?;
+        log::debug!("Fichier installé en tant que {}: {}", user, remote_path);
+        uploaded.backup = backup;
+        Ok(uploaded)
+    }
+
+    /// Appliquer mode, date, propriétaire et groupe demandés à un fichier écrit
+    /// (avant son renommage sur la destination)
+    async fn apply_attributes(
+        &mut self,
+        local_path: &Path,
+        remote_path: &str,
+        options: &UploadOptions,
+    ) -> Result<()> {
+        let mode = options
+            .mode
+            .or_else(|| options.preserve.then(|| local_mode(local_path)));
+        if let Some(mode) = mode {
+            self.set_mode(remote_path, mode).await?;
+        }
+        if options.preserve {
+            self.set_times(local_path, remote_path).await?;
+        }
+
+        // En dernier: après chown, l'utilisateur de connexion n'est plus propriétaire
+        if let Some(command) = ownership_command(
+            remote_path,
+            options.owner.as_deref(),
+            options.group.as_deref(),
+        ) {
+            self.run_checked(&command, None).await.with_context(|| {
+                format!("Impossible de changer le propriétaire de {}", remote_path)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Attributs d'un fichier distant existant (absent ou autre type: `None`)
+    async fn existing_file(&mut self, remote_path: &str) -> Option<FileAttributes> {
+        let sftp = self.sftp.as_mut()?;
+        let attributes = sftp.metadata(remote_path).await.ok()?;
+        attributes.file_type().is_file().then_some(attributes)
+    }
+
+    /// Reporter sur `remote_path` le mode et le propriétaire de la destination
+    /// qu'il remplace. Changer de propriétaire demande en général d'être root:
+    /// un refus est seulement journalisé.
+    async fn inherit_attributes(
+        &mut self,
+        remote_path: &str,
+        replaced: &FileAttributes,
+    ) -> Result<()> {
+        let (mode, owner) = inherited_attributes(replaced);
+        if let Some(mode) = mode {
+            self.set_mode(remote_path, mode).await?;
+        }
+        if let (Some(owner), Some(sftp)) = (owner, self.sftp.as_mut()) {
+            if let Err(e) = sftp.set_metadata(remote_path, owner).await {
+                log::debug!(
+                    "Propriétaire de la destination non reporté sur {}: {}",
+                    remote_path,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Changer le mode d'un fichier distant (SFTP setstat)
+    async fn set_mode(&mut self, remote_path: &str, mode: u32) -> Result<()> {
+        let sftp = self
+            .sftp
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+        let attributes = FileAttributes {
+            permissions: Some(mode),
+            ..FileAttributes::empty()
+        };
+        sftp.set_metadata(remote_path, attributes)
+            .await
+            .with_context(|| format!("Impossible de changer le mode de {}", remote_path))
+    }
+
+    /// Reporter les dates d'accès et de modification du fichier local (SFTP setstat)
+    async fn set_times(&mut self, local_path: &Path, remote_path: &str) -> Result<()> {
+        let metadata = std::fs::metadata(local_path)
+            .with_context(|| format!("Impossible de lire: {}", local_path.display()))?;
+        let seconds = |time: std::io::Result<std::time::SystemTime>| {
+            time.ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .and_then(|d| u32::try_from(d.as_secs()).ok())
+        };
+        let mtime = seconds(metadata.modified())
+            .with_context(|| format!("Date de modification illisible: {}", local_path.display()))?;
+        let attributes = FileAttributes {
+            atime: Some(seconds(metadata.accessed()).unwrap_or(mtime)),
+            mtime: Some(mtime),
+            ..FileAttributes::empty()
+        };
+
+        let sftp = self
+            .sftp
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+        sftp.set_metadata(remote_path, attributes)
+            .await
+            .with_context(|| format!("Impossible de changer la date de {}", remote_path))
+    }
+
+    /// Renommer `from` sur `to`, en remplaçant `to` s'il existe.
+    ///
+    /// Utilise l'extension posix-rename@openssh.com (rename(2), atomique) quand le
+    /// serveur la propose. Sinon le SSH_FXP_RENAME standard refuse d'écraser: la
+    /// destination est alors supprimée juste avant, sans garantie d'atomicité.
+    async fn rename_over(&mut self, from: &str, to: &str) -> Result<()> {
+        let sftp = self
+            .sftp
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+        if sftp.posix_rename {
+            let data = encode_strings(&[from, to]);
+            let reply = sftp.session.extended(POSIX_RENAME, data).await;
+            return match reply {
+                Ok(Packet::Status(status)) if status.status_code == StatusCode::Ok => Ok(()),
+                Ok(Packet::Status(status)) => Err(anyhow::anyhow!(
+                    "Impossible de renommer {} en {}: {}",
+                    from,
+                    to,
+                    status.error_message
+                )),
+                Ok(_) => Err(anyhow::anyhow!(
+                    "Réponse inattendue du serveur à {}",
+                    POSIX_RENAME
+                )),
+                Err(e) => {
+                    Err(e).with_context(|| format!("Impossible de renommer {} en {}", from, to))
+                }
+            };
+        }
+
+        if sftp.rename(from, to).await.is_ok() {
+            return Ok(());
+        }
+
+        log::debug!(
+            "posix-rename indisponible, remplacement non atomique de {}",
+            to
+        );
+        if sftp.try_exists(to).await.unwrap_or(false) {
+            sftp.remove_file(to)
+                .await
+                .with_context(|| format!("Impossible de remplacer {}", to))?;
+        }
+        sftp.rename(from, to)
+            .await
+            .with_context(|| format!("Impossible de renommer {} en {}", from, to))
+    }
+
+    /// Supprimer un fichier distant sans échouer (nettoyage après erreur)
+    async fn remove_quietly(&mut self, remote_path: &str) {
+        if let Some(sftp) = self.sftp.as_mut() {
+            if let Err(e) = sftp.remove_file(remote_path).await {
+                log::warn!("⚠️  Impossible de supprimer {}: {}", remote_path, e);
+            }
+        }
+    }
+
+    /// Créer un répertoire distant et ses parents, via sudo si `become_user`
+    pub async fn create_dir_all(
+        &mut self,
+        remote_dir: &str,
+        become_user: Option<&str>,
+    ) -> Result<()> {
+        match become_user {
+            Some(user) => {
+                let command = format!("mkdir -p {}", sudo::shell_quote(remote_dir));
+                self.run_checked(&command, Some(user))
+                    .await
+                    .with_context(|| {
+                        format!("Impossible de créer le répertoire: {}", remote_dir)
+                    })?;
+                Ok(())
+            }
+            None => self.ensure_remote_directory(remote_dir).await,
+        }
+    }
+
+    /// Créer (ou remplacer) un lien symbolique distant `link` → `target`.
+    ///
+    /// Passe par `ln -sfn` : l'ordre des arguments de SSH_FXP_SYMLINK est inversé
+    /// par OpenSSH, le résultat dépendrait du serveur.
+    pub async fn create_symlink(
+        &mut self,
+        link: &str,
+        target: &str,
+        become_user: Option<&str>,
+    ) -> Result<()> {
+        if let Some(parent) = Path::new(link).parent().and_then(|p| p.to_str()) {
+            if !parent.is_empty() {
+                self.create_dir_all(parent, become_user).await?;
+            }
+        }
+
+        let command = format!(
+            "ln -sfn {} {}",
+            sudo::shell_quote(target),
+            sudo::shell_quote(link)
+        );
+        self.run_checked(&command, become_user)
+            .await
+            .with_context(|| format!("Impossible de créer le lien {} -> {}", link, target))?;
+        Ok(())
+    }
+
+    /// Copier le fichier distant existant avant de le remplacer (--backup).
+    /// Renvoie le chemin de la copie, `None` si la destination n'existait pas.
+    async fn backup_existing(
+        &mut self,
+        remote_path: &str,
+        backup: &BackupOptions,
+        become_user: Option<&str>,
+    ) -> Result<Option<String>> {
+        let dir = backup
+            .dir
+            .as_deref()
+            .map(|dir| path_expansion::expand_path(dir, &self.username, self.get_remote_home()))
+            .transpose()
+            .context("Erreur lors de l'expansion du répertoire de sauvegarde")?;
+        let copy = backup_path(remote_path, &backup.run_id, dir.as_deref());
+        let parent = Path::new(&copy)
+            .parent()
+            .and_then(|p| p.to_str())
+            .filter(|p| !p.is_empty())
+            .unwrap_or(".");
+
+        let command = format!(
+            "if [ -f {} ]; then mkdir -p {} && cp -p {} {} && echo {}; fi",
+            sudo::shell_quote(remote_path),
+            sudo::shell_quote(parent),
+            sudo::shell_quote(remote_path),
+            sudo::shell_quote(&copy),
+            BACKUP_DONE
+        );
+        let output = self
+            .run_checked(&command, become_user)
+            .await
+            .with_context(|| format!("Sauvegarde de {} impossible", remote_path))?;
+
+        if output.contains(BACKUP_DONE) {
+            log::debug!("💾 Sauvegarde: {} -> {}", remote_path, copy);
+            Ok(Some(copy))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Annuler le remplacement d'un fichier (xsshend rollback): recopier la
+    /// sauvegarde sur la destination, ou supprimer un fichier qui n'existait pas.
+    /// La sauvegarde est conservée.
+    pub async fn restore_backup(
+        &mut self,
+        remote_path: &str,
+        backup: Option<&str>,
+        become_user: Option<&str>,
+    ) -> Result<()> {
+        let command = match backup {
+            Some(backup) => {
+                let partial = sudo::shell_quote(&partial_path(remote_path));
+                format!(
+                    "cp -p {} {} && mv -f {} {} || {{ rm -f {}; exit 1; }}",
+                    sudo::shell_quote(backup),
+                    partial,
+                    partial,
+                    sudo::shell_quote(remote_path),
+                    partial
+                )
+            }
+            None => format!("rm -f {}", sudo::shell_quote(remote_path)),
+        };
+        self.run_checked(&command, become_user)
+            .await
+            .with_context(|| format!("Restauration de {} impossible", remote_path))?;
+        Ok(())
+    }
+
+    /// Chemins distants correspondant à `pattern` (jokers `*`, `?`, `[...]`),
+    /// développés par le shell du serveur. Un chemin sans joker est renvoyé tel quel.
+    pub async fn expand_remote_glob(&mut self, pattern: &str) -> Result<Vec<String>> {
+        if !has_glob(pattern) {
+            return Ok(vec![pattern.to_string()]);
+        }
+        let command = format!(
+            "for f in {}; do if [ -e \"$f\" ]; then printf '%s\\n' \"$f\"; fi; done",
+            glob_quote(pattern)
+        );
+        let output = self
+            .run_checked(&command, None)
+            .await
+            .with_context(|| format!("Développement de {} impossible", pattern))?;
+        Ok(output
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Fichiers à télécharger pour `remote_path`: le fichier lui-même ou, avec
+    /// `recursive`, tous les fichiers du répertoire (les liens vers un fichier
+    /// sont suivis, ceux vers un répertoire ignorés)
+    pub async fn list_remote_files(
+        &mut self,
+        remote_path: &str,
+        recursive: bool,
+    ) -> Result<Vec<RemoteFile>> {
+        let sftp = self
+            .sftp
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+        let metadata = sftp
+            .metadata(remote_path)
+            .await
+            .with_context(|| format!("Fichier distant introuvable: {}", remote_path))?;
+        if !metadata.file_type().is_dir() {
+            return Ok(vec![RemoteFile {
+                path: remote_path.to_string(),
+                size: metadata.len(),
+            }]);
+        }
+        if !recursive {
+            anyhow::bail!("{} est un répertoire (utilisez --recursive)", remote_path);
+        }
+
+        let mut files = Vec::new();
+        let mut pending = vec![remote_path.trim_end_matches('/').to_string()];
+        while let Some(dir) = pending.pop() {
+            let listing = if dir.is_empty() { "/" } else { dir.as_str() };
+            let entries = sftp
+                .read_dir(listing)
+                .await
+                .with_context(|| format!("Impossible de lister {}", listing))?;
+            for entry in entries {
+                let path = format!("{}/{}", dir, entry.file_name());
+                let file_type = entry.file_type();
+                if file_type.is_dir() {
+                    pending.push(path);
+                } else if file_type.is_file() {
+                    let size = entry.metadata().len();
+                    files.push(RemoteFile { path, size });
+                } else if file_type.is_symlink() {
+                    match sftp.metadata(path.as_str()).await {
+                        Ok(target) if target.file_type().is_file() => {
+                            let size = target.len();
+                            files.push(RemoteFile { path, size });
+                        }
+                        _ => log::debug!("Lien ignoré: {}", path),
+                    }
+                }
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Contenu d'un répertoire distant, récursivement, avec des chemins relatifs
+    /// à `remote_dir` (liens symboliques non suivis). Vide si le répertoire n'existe pas.
+    pub async fn list_remote_tree(&mut self, remote_dir: &str) -> Result<Vec<RemoteEntry>> {
+        let sftp = self
+            .sftp
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+        if !sftp
+            .try_exists(remote_dir)
+            .await
+            .with_context(|| format!("Impossible de lire {}", remote_dir))?
+        {
+            return Ok(Vec::new());
+        }
+        if !sftp.metadata(remote_dir).await?.file_type().is_dir() {
+            anyhow::bail!("{} n'est pas un répertoire", remote_dir);
+        }
+
+        let base = remote_dir.trim_end_matches('/');
+        let mut entries = Vec::new();
+        let mut pending = vec![String::new()];
+        while let Some(relative) = pending.pop() {
+            let dir = match (base.is_empty(), relative.is_empty()) {
+                (true, true) => "/".to_string(),
+                (_, true) => base.to_string(),
+                _ => format!("{}/{}", base, relative),
+            };
+            let listing = sftp
+                .read_dir(dir.as_str())
+                .await
+                .with_context(|| format!("Impossible de lister {}", dir))?;
+            for entry in listing {
+                let path = if relative.is_empty() {
+                    entry.file_name()
+                } else {
+                    format!("{}/{}", relative, entry.file_name())
+                };
+                let file_type = entry.file_type();
+                let kind = if file_type.is_dir() {
+                    pending.push(path.clone());
+                    RemoteKind::Dir
+                } else if file_type.is_symlink() {
+                    let target = sftp
+                        .read_link(&format!("{}/{}", base, path))
+                        .await
+                        .unwrap_or_default();
+                    RemoteKind::Symlink { target }
+                } else {
+                    let metadata = entry.metadata();
+                    RemoteKind::File {
+                        size: metadata.len(),
+                        mtime: metadata.mtime,
+                    }
+                };
+                entries.push(RemoteEntry { path, kind });
+            }
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+    /// Supprimer un fichier, un lien ou un répertoire vide distant, via sudo si `become_user`
+    pub async fn remove_remote(
+        &mut self,
+        remote_path: &str,
+        is_dir: bool,
+        become_user: Option<&str>,
+    ) -> Result<()> {
+        match become_user {
+            Some(user) => {
+                let command = format!(
+                    "{} {}",
+                    if is_dir { "rmdir" } else { "rm -f" },
+                    sudo::shell_quote(remote_path)
+                );
+                self.run_checked(&command, Some(user)).await?;
+            }
+            None => {
+                let sftp = self
+                    .sftp
+                    .as_mut()
+                    .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+                if is_dir {
+                    sftp.remove_dir(remote_path).await?;
+                } else {
+                    sftp.remove_file(remote_path).await?;
+                }
+            }
+        }
+        log::debug!("🗑️  Supprimé: {}", remote_path);
+        Ok(())
+    }
+
+    /// Télécharger un fichier distant par streaming vers `local_path`.
+    ///
+    /// Le contenu est écrit dans un fichier caché du même répertoire local puis
+    /// renommé: un fichier tronqué n'apparaît jamais sous son nom final.
+    pub async fn download_file(
+        &mut self,
+        remote_path: &str,
+        local_path: &Path,
+        progress: &TransferProgress,
+    ) -> Result<DownloadedFile> {
+        let _running = RunningCommand::start();
+
+        if let Some(parent) = local_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Impossible de créer {}", parent.display()))?;
+        }
+        let partial = local_partial_path(local_path);
+
+        match self.receive_file(remote_path, &partial, progress).await {
+            Ok(downloaded) => {
+                tokio::fs::rename(&partial, local_path)
+                    .await
+                    .with_context(|| format!("Impossible de renommer {}", partial.display()))?;
+                Ok(downloaded)
+            }
+            Err(e) => {
+                if let Err(cleanup) = tokio::fs::remove_file(&partial).await {
+                    log::debug!("{} non supprimé: {}", partial.display(), cleanup);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Lire un fichier distant et l'écrire dans `local_path`
+    async fn receive_file(
+        &mut self,
+        remote_path: &str,
+        local_path: &Path,
+        progress: &TransferProgress,
+    ) -> Result<DownloadedFile> {
+        let sftp = self
+            .sftp
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+        let handle = sftp
+            .open_read(remote_path)
+            .await
+            .with_context(|| format!("Impossible de lire le fichier distant: {}", remote_path))?;
+
+        let received = async {
+            let mut file = tokio::fs::File::create(local_path)
+                .await
+                .with_context(|| format!("Impossible de créer {}", local_path.display()))?;
+
+            progress.restart_file(0);
+            let mut hasher = Sha256::new();
+            let mut total_bytes = 0;
+            loop {
+                if interrupt::is_interrupted() {
+                    anyhow::bail!("Téléchargement interrompu");
+                }
+
+                let Some(chunk) = sftp
+                    .read_chunk(&handle, total_bytes, usize::MAX)
+                    .await
+                    .context("Erreur lors de la lecture du fichier distant")?
+                else {
+                    break;
+                };
+
+                hasher.update(&chunk);
+                file.write_all(&chunk)
+                    .await
+                    .context("Erreur lors de l'écriture du fichier local")?;
+                total_bytes += chunk.len() as u64;
+                progress.inc(chunk.len() as u64);
+            }
+            file.sync_all()
+                .await
+                .context("Erreur lors de la synchronisation du fichier local")?;
+            Ok((total_bytes, hasher))
+        }
+        .await;
+
+        if let Err(e) = sftp.close(&handle).await {
+            log::debug!("Fermeture de {} impossible: {}", remote_path, e);
+        }
+        let (total_bytes, hasher) = received?;
+
+        log::debug!(
+            "Fichier téléchargé: {} -> {} ({} octets)",
+            remote_path,
+            local_path.display(),
+            total_bytes
+        );
+
+        Ok(DownloadedFile {
+            bytes: total_bytes,
+            sha256: HEXLOWER.encode(&hasher.finalize()),
+        })
+    }
+
+    /// Exécuter une commande utilitaire, en échec si son code de sortie n'est pas 0.
+    /// Renvoie sa sortie standard.
+    async fn run_checked(&mut self, command: &str, become_user: Option<&str>) -> Result<String> {
+        let options = ExecOptions {
+            become_user: become_user.map(str::to_string),
+            ..Default::default()
+        };
+        self.run_checked_with(command, &options).await
+    }
+
+    /// Comme `run_checked`, avec des options d'exécution complètes
+    async fn run_checked_with(&mut self, command: &str, options: &ExecOptions) -> Result<String> {
+        let output = self
+            .execute_command_streaming(command, options, Duration::from_secs(60), |_, _| {})
+            .await?;
+        if output.exit_code != 0 || output.killed {
+            let message = if output.stderr.trim().is_empty() {
+                output.stdout.trim()
+            } else {
+                output.stderr.trim()
+            };
+            anyhow::bail!("{} (code {})", message, output.exit_code);
+        }
+        Ok(output.stdout)
+    }
+
+    /// Écrire un fichier local dans un fichier distant ouvert selon `open`,
+    /// à partir de `resume` (position et empreinte du début déjà envoyé), avec
+    /// jusqu'à `pipeline.requests` écritures en vol. Chaque bloc acquitté fait
+    /// avancer `progress`.
+    async fn send_file(
+        &mut self,
+        local_path: &Path,
+        remote_path: &str,
+        open: OpenMode,
+        resume: Option<(u64, Sha256)>,
+        pipeline: Pipeline,
+        progress: &TransferProgress,
+    ) -> Result<UploadedFile> {
+        use std::io::SeekFrom;
+        use tokio::io::AsyncSeekExt;
+
+        let raw = self
+            .sftp
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+
+        // Créer le fichier distant
+        let attributes = FileAttributes {
+            permissions: open.mode,
+            ..FileAttributes::empty()
+        };
+        let handle = raw
+            .session
+            .open(remote_path, open.flags, attributes)
+            .await
+            .with_context(|| format!("Impossible de créer le fichier distant: {}", remote_path))?
+            .handle;
+
+        let written = async {
+            let mut file = tokio::fs::File::open(local_path).await.with_context(|| {
+                format!("Impossible de lire le fichier local: {:?}", local_path)
+            })?;
+
+            let (offset, mut hasher) = resume.unwrap_or_default();
+            progress.restart_file(offset);
+            if offset > 0 {
+                file.seek(SeekFrom::Start(offset))
+                    .await
+                    .context("Erreur lors du positionnement dans le fichier local")?;
+            }
+
+            log::debug!(
+                "Écriture de {} : {} requête(s) de {} octets en vol ({} octets)",
+                remote_path,
+                pipeline.requests,
+                pipeline.chunk_size,
+                pipeline.window()
+            );
+            let written = pipeline
+                .write(&raw.session, &handle, &mut file, offset, &mut hasher, |n| {
+                    progress.inc(n)
+                })
+                .await?;
+
+            if raw.fsync {
+                raw.session
+                    .fsync(handle.as_str())
+                    .await
+                    .context("Erreur lors de la synchronisation du fichier distant")?;
+            }
+            Ok::<_, anyhow::Error>((offset + written, hasher))
+        }
+        .await;
+
+        let closed = raw.session.close(handle.as_str()).await;
+        let (total_bytes, hasher) = written?;
+        closed.context("Erreur lors de la fermeture du fichier distant")?;
+
+        log::debug!(
+            "Fichier téléversé: {} -> {} ({} octets)",
+            local_path.display(),
+            remote_path,
+            total_bytes
+        );
+
+        Ok(UploadedFile {
+            bytes: total_bytes,
+            sha256: HEXLOWER.encode(&hasher.finalize()),
+            verified: false,
+            skipped: false,
+            backup: None,
+        })
+    }
+
+    /// Comparer l'empreinte SHA-256 d'un fichier distant à celle attendue
+    async fn verify_remote(&mut self, remote_path: &str, expected: &str) -> Result<()> {
+        let actual = self.remote_sha256(remote_path, None).await?;
+        if actual != expected {
+            return Err(ChecksumMismatch {
+                path: remote_path.to_string(),
+                expected: expected.to_string(),
+                actual,
+            }
+            .into());
+        }
+        log::debug!("🔒 SHA-256 vérifié: {} ({})", remote_path, actual);
+        Ok(())
+    }
+
+    /// Empreinte SHA-256 d'un fichier distant, ou de ses `limit` premiers octets:
+    /// `sha256sum` (ou `shasum`) sur le serveur, à défaut relecture via SFTP
+    pub async fn remote_sha256(&mut self, remote_path: &str, limit: Option<u64>) -> Result<String> {
+        let quoted = sudo::shell_quote(remote_path);
+        let command = match limit {
+            None => format!(
+                "sha256sum {} 2>/dev/null || shasum -a 256 {}",
+                quoted, quoted
+            ),
+            Some(limit) => format!(
+                "head -c {} {} | sha256sum 2>/dev/null || head -c {} {} | shasum -a 256",
+                limit, quoted, limit, quoted
+            ),
+        };
+        match self.execute_command(&command, VERIFY_TIMEOUT).await {
+            Ok(output) if output.exit_code == 0 && !output.killed => {
+                if let Some(digest) = parse_sha256(&output.stdout) {
+                    return Ok(digest);
+                }
+                log::debug!("Sortie sha256sum illisible: {}", output.stdout.trim());
+            }
+            Ok(output) => log::debug!(
+                "sha256sum indisponible sur {} (code {})",
+                self.host,
+                output.exit_code
+            ),
+            Err(e) => log::debug!("Exécution impossible sur {}: {:#}", self.host, e),
+        }
+
+        log::debug!("Relecture via SFTP pour vérifier {}", remote_path);
+        self.read_back_sha256(remote_path, limit).await
+    }
+
+    /// Relire un fichier distant via SFTP (au plus `limit` octets) et calculer son empreinte
+    async fn read_back_sha256(&mut self, remote_path: &str, limit: Option<u64>) -> Result<String> {
+        let sftp = self
+            .sftp
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
+        let handle = sftp
+            .open_read(remote_path)
+            .await
+            .with_context(|| format!("Impossible de relire le fichier distant: {}", remote_path))?;
+
+        let mut hasher = Sha256::new();
+        let limit = limit.unwrap_or(u64::MAX);
+        let mut offset = 0;
+        let read = loop {
+            let want = usize::try_from(limit - offset).unwrap_or(usize::MAX);
+            if want == 0 {
+                break Ok(());
+            }
+            match sftp.read_chunk(&handle, offset, want).await {
+                Ok(Some(chunk)) => {
+                    hasher.update(&chunk);
+                    offset += chunk.len() as u64;
+                }
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+
+        if let Err(e) = sftp.close(&handle).await {
+            log::debug!("Fermeture de {} impossible: {}", remote_path, e);
+        }
+        read.context("Erreur lors de la relecture du fichier distant")?;
+        Ok(HEXLOWER.encode(&hasher.finalize()))
+    }
+
+    /// Récupérer le répertoire HOME réel du serveur distant
+    async fn fetch_remote_home(&mut self) -> Result<()> {
+        // Essayer d'abord avec 'pwd' (répertoire de connexion = HOME)
+        if let Ok(output) = self.execute_command("pwd", Duration::from_secs(5)).await {
+            if output.exit_code == 0 {
+                let home = output.stdout.trim();
+                if !home.is_empty() {
+                    self.remote_home = Some(home.to_string());
+                    log::debug!("📂 HOME détecté via pwd: {}", home);
+                    return Ok(());
+                }
+            }
+        }
+
+        // Fallback avec 'echo $HOME'
+        if let Ok(output) = self
+            .execute_command("echo $HOME", Duration::from_secs(5))
+            .await
+        {
+            if output.exit_code == 0 {
+                let home = output.stdout.trim();
+                if !home.is_empty() && home != "$HOME" {
+                    self.remote_home = Some(home.to_string());
+                    log::debug!("📂 HOME détecté via $HOME: {}", home);
+                    return Ok(());
+                }
+            }
+        }
+
+        // Dernier recours : supposer /home/username
+        let fallback_home = format!("/home/{}", self.username);
+        self.remote_home = Some(fallback_home.clone());
+        log::warn!(
+            "⚠️  Impossible de détecter HOME, utilisation de fallback: {}",
+            fallback_home
+        );
+
+        Ok(())
+    }
+
+    /// Obtenir le répertoire HOME réel du serveur distant
+    pub fn get_remote_home(&self) -> Option<&str> {
+        self.remote_home.as_deref()
+    }
+
+    /// S'assurer que le répertoire distant existe, parents compris (`mkdir -p`)
     async fn ensure_remote_directory(&mut self, remote_dir: &str) -> Result<()> {
         let sftp = self
             .sftp
             .as_mut()
             .ok_or_else(|| anyhow::anyhow!("Connexion SFTP non établie"))?;
 
-        // Essayer de créer le répertoire (ignore l'erreur s'il existe déjà)
-        let _ = sftp.create_dir(remote_dir).await;
+        // Du plus proche de la racine au plus profond
+        let mut missing = Vec::new();
+        for dir in Path::new(remote_dir).ancestors() {
+            let dir = dir.to_string_lossy();
+            if dir.is_empty() || dir == "/" || self.known_dirs.contains(dir.as_ref()) {
+                break;
+            }
+            match sftp.metadata(dir.as_ref()).await {
+                Ok(metadata) if metadata.is_dir() => {
+                    self.known_dirs.insert(dir.into_owned());
+                    break;
+                }
+                Ok(_) => anyhow::bail!("{} existe et n'est pas un répertoire", dir),
+                Err(_) => missing.push(dir.into_owned()),
+            }
+        }
+
+        for dir in missing.into_iter().rev() {
+            if let Err(e) = sftp.create_dir(dir.as_str()).await {
+                // Créé entre-temps par une autre connexion ?
+                let exists = matches!(sftp.metadata(dir.as_str()).await, Ok(m) if m.is_dir());
+                if !exists {
+                    return Err(e).with_context(|| {
+                        format!("Impossible de créer le répertoire distant: {}", dir)
+                    });
+                }
+            }
+            log::debug!("📁 Répertoire distant créé: {}", dir);
+            self.known_dirs.insert(dir);
+        }
         Ok(())
     }
 
@@ -407,81 +1931,720 @@ impl SshClient {
         command: &str,
         timeout: Duration,
     ) -> Result<CommandOutput> {
+        self.execute_command_streaming(command, &ExecOptions::default(), timeout, |_, _| {})
+            .await
+    }
+
+    /// Exécuter une commande SSH en transmettant la sortie au fil de l'eau.
+    ///
+    /// `on_output` reçoit chaque bloc de données dès sa réception ; la sortie
+    /// complète est aussi renvoyée dans `CommandOutput`. Au timeout ou sur Ctrl-C,
+    /// le processus distant reçoit TERM puis KILL après `KILL_GRACE_PERIOD`.
+    pub async fn execute_command_streaming<F>(
+        &mut self,
+        command: &str,
+        options: &ExecOptions,
+        timeout: Duration,
+        mut on_output: F,
+    ) -> Result<CommandOutput>
+    where
+        F: FnMut(OutputStream, &[u8]) + Send,
+    {
         log::debug!("execute_command: '{}'", command);
 
+        // --become: sudo dans un PTY, invite retirée de la sortie
+        let mut command = match &options.become_user {
+            Some(user) => sudo::wrap_command(command, user),
+            None => command.to_string(),
+        };
+        if let Some(path) = &options.input_file {
+            command = sudo::redirect_input(&command, path);
+        }
+        if options.become_user.is_some() && options.input.is_some() {
+            anyhow::bail!("--stdin n'est pas compatible avec --become");
+        }
+        let pty = options.pty || options.become_user.is_some();
+        let mut sudo_filter = options
+            .become_user
+            .as_ref()
+            .map(|_| PromptFilter::default());
+        let mut sudo_prompts = 0;
+        let mut sudo_failure: Option<&str> = None;
+        let password_cache = self.password_cache.clone();
+
         let handle = self
             .handle
-            .as_mut()
+            .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Connexion SSH non établie"))?;
 
         log::debug!("Ouverture d'un canal SSH");
-        let mut channel = handle.channel_open_session().await?;
+        let channel = handle.channel_open_session().await?;
+
+        if pty {
+            log::debug!("Allocation d'un pseudo-terminal");
+            channel
+                .request_pty(true, "xterm", 80, 24, 0, 0, &[])
+                .await
+                .context("Allocation du pseudo-terminal impossible")?;
+        }
 
         // Exécuter la commande
         log::debug!("Envoi de la commande au serveur");
-        channel.exec(true, command).await?;
+        channel.exec(true, command.as_str()).await?;
+
+        // Lecture et écriture séparées: l'entrée est envoyée pendant que la sortie
+        // est lue, sinon un processus qui écrit avant de tout lire bloquerait le canal
+        let (mut reader, writer) = channel.split();
+        let input = options.input.as_deref();
+        let send_input = async {
+            let Some(input) = input else { return };
+            log::debug!("Envoi de {} octets sur stdin", input.len());
+            if let Err(e) = writer.data(input).await {
+                // Le processus a pu se terminer sans tout lire
+                log::debug!("Envoi de stdin interrompu: {}", e);
+                return;
+            }
+            if let Err(e) = writer.eof().await {
+                log::debug!("Envoi de EOF impossible: {}", e);
+            }
+        };
+        tokio::pin!(send_input);
+        let mut input_sent = input.is_none();
 
-        // Lire stdout et stderr
+        // Lire stdout et stderr jusqu'à la fermeture du canal: le code de sortie
+        // et le signal arrivent après EOF
         let mut stdout = Vec::new();
         let mut stderr = Vec::new();
-        let mut exit_code: i32 = 0;
+        let mut exit_status: Option<u32> = None;
+        let mut exit_signal: Option<String> = None;
+
+        let mut running = RunningCommand::start();
+        let mut interrupted = false;
+        let mut stage = TerminationStage::Running;
+        let mut deadline = tokio::time::Instant::now() + timeout;
 
         log::debug!("Lecture de la sortie (timeout: {:?})", timeout);
-        let result = tokio::time::timeout(timeout, async {
-            loop {
-                match channel.wait().await {
-                    Some(russh::ChannelMsg::Data { ref data }) => {
-                        log::trace!("Reçu {} octets sur stdout", data.len());
-                        stdout.extend_from_slice(data);
-                    }
-                    Some(russh::ChannelMsg::ExtendedData { ref data, .. }) => {
-                        log::trace!("Reçu {} octets sur stderr", data.len());
-                        stderr.extend_from_slice(data);
-                    }
-                    Some(russh::ChannelMsg::ExitStatus { exit_status }) => {
-                        log::debug!("Code de sortie: {}", exit_status);
-                        exit_code = exit_status as i32;
+        loop {
+            let msg = tokio::select! {
+                _ = &mut send_input, if !input_sent => {
+                    input_sent = true;
+                    continue;
+                }
+                _ = running.interrupted(), if !interrupted => {
+                    interrupted = true;
+                    if stage == TerminationStage::Running {
+                        deadline = tokio::time::Instant::now();
                     }
-                    Some(russh::ChannelMsg::Eof) | None => {
-                        log::debug!("Fin de la sortie (EOF)");
-                        break;
+                    continue;
+                }
+                _ = tokio::time::sleep_until(deadline) => {
+                    stage = match stage {
+                        TerminationStage::Running => {
+                            if interrupted {
+                                log::warn!("⚠️  Interruption demandée, envoi de TERM au processus distant");
+                            } else {
+                                log::warn!("⏱️  Timeout ({:?}), envoi de TERM au processus distant", timeout);
+                            }
+                            if pty {
+                                // Ctrl-C dans le terminal: SIGINT même si le serveur ignore les signaux
+                                let _ = writer.data(&b"\x03"[..]).await;
+                            }
+                            let _ = writer.signal(russh::Sig::TERM).await;
+                            deadline = tokio::time::Instant::now() + KILL_GRACE_PERIOD;
+                            TerminationStage::Terminating
+                        }
+                        TerminationStage::Terminating => {
+                            log::warn!("⚠️  Processus toujours actif, envoi de KILL");
+                            let _ = writer.signal(russh::Sig::KILL).await;
+                            deadline = tokio::time::Instant::now() + CLOSE_GRACE_PERIOD;
+                            TerminationStage::Killing
+                        }
+                        TerminationStage::Killing => {
+                            // Fermer le canal (SIGHUP si un PTY est alloué) et abandonner
+                            log::warn!("⚠️  Aucune réponse après KILL, fermeture du canal");
+                            let _ = writer.close().await;
+                            break;
+                        }
+                    };
+                    continue;
+                }
+                msg = reader.wait() => msg,
+            };
+            match msg {
+                Some(russh::ChannelMsg::Data { ref data }) => {
+                    log::trace!("Reçu {} octets sur stdout", data.len());
+                    let data = match sudo_filter.as_mut() {
+                        Some(filter) => {
+                            let (clean, prompts) = filter.push(data);
+                            if prompts > 0 && sudo_failure.is_none() {
+                                sudo_prompts += prompts;
+                                sudo_failure =
+                                    answer_sudo_prompt(&writer, &password_cache, sudo_prompts)
+                                        .await?;
+                            }
+                            clean
+                        }
+                        None => data.to_vec(),
+                    };
+                    if !data.is_empty() {
+                        on_output(OutputStream::Stdout, &data);
+                        stdout.extend_from_slice(&data);
                     }
-                    _ => {}
                 }
+                Some(russh::ChannelMsg::ExtendedData { ref data, .. }) => {
+                    log::trace!("Reçu {} octets sur stderr", data.len());
+                    on_output(OutputStream::Stderr, data);
+                    stderr.extend_from_slice(data);
+                }
+                Some(russh::ChannelMsg::ExitStatus {
+                    exit_status: status,
+                }) => {
+                    log::debug!("Code de sortie: {}", status);
+                    exit_status = Some(status);
+                }
+                Some(russh::ChannelMsg::ExitSignal {
+                    signal_name,
+                    core_dumped,
+                    error_message,
+                    ..
+                }) => {
+                    let name = signal_name_of(&signal_name);
+                    log::debug!(
+                        "Processus terminé par SIG{} (core: {}) {}",
+                        name,
+                        core_dumped,
+                        error_message
+                    );
+                    exit_signal = Some(name);
+                }
+                Some(russh::ChannelMsg::Eof) => {
+                    log::debug!("Fin de la sortie (EOF)");
+                }
+                Some(russh::ChannelMsg::Close) | None => {
+                    log::debug!("Canal fermé");
+                    break;
+                }
+                _ => {}
             }
-        })
-        .await;
+        }
 
-        match result {
-            Ok(_) => {
-                log::debug!(
-                    "Commande terminée - stdout: {} octets, stderr: {} octets, exit: {}",
-                    stdout.len(),
-                    stderr.len(),
-                    exit_code
-                );
-            }
-            Err(_) => {
-                log::warn!("Timeout lors de l'exécution de la commande");
-            }
+        if let Some(rest) = sudo_filter.as_mut().map(PromptFilter::flush) {
+            on_output(OutputStream::Stdout, &rest);
+            stdout.extend_from_slice(&rest);
+        }
+        if let Some(failure) = sudo_failure {
+            anyhow::bail!("{} (--become)", failure);
         }
 
-        result.context("Timeout d'exécution de la commande")?;
+        let killed = stage != TerminationStage::Running;
+        let exit_code = exit_code_of(exit_status, exit_signal.as_deref(), killed)?;
+
+        log::debug!(
+            "Commande terminée - stdout: {} octets, stderr: {} octets, exit: {}, signal: {:?}, tuée: {}",
+            stdout.len(),
+            stderr.len(),
+            exit_code,
+            exit_signal,
+            killed
+        );
+
+        let mut stdout = String::from_utf8_lossy(&stdout).to_string();
+        if pty {
+            // Le terminal traduit les fins de ligne
+            stdout = stdout.replace("\r\n", "\n");
+        }
 
         Ok(CommandOutput {
-            stdout: String::from_utf8_lossy(&stdout).to_string(),
+            stdout,
             stderr: String::from_utf8_lossy(&stderr).to_string(),
             exit_code,
+            exit_signal,
+            killed,
         })
     }
 }
 
+/// Mode du fichier local (0644 si indisponible)
+fn local_mode(path: &Path) -> u32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            return metadata.permissions().mode() & 0o7777;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    0o644
+}
+
+/// Chemin de la copie de sauvegarde d'un fichier remplacé: à côté du fichier
+/// (`app.conf.<run>.bak`) ou sous `dir/<run>/` en reprenant son chemin complet
+fn backup_path(remote_path: &str, run_id: &str, dir: Option<&str>) -> String {
+    match dir {
+        Some(dir) => format!(
+            "{}/{}/{}",
+            dir.trim_end_matches('/'),
+            run_id,
+            remote_path.trim_start_matches('/')
+        ),
+        None => format!("{}.{}.bak", remote_path, run_id),
+    }
+}
+
+/// Mode octal de --chmod ou de hosts.json ("644", "0755", "2775")
+pub fn parse_mode(value: &str) -> Result<u32, String> {
+    let digits = value.strip_prefix("0o").unwrap_or(value);
+    if digits.is_empty() || digits.len() > 4 {
+        return Err(format!("mode invalide '{}' (octal, ex: 0644)", value));
+    }
+    u32::from_str_radix(digits, 8)
+        .map_err(|_| format!("mode invalide '{}' (octal, ex: 0644)", value))
+}
+
+/// Commande `chown`/`chgrp` pour le propriétaire et le groupe demandés
+fn ownership_command(path: &str, owner: Option<&str>, group: Option<&str>) -> Option<String> {
+    let path = sudo::shell_quote(path);
+    match (owner, group) {
+        (Some(owner), Some(group)) => Some(format!(
+            "chown {} {}",
+            sudo::shell_quote(&format!("{}:{}", owner, group)),
+            path
+        )),
+        (Some(owner), None) => Some(format!("chown {} {}", sudo::shell_quote(owner), path)),
+        (None, Some(group)) => Some(format!("chgrp {} {}", sudo::shell_quote(group), path)),
+        (None, None) => None,
+    }
+}
+
+/// Options de `install` (--become): mode, propriétaire, groupe et dates
+fn install_args(mode: u32, options: &UploadOptions) -> String {
+    let mut args = format!("-m {:o}", mode);
+    if let Some(owner) = &options.owner {
+        args.push_str(&format!(" -o {}", sudo::shell_quote(owner)));
+    }
+    if let Some(group) = &options.group {
+        args.push_str(&format!(" -g {}", sudo::shell_quote(group)));
+    }
+    if options.preserve {
+        args.push_str(" -p");
+    }
+    args
+}
+
+/// Commande d'installation --become: le contenu est lu sur l'entrée standard
+/// (le fichier temporaire, que seul l'utilisateur de connexion peut lire)
+fn become_install_command(
+    parent_dir: &str,
+    remote_path: &str,
+    install_args: &str,
+    in_place: bool,
+) -> String {
+    if in_place {
+        format!(
+            "mkdir -p {} && install {} /dev/stdin {}",
+            sudo::shell_quote(parent_dir),
+            install_args,
+            sudo::shell_quote(remote_path)
+        )
+    } else {
+        let partial = sudo::shell_quote(&partial_path(remote_path));
+        format!(
+            "mkdir -p {} && install {} /dev/stdin {} && mv -f {} {} || {{ rm -f {}; exit 1; }}",
+            sudo::shell_quote(parent_dir),
+            install_args,
+            partial,
+            partial,
+            sudo::shell_quote(remote_path),
+            partial
+        )
+    }
+}
+
+/// Fichier caché, à côté de la destination, où le contenu est écrit avant
+/// d'être renommé: `/etc/app/app.conf` → `/etc/app/.app.conf.xsshend-part`
+fn partial_path(remote_path: &str) -> String {
+    match remote_path.rsplit_once('/') {
+        Some((dir, name)) => format!("{}/.{}.xsshend-part", dir, name),
+        None => format!(".{}.xsshend-part", remote_path),
+    }
+}
+
+/// Mode (bits de permission seuls) et propriétaire d'une destination remplacée,
+/// à reporter sur le fichier qui la remplace
+fn inherited_attributes(replaced: &FileAttributes) -> (Option<u32>, Option<FileAttributes>) {
+    let mode = replaced.permissions.map(|mode| mode & 0o7777);
+    let owner = match (replaced.uid, replaced.gid) {
+        (Some(uid), Some(gid)) => Some(FileAttributes {
+            uid: Some(uid),
+            gid: Some(gid),
+            ..FileAttributes::empty()
+        }),
+        _ => None,
+    };
+    (mode, owner)
+}
+
+/// Fichier local caché écrit pendant un téléchargement
+fn local_partial_path(local_path: &Path) -> PathBuf {
+    let name = local_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    local_path.with_file_name(format!(".{}.xsshend-part", name))
+}
+
+/// Le chemin contient-il des jokers shell ?
+fn has_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Protéger un motif pour le shell distant, sauf ses jokers (`*`, `?`, `[...]`)
+fn glob_quote(pattern: &str) -> String {
+    let mut quoted = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        if !(c.is_ascii_alphanumeric() || "/._-+,:@%=*?[]!".contains(c)) {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted
+}
+
+/// Empreinte SHA-256 en cours des `limit` premiers octets d'un fichier local
+async fn hash_prefix(path: &Path, limit: u64) -> Result<Sha256> {
+    use tokio::io::AsyncReadExt;
+
+    let file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("Impossible de lire le fichier local: {:?}", path))?;
+    let mut reader = file.take(limit);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 65536];
+    loop {
+        let n = reader.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher)
+}
+
+/// Empreinte lue dans la sortie de `sha256sum` / `shasum -a 256`
+fn parse_sha256(output: &str) -> Option<String> {
+    let digest = output.split_whitespace().next()?;
+    (digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| digest.to_ascii_lowercase())
+}
+
+/// Encodage SFTP d'une suite de chaînes (longueur u32 big-endian + octets)
+fn encode_strings(values: &[&str]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for value in values {
+        data.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        data.extend_from_slice(value.as_bytes());
+    }
+    data
+}
+
+/// Ouverture d'un fichier distant par `send_file`
+#[derive(Debug, Clone, Copy)]
+struct OpenMode {
+    flags: OpenFlags,
+    /// Mode d'un fichier créé (défaut: umask du serveur)
+    mode: Option<u32>,
+}
+
+/// Répondre à l'invite sudo. Renvoie le motif d'échec si la commande ne peut
+/// pas continuer (mot de passe refusé ou indisponible); elle est alors interrompue.
+async fn answer_sudo_prompt(
+    writer: &russh::ChannelWriteHalf<Msg>,
+    password_cache: &PasswordCache,
+    prompts: usize,
+) -> Result<Option<&'static str>> {
+    let failure = if prompts > 1 {
+        // sudo redemande: le mot de passe a été refusé
+        password_cache.forget_sudo();
+        "Mot de passe sudo refusé"
+    } else {
+        match password_cache.obtain_sudo().await? {
+            Some(password) => {
+                writer
+                    .data(format!("{}\n", password).as_bytes())
+                    .await
+                    .context("Envoi du mot de passe sudo impossible")?;
+                return Ok(None);
+            }
+            None => "Mot de passe sudo requis, saisie impossible en mode non interactif",
+        }
+    };
+
+    // Abandonner l'invite (Ctrl-C dans le terminal)
+    let _ = writer.data(&b"\x03"[..]).await;
+    Ok(Some(failure))
+}
+
+/// Délai laissé au processus distant entre TERM et KILL
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(5);
+/// Délai d'attente de la fermeture du canal après KILL
+const CLOSE_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
+/// Étapes de l'arrêt d'une commande distante (timeout ou Ctrl-C)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TerminationStage {
+    Running,
+    Terminating,
+    Killing,
+}
+
+/// Nom d'un signal SSH sans préfixe ("TERM", "KILL", ...)
+fn signal_name_of(signal: &russh::Sig) -> String {
+    match signal {
+        russh::Sig::Custom(name) => name.clone(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Code de sortie d'après ExitStatus / ExitSignal. Un canal fermé sans l'un
+/// ni l'autre (connexion coupée, sshd tué) est un échec, jamais un code 0.
+fn exit_code_of(exit_status: Option<u32>, exit_signal: Option<&str>, killed: bool) -> Result<i32> {
+    match (exit_status, exit_signal) {
+        (Some(status), _) => Ok(status as i32),
+        // Convention shell: 128 + numéro du signal
+        (None, Some(signal)) => Ok(signal_number(signal).map_or(-1, |n| 128 + n)),
+        (None, None) if killed => Ok(-1),
+        (None, None) => Err(anyhow::anyhow!(
+            "Canal fermé sans code de sortie (connexion perdue ?)"
+        )),
+    }
+}
+
+/// Numéro POSIX d'un signal, pour calculer le code de sortie
+fn signal_number(name: &str) -> Option<i32> {
+    let number = match name {
+        "HUP" => 1,
+        "INT" => 2,
+        "QUIT" => 3,
+        "ILL" => 4,
+        "ABRT" => 6,
+        "FPE" => 8,
+        "KILL" => 9,
+        "USR1" => 10,
+        "SEGV" => 11,
+        "PIPE" => 13,
+        "ALRM" => 14,
+        "TERM" => 15,
+        _ => return None,
+    };
+    Some(number)
+}
+
+/// Options d'écriture des fichiers téléversés
+#[derive(Debug, Clone, Default)]
+pub struct UploadOptions {
+    /// Écrire directement dans la destination, sans fichier temporaire ni
+    /// renommage (conserve l'inode, donc les liens durs et le propriétaire)
+    pub in_place: bool,
+    /// Recalculer l'empreinte SHA-256 côté serveur et la comparer (--verify)
+    pub verify: bool,
+    /// Reprendre un fichier partiel dont le début correspond au fichier local,
+    /// et le conserver en cas d'échec (--resume)
+    pub resume: bool,
+    /// Ne pas renvoyer un fichier distant déjà identique (--skip-unchanged)
+    pub skip_unchanged: Option<UnchangedCheck>,
+    /// Reporter le mode et la date de modification du fichier local (--preserve)
+    pub preserve: bool,
+    /// Mode imposé, prioritaire sur celui du fichier local (--chmod)
+    pub mode: Option<u32>,
+    /// Propriétaire imposé (--chown)
+    pub owner: Option<String>,
+    /// Groupe imposé (--chgrp)
+    pub group: Option<String>,
+    /// Copier le fichier distant existant avant de le remplacer (--backup)
+    pub backup: Option<BackupOptions>,
+    /// Écritures SFTP en vol et taille de chacune (--requests, --chunk-size)
+    pub pipeline: Pipeline,
+}
+
+/// Sauvegarde des fichiers distants remplacés
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupOptions {
+    /// Identifiant de l'exécution, repris dans le nom des copies
+    pub run_id: String,
+    /// Répertoire des copies (--backup-dir); par défaut à côté du fichier
+    pub dir: Option<String>,
+}
+
+impl UploadOptions {
+    /// Compléter avec les attributs définis pour l'hôte dans hosts.json
+    /// (`preserve`, `chmod`, `chown`, `chgrp`); la ligne de commande l'emporte
+    pub fn with_host_defaults(&self, host: &HostOptions) -> Result<Self> {
+        let mut options = self.clone();
+        options.preserve |= host.preserve.unwrap_or(false);
+        if options.mode.is_none() {
+            options.mode = host
+                .chmod
+                .as_deref()
+                .map(parse_mode)
+                .transpose()
+                .map_err(|e| anyhow::anyhow!("hosts.json, chmod: {}", e))?;
+        }
+        if options.owner.is_none() {
+            options.owner = host.chown.clone();
+        }
+        if options.group.is_none() {
+            options.group = host.chgrp.clone();
+        }
+        Ok(options)
+    }
+}
+
+/// Comparaison d'un fichier distant existant avec le fichier local (--skip-unchanged)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnchangedCheck {
+    /// Même taille et fichier distant au moins aussi récent (une seule requête stat)
+    Mtime,
+    /// Même taille et même empreinte SHA-256 (relit les deux fichiers)
+    Hash,
+}
+
+impl fmt::Display for UnchangedCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnchangedCheck::Mtime => write!(f, "mtime"),
+            UnchangedCheck::Hash => write!(f, "hash"),
+        }
+    }
+}
+
+impl FromStr for UnchangedCheck {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "mtime" => Ok(UnchangedCheck::Mtime),
+            "hash" => Ok(UnchangedCheck::Hash),
+            other => Err(format!("comparaison inconnue '{}' (mtime ou hash)", other)),
+        }
+    }
+}
+
+/// Aucune méthode d'authentification acceptée par le serveur
+#[derive(Debug)]
+pub struct AuthenticationError {
+    pub user: String,
+    pub host: String,
+}
+
+impl fmt::Display for AuthenticationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Authentification impossible pour {}@{}",
+            self.user, self.host
+        )
+    }
+}
+
+impl std::error::Error for AuthenticationError {}
+
+/// Empreinte distante différente du contenu envoyé (--verify)
+#[derive(Debug)]
+pub struct ChecksumMismatch {
+    pub path: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Empreinte SHA-256 différente pour {} : locale {}, distante {}",
+            self.path, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+/// Fichier écrit sur le serveur
+#[derive(Debug, Clone, Default)]
+pub struct UploadedFile {
+    pub bytes: u64,
+    /// SHA-256 du contenu envoyé, calculé pendant la lecture locale
+    /// (vide pour un fichier inchangé comparé par taille et date)
+    pub sha256: String,
+    /// Empreinte distante recalculée et identique
+    pub verified: bool,
+    /// Déjà identique sur le serveur: rien n'a été envoyé (--skip-unchanged)
+    pub skipped: bool,
+    /// Copie de la version remplacée (--backup)
+    pub backup: Option<String>,
+}
+
+/// Fichier distant à télécharger
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFile {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Élément d'une arborescence distante (xsshend sync)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    /// Chemin relatif au répertoire listé
+    pub path: String,
+    pub kind: RemoteKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteKind {
+    File { size: u64, mtime: Option<u32> },
+    Dir,
+    Symlink { target: String },
+}
+
+/// Fichier distant copié en local
+#[derive(Debug, Clone, Default)]
+pub struct DownloadedFile {
+    pub bytes: u64,
+    /// SHA-256 du contenu reçu
+    pub sha256: String,
+}
+
+/// Options d'exécution d'une commande distante
+#[derive(Debug, Clone, Default)]
+pub struct ExecOptions {
+    /// Données envoyées sur l'entrée standard du processus, suivies d'un EOF
+    pub input: Option<Arc<[u8]>>,
+    /// Allouer un pseudo-terminal: le processus reçoit SIGHUP à la fermeture
+    /// du canal (stderr est alors mêlé à stdout)
+    pub pty: bool,
+    /// Exécuter via sudo en tant que cet utilisateur (--become), avec PTY
+    pub become_user: Option<String>,
+    /// Fichier distant branché sur l'entrée standard par le shell de connexion
+    pub input_file: Option<String>,
+}
+
+/// Flux de sortie d'une commande distante
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
 /// Sortie d'une commande SSH exécutée
 #[derive(Debug, Clone)]
 pub struct CommandOutput {
     pub stdout: String,
     pub stderr: String,
     pub exit_code: i32,
+    /// Signal ayant terminé le processus ("TERM", "KILL"...), d'après ExitSignal
+    pub exit_signal: Option<String>,
+    /// Arrêtée par xsshend (timeout ou Ctrl-C)
+    pub killed: bool,
 }
 
 impl Drop for SshClient {
@@ -493,3 +2656,201 @@ impl Drop for SshClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_path_is_hidden_sibling() {
+        assert_eq!(
+            partial_path("/etc/app/app.conf"),
+            "/etc/app/.app.conf.xsshend-part"
+        );
+        assert_eq!(partial_path("notes.txt"), ".notes.txt.xsshend-part");
+    }
+
+    #[test]
+    fn test_become_staging_file_never_widened() {
+        // Lisible par l'utilisateur de connexion seul
+        assert_eq!(STAGING_MODE & 0o077, 0);
+
+        for in_place in [false, true] {
+            let command = become_install_command(
+                "/srv/app",
+                "/srv/app/secret.env",
+                "-m 640 -o 'app'",
+                in_place,
+            );
+            // Contenu lu sur l'entrée standard, jamais depuis /tmp ni après chmod
+            assert!(command.contains("install -m 640 -o 'app' /dev/stdin "));
+            assert!(!command.contains("/tmp") && !command.contains("chmod"));
+        }
+    }
+
+    #[test]
+    fn test_replaced_file_keeps_mode_and_owner() {
+        // app.conf en 0600, appartenant à app:app (1001:1001)
+        let replaced = FileAttributes {
+            permissions: Some(0o100600),
+            uid: Some(1001),
+            gid: Some(1001),
+            ..FileAttributes::empty()
+        };
+        let (mode, owner) = inherited_attributes(&replaced);
+        assert_eq!(mode, Some(0o600));
+        let owner = owner.unwrap();
+        assert_eq!((owner.uid, owner.gid), (Some(1001), Some(1001)));
+        // Seul le propriétaire est changé par ce setstat, pas le mode ni les dates
+        assert_eq!((owner.permissions, owner.mtime), (None, None));
+
+        let (mode, owner) = inherited_attributes(&FileAttributes::empty());
+        assert!(mode.is_none() && owner.is_none());
+    }
+
+    #[test]
+    fn test_exit_code_without_status_is_a_failure() {
+        assert_eq!(exit_code_of(Some(0), None, false).unwrap(), 0);
+        assert_eq!(exit_code_of(Some(3), Some("TERM"), false).unwrap(), 3);
+        assert_eq!(exit_code_of(None, Some("KILL"), false).unwrap(), 137);
+        assert_eq!(exit_code_of(None, None, true).unwrap(), -1);
+
+        // Connexion coupée avant la fin de la commande
+        let error = exit_code_of(None, None, false).unwrap_err();
+        assert!(error.to_string().contains("sans code de sortie"));
+    }
+
+    #[test]
+    fn test_parse_sha256_output() {
+        let digest = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(
+            parse_sha256(&format!("{}  /srv/app/empty\n", digest)),
+            Some(digest.to_ascii_lowercase())
+        );
+        assert_eq!(parse_sha256("sha256sum: /srv/app/x: No such file"), None);
+        assert_eq!(parse_sha256(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_hash_prefix_matches_partial_content() {
+        let temp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(temp.path(), b"abcdef").unwrap();
+
+        let prefix = hash_prefix(temp.path(), 3).await.unwrap();
+        assert_eq!(
+            HEXLOWER.encode(&prefix.clone().finalize()),
+            HEXLOWER.encode(&Sha256::digest(b"abc"))
+        );
+
+        // L'empreinte en cours se prolonge avec la suite du fichier
+        let mut full = prefix;
+        full.update(b"def");
+        assert_eq!(full.finalize(), Sha256::digest(b"abcdef"));
+    }
+
+    #[test]
+    fn test_unchanged_check_parsing() {
+        assert_eq!("hash".parse::<UnchangedCheck>(), Ok(UnchangedCheck::Hash));
+        assert!("size".parse::<UnchangedCheck>().is_err());
+        assert_eq!(UnchangedCheck::Mtime.to_string(), "mtime");
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("0755"), Ok(0o755));
+        assert_eq!(parse_mode("644"), Ok(0o644));
+        assert_eq!(parse_mode("2775"), Ok(0o2775));
+        assert!(parse_mode("u+x").is_err());
+        assert!(parse_mode("0899").is_err());
+        assert!(parse_mode("107777").is_err());
+    }
+
+    #[test]
+    fn test_ownership_and_install_args() {
+        assert_eq!(
+            ownership_command("/srv/app/run.sh", Some("deploy"), Some("www-data")),
+            Some("chown 'deploy:www-data' '/srv/app/run.sh'".to_string())
+        );
+        assert_eq!(
+            ownership_command("/srv/app/run.sh", None, Some("www-data")),
+            Some("chgrp 'www-data' '/srv/app/run.sh'".to_string())
+        );
+        assert_eq!(ownership_command("/srv/app/run.sh", None, None), None);
+
+        let options = UploadOptions {
+            preserve: true,
+            owner: Some("deploy".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(install_args(0o755, &options), "-m 755 -o 'deploy' -p");
+    }
+
+    #[test]
+    fn test_host_defaults_yield_to_cli() {
+        let host = HostOptions {
+            preserve: Some(true),
+            chmod: Some("0640".to_string()),
+            chgrp: Some("www-data".to_string()),
+            ..Default::default()
+        };
+        let cli = UploadOptions {
+            mode: Some(0o755),
+            ..Default::default()
+        };
+        let options = cli.with_host_defaults(&host).unwrap();
+        assert!(options.preserve);
+        assert_eq!(options.mode, Some(0o755));
+        assert_eq!(options.group.as_deref(), Some("www-data"));
+        assert_eq!(options.owner, None);
+
+        let invalid = HostOptions {
+            chmod: Some("rwxr-xr-x".to_string()),
+            ..Default::default()
+        };
+        assert!(UploadOptions::default()
+            .with_host_defaults(&invalid)
+            .is_err());
+    }
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(
+            backup_path("/etc/app/app.conf", "20261017-101500-1a2b", None),
+            "/etc/app/app.conf.20261017-101500-1a2b.bak"
+        );
+        assert_eq!(
+            backup_path(
+                "/etc/app/app.conf",
+                "20261017-101500-1a2b",
+                Some("/var/backups/xsshend/")
+            ),
+            "/var/backups/xsshend/20261017-101500-1a2b/etc/app/app.conf"
+        );
+    }
+
+    #[test]
+    fn test_glob_quote_keeps_wildcards_only() {
+        assert!(has_glob("/var/crash/core.*"));
+        assert!(!has_glob("/etc/hosts"));
+        assert_eq!(
+            glob_quote("/var/log/app-[0-9]*.log"),
+            "/var/log/app-[0-9]*.log"
+        );
+        assert_eq!(
+            glob_quote("/srv/my dir/$(id);*.txt"),
+            "/srv/my\\ dir/\\$\\(id\\)\\;*.txt"
+        );
+        assert_eq!(
+            local_partial_path(Path::new("out/WEB_01/etc/hosts")),
+            PathBuf::from("out/WEB_01/etc/.hosts.xsshend-part")
+        );
+    }
+
+    #[test]
+    fn test_encode_strings() {
+        assert_eq!(
+            encode_strings(&["ab", ""]),
+            vec![0, 0, 0, 2, b'a', b'b', 0, 0, 0, 0]
+        );
+    }
+}
@@ -0,0 +1,554 @@
+// Vérification des clés d'hôte SSH contre ~/.ssh/known_hosts
+//
+// Supporte les entrées hachées (|1|sel|hash), les motifs génériques (*, ?, !),
+// la notation [hôte]:port ainsi que les marqueurs @revoked et @cert-authority.
+use anyhow::{Context, Result};
+use data_encoding::BASE64;
+use hmac::{Hmac, Mac};
+use russh::keys::ssh_key::{HashAlg, PublicKey};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::utils::pattern::match_pattern_list;
+
+/// Sérialise les ajouts concurrents dans known_hosts (connexions parallèles)
+static LEARN_LOCK: Mutex<()> = Mutex::new(());
+
+/// Politique de vérification des clés d'hôte
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HostKeyPolicy {
+    /// Refuser tout hôte absent de known_hosts
+    Strict,
+    /// Enregistrer automatiquement les nouveaux hôtes, refuser les clés modifiées
+    #[default]
+    AcceptNew,
+    /// Aucune vérification (déconseillé)
+    Off,
+}
+
+impl fmt::Display for HostKeyPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostKeyPolicy::Strict => write!(f, "strict"),
+            HostKeyPolicy::AcceptNew => write!(f, "accept-new"),
+            HostKeyPolicy::Off => write!(f, "off"),
+        }
+    }
+}
+
+/// Erreur de vérification de clé d'hôte — distincte des erreurs réseau
+#[derive(Debug)]
+pub enum HostKeyError {
+    /// La clé présentée diffère de celle enregistrée (possible attaque MITM)
+    Changed {
+        host: String,
+        expected: String,
+        actual: String,
+        path: PathBuf,
+        line: usize,
+    },
+    /// La clé présentée est marquée @revoked
+    Revoked {
+        host: String,
+        fingerprint: String,
+        path: PathBuf,
+        line: usize,
+    },
+    /// Hôte inconnu avec la politique `strict`
+    Unknown {
+        host: String,
+        fingerprint: String,
+        path: PathBuf,
+    },
+    /// Hôte couvert par @cert-authority seulement: les certificats d'hôte ne
+    /// sont pas vérifiés, la clé brute n'est pas acceptée pour autant
+    Uncertified {
+        host: String,
+        fingerprint: String,
+        path: PathBuf,
+        line: usize,
+    },
+}
+
+impl fmt::Display for HostKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostKeyError::Changed {
+                host,
+                expected,
+                actual,
+                path,
+                line,
+            } => write!(
+                f,
+                "🚨 La clé d'hôte de {} a changé ! attendue: {} ({}:{}), reçue: {}",
+                host,
+                expected,
+                path.display(),
+                line,
+                actual
+            ),
+            HostKeyError::Revoked {
+                host,
+                fingerprint,
+                path,
+                line,
+            } => write!(
+                f,
+                "🚨 La clé d'hôte de {} est révoquée: {} ({}:{})",
+                host,
+                fingerprint,
+                path.display(),
+                line
+            ),
+            HostKeyError::Unknown {
+                host,
+                fingerprint,
+                path,
+            } => write!(
+                f,
+                "Hôte inconnu {} (clé {}) absent de {} — politique strict",
+                host,
+                fingerprint,
+                path.display()
+            ),
+            HostKeyError::Uncertified {
+                host,
+                fingerprint,
+                path,
+                line,
+            } => write!(
+                f,
+                "🚨 {} relève de @cert-authority ({}:{}) mais les certificats d'hôte ne sont pas vérifiés: clé {} refusée (ajoutez-la à known_hosts)",
+                host,
+                path.display(),
+                line,
+                fingerprint
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HostKeyError {}
+
+/// Marqueur optionnel en tête de ligne known_hosts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    None,
+    CertAuthority,
+    Revoked,
+}
+
+/// Ligne valide de known_hosts
+#[derive(Debug, Clone)]
+struct KnownHostEntry {
+    marker: Marker,
+    patterns: String,
+    key: PublicKey,
+    line: usize,
+}
+
+/// Résultat de la recherche d'une clé d'hôte
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostKeyStatus {
+    /// Clé connue et identique
+    Known,
+    /// Aucune clé de ce type enregistrée pour l'hôte
+    Unknown,
+    /// Une clé du même type est enregistrée mais diffère
+    Changed { expected: PublicKey, line: usize },
+    /// Clé explicitement révoquée
+    Revoked { line: usize },
+    /// Aucune clé enregistrée, mais l'hôte relève d'une autorité
+    /// @cert-authority: russh ne négociant pas de certificats d'hôte, la clé
+    /// brute ne peut pas être validée
+    CertAuthorityOnly { line: usize },
+}
+
+/// Contenu analysé d'un fichier known_hosts
+pub struct KnownHosts {
+    path: PathBuf,
+    entries: Vec<KnownHostEntry>,
+}
+
+impl KnownHosts {
+    /// Chemin par défaut: ~/.ssh/known_hosts
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Impossible de déterminer le répertoire home")?;
+        Ok(home.join(".ssh").join("known_hosts"))
+    }
+
+    /// Charge un fichier known_hosts (un fichier absent équivaut à un fichier vide)
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Impossible de lire {}", path.display()))
+            }
+        };
+
+        Ok(Self::parse(path, &content))
+    }
+
+    /// Analyse le contenu d'un fichier known_hosts; les lignes invalides sont ignorées
+    fn parse(path: &Path, content: &str) -> Self {
+        let mut entries = Vec::new();
+
+        for (index, raw_line) in content.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let mut first = fields.next();
+            let marker = match first {
+                Some("@cert-authority") => Marker::CertAuthority,
+                Some("@revoked") => Marker::Revoked,
+                Some(other) if other.starts_with('@') => {
+                    log::debug!("known_hosts:{}: marqueur inconnu {}", index + 1, other);
+                    continue;
+                }
+                _ => Marker::None,
+            };
+            if marker != Marker::None {
+                first = fields.next();
+            }
+
+            let (Some(patterns), Some(_key_type), Some(key_data)) =
+                (first, fields.next(), fields.next())
+            else {
+                log::debug!("known_hosts:{}: ligne incomplète ignorée", index + 1);
+                continue;
+            };
+
+            match russh::keys::parse_public_key_base64(key_data) {
+                Ok(key) => entries.push(KnownHostEntry {
+                    marker,
+                    patterns: patterns.to_string(),
+                    key,
+                    line: index + 1,
+                }),
+                Err(e) => log::debug!("known_hosts:{}: clé illisible ({})", index + 1, e),
+            }
+        }
+
+        KnownHosts {
+            path: path.to_path_buf(),
+            entries,
+        }
+    }
+
+    /// Recherche le statut de la clé `key` présentée par `host:port`
+    pub fn check(&self, host: &str, port: u16, key: &PublicKey) -> HostKeyStatus {
+        let lookup = lookup_name(host, port);
+
+        // Une clé révoquée est refusée quel que soit le reste du fichier
+        if let Some(entry) = self
+            .entries
+            .iter()
+            .find(|e| e.marker == Marker::Revoked && e.key.key_data() == key.key_data())
+        {
+            return HostKeyStatus::Revoked { line: entry.line };
+        }
+
+        let mut changed = None;
+        for entry in self
+            .entries
+            .iter()
+            .filter(|e| e.marker == Marker::None && host_matches(&e.patterns, &lookup))
+        {
+            if entry.key.key_data() == key.key_data() {
+                return HostKeyStatus::Known;
+            }
+            if entry.key.algorithm() == key.algorithm() && changed.is_none() {
+                changed = Some(HostKeyStatus::Changed {
+                    expected: entry.key.clone(),
+                    line: entry.line,
+                });
+            }
+        }
+
+        if let Some(status) = changed {
+            return status;
+        }
+
+        match self
+            .entries
+            .iter()
+            .find(|e| e.marker == Marker::CertAuthority && host_matches(&e.patterns, &lookup))
+        {
+            Some(entry) => HostKeyStatus::CertAuthorityOnly { line: entry.line },
+            None => HostKeyStatus::Unknown,
+        }
+    }
+
+    /// Vérifie la clé d'hôte selon la politique, en enregistrant les nouveaux hôtes si permis
+    pub fn verify(
+        path: &Path,
+        host: &str,
+        port: u16,
+        key: &PublicKey,
+        policy: HostKeyPolicy,
+    ) -> Result<()> {
+        if policy == HostKeyPolicy::Off {
+            log::debug!("Vérification de clé d'hôte désactivée pour {}", host);
+            return Ok(());
+        }
+
+        let known_hosts = Self::load(path)?;
+        let display_host = lookup_name(host, port);
+
+        match known_hosts.check(host, port, key) {
+            HostKeyStatus::Known => {
+                log::debug!("✅ Clé d'hôte reconnue pour {}", display_host);
+                Ok(())
+            }
+            HostKeyStatus::Changed { expected, line } => Err(HostKeyError::Changed {
+                host: display_host,
+                expected: fingerprint(&expected),
+                actual: fingerprint(key),
+                path: known_hosts.path,
+                line,
+            }
+            .into()),
+            HostKeyStatus::Revoked { line } => Err(HostKeyError::Revoked {
+                host: display_host,
+                fingerprint: fingerprint(key),
+                path: known_hosts.path,
+                line,
+            }
+            .into()),
+            // Jamais enregistrée à la première connexion, même en accept-new:
+            // l'utilisateur comptait sur l'autorité pour authentifier l'hôte
+            HostKeyStatus::CertAuthorityOnly { line } => {
+                log::warn!(
+                    "⚠️  {} relève de @cert-authority ({}:{}) mais les certificats d'hôte ne sont pas vérifiés: connexion refusée",
+                    display_host,
+                    known_hosts.path.display(),
+                    line
+                );
+                Err(HostKeyError::Uncertified {
+                    host: display_host,
+                    fingerprint: fingerprint(key),
+                    path: known_hosts.path,
+                    line,
+                }
+                .into())
+            }
+            HostKeyStatus::Unknown if policy == HostKeyPolicy::Strict => {
+                Err(HostKeyError::Unknown {
+                    host: display_host,
+                    fingerprint: fingerprint(key),
+                    path: known_hosts.path,
+                }
+                .into())
+            }
+            HostKeyStatus::Unknown => {
+                let _guard = LEARN_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+                russh::keys::known_hosts::learn_known_hosts_path(host, port, key, path)
+                    .with_context(|| format!("Impossible d'écrire dans {}", path.display()))?;
+                log::warn!(
+                    "⚠️  Nouvel hôte {} ajouté à {} ({})",
+                    display_host,
+                    path.display(),
+                    fingerprint(key)
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Empreinte SHA256 au format OpenSSH (SHA256:...)
+pub fn fingerprint(key: &PublicKey) -> String {
+    key.fingerprint(HashAlg::Sha256).to_string()
+}
+
+/// Nom recherché dans known_hosts: `hôte` ou `[hôte]:port` hors port 22
+fn lookup_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+/// Teste une liste de motifs known_hosts (hachés ou génériques) contre un nom
+fn host_matches(patterns: &str, lookup: &str) -> bool {
+    let mut plain = Vec::new();
+
+    for pattern in patterns.split(',') {
+        if let Some(hashed) = pattern.strip_prefix("|1|") {
+            if hashed_host_matches(hashed, lookup) {
+                return true;
+            }
+        } else {
+            plain.push(pattern);
+        }
+    }
+
+    match_pattern_list(plain, lookup)
+}
+
+/// Vérifie une entrée hachée `sel|hash` (HMAC-SHA1 du nom avec le sel)
+fn hashed_host_matches(hashed: &str, lookup: &str) -> bool {
+    let Some((salt, hash)) = hashed.split_once('|') else {
+        return false;
+    };
+    let (Ok(salt), Ok(hash)) = (
+        BASE64.decode(salt.as_bytes()),
+        BASE64.decode(hash.as_bytes()),
+    ) else {
+        return false;
+    };
+
+    match Hmac::<Sha1>::new_from_slice(&salt) {
+        Ok(mac) => mac
+            .chain_update(lookup.as_bytes())
+            .verify_slice(&hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const KEY_A: &str = "AAAAC3NzaC1lZDI1NTE5AAAAILIG2T/B0l0gaqj3puu510tu9N1OkQ4znY3LYuEm5zCF";
+    const KEY_B: &str = "AAAAC3NzaC1lZDI1NTE5AAAAIJdD7y3aLq454yWBdwLWbieU1ebz9/cu7/QEXn9OIeZJ";
+
+    fn key(data: &str) -> PublicKey {
+        russh::keys::parse_public_key_base64(data).unwrap()
+    }
+
+    fn known_hosts(content: &str) -> KnownHosts {
+        KnownHosts::parse(Path::new("known_hosts"), content)
+    }
+
+    #[test]
+    fn test_plain_and_port_entries() {
+        let kh = known_hosts(&format!(
+            "web01.example.com,10.0.0.1 ssh-ed25519 {}\n[bastion]:2222 ssh-ed25519 {}\n",
+            KEY_A, KEY_B
+        ));
+        assert_eq!(
+            kh.check("web01.example.com", 22, &key(KEY_A)),
+            HostKeyStatus::Known
+        );
+        assert_eq!(kh.check("bastion", 2222, &key(KEY_B)), HostKeyStatus::Known);
+        assert_eq!(kh.check("bastion", 22, &key(KEY_B)), HostKeyStatus::Unknown);
+    }
+
+    #[test]
+    fn test_hashed_entry() {
+        let kh = known_hosts(&format!(
+            "|1|O33ESRMWPVkMYIwJ1Uw+n877jTo=|nuuC5vEqXlEZ/8BXQR7m619W6Ak= ssh-ed25519 {}\n",
+            KEY_A
+        ));
+        assert_eq!(
+            kh.check("example.com", 22, &key(KEY_A)),
+            HostKeyStatus::Known
+        );
+        assert_eq!(
+            kh.check("example.org", 22, &key(KEY_A)),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn test_changed_and_revoked_keys() {
+        let kh = known_hosts(&format!(
+            "*.example.com ssh-ed25519 {}\n@revoked * ssh-ed25519 {}\n@cert-authority *.example.com ssh-ed25519 {}\n",
+            KEY_A, KEY_B, KEY_B
+        ));
+        assert!(matches!(
+            kh.check("web01.example.com", 22, &key(KEY_B)),
+            HostKeyStatus::Revoked { line: 2 }
+        ));
+
+        // L'autorité ne valide pas une clé brute: hôte signalé, pas inconnu
+        let kh = known_hosts(&format!(
+            "@cert-authority *.example.com ssh-ed25519 {}\n",
+            KEY_B
+        ));
+        assert_eq!(
+            kh.check("web01.example.com", 22, &key(KEY_A)),
+            HostKeyStatus::CertAuthorityOnly { line: 1 }
+        );
+        assert_eq!(
+            kh.check("example.org", 22, &key(KEY_A)),
+            HostKeyStatus::Unknown
+        );
+
+        let kh = known_hosts(&format!("*.example.com ssh-ed25519 {}\n", KEY_A));
+        assert!(matches!(
+            kh.check("web01.example.com", 22, &key(KEY_B)),
+            HostKeyStatus::Changed { line: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_verify_policies() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("known_hosts");
+
+        // strict: hôte inconnu refusé
+        let err = KnownHosts::verify(&path, "new-host", 22, &key(KEY_A), HostKeyPolicy::Strict)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<HostKeyError>(),
+            Some(HostKeyError::Unknown { .. })
+        ));
+
+        // accept-new: hôte enregistré puis reconnu
+        KnownHosts::verify(&path, "new-host", 22, &key(KEY_A), HostKeyPolicy::AcceptNew).unwrap();
+        KnownHosts::verify(&path, "new-host", 22, &key(KEY_A), HostKeyPolicy::Strict).unwrap();
+
+        // clé modifiée: refusée même en accept-new, avec les deux empreintes
+        let err = KnownHosts::verify(&path, "new-host", 22, &key(KEY_B), HostKeyPolicy::AcceptNew)
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains(&fingerprint(&key(KEY_A))));
+        assert!(message.contains(&fingerprint(&key(KEY_B))));
+
+        // off: tout est accepté
+        KnownHosts::verify(&path, "new-host", 22, &key(KEY_B), HostKeyPolicy::Off).unwrap();
+
+        // @cert-authority seul: refusé même en accept-new, et rien n'est enregistré
+        std::fs::write(
+            &path,
+            format!("@cert-authority *.example.com ssh-ed25519 {}\n", KEY_B),
+        )
+        .unwrap();
+        let err = KnownHosts::verify(
+            &path,
+            "web01.example.com",
+            22,
+            &key(KEY_A),
+            HostKeyPolicy::AcceptNew,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<HostKeyError>(),
+            Some(HostKeyError::Uncertified { line: 1, .. })
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        KnownHosts::verify(
+            &path,
+            "web01.example.com",
+            22,
+            &key(KEY_A),
+            HostKeyPolicy::Off,
+        )
+        .unwrap();
+    }
+}
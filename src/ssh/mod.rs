@@ -2,4 +2,11 @@
 pub mod agent;
 pub mod client;
 pub mod keys;
+pub mod known_hosts;
+pub mod password;
+pub mod pipeline;
 pub mod pool;
+pub mod retry;
+pub mod sftp;
+pub mod sudo;
+pub mod target;
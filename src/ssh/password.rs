@@ -0,0 +1,270 @@
+// Mots de passe SSH (authentification password et keyboard-interactive)
+//
+// Les réponses sont saisies via dialoguer une seule fois par hôte et par exécution,
+// ou fournies une fois pour toutes avec --password-stdin. Le dernier mot de passe
+// accepté est proposé d'abord aux autres hôtes: une nouvelle saisie n'est demandée
+// que s'il est refusé.
+use anyhow::{Context, Result};
+use console::Term;
+use dialoguer::{Input, Password};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
+
+/// Cache des mots de passe, partagé par toutes les connexions d'une exécution
+#[derive(Clone)]
+pub struct PasswordCache {
+    /// Par hôte ("user@host:port"); `None` = mot de passe refusé, ne plus le proposer
+    cache: Arc<RwLock<HashMap<String, Option<String>>>>,
+    /// Mot de passe lu sur stdin (--password-stdin), valable pour tous les hôtes
+    default: Option<Arc<String>>,
+    /// Dernier mot de passe accepté, essayé sur les hôtes sans entrée dédiée
+    last_accepted: Arc<RwLock<Option<String>>>,
+    /// Mot de passe sudo (--become), commun à tous les hôtes ; `Some(None)` = refusé
+    sudo: Arc<RwLock<Option<Option<String>>>>,
+    /// Autoriser les saisies interactives
+    interactive: bool,
+    /// Une seule invite à la fois, même avec des connexions parallèles
+    prompt_lock: Arc<Mutex<()>>,
+}
+
+impl PasswordCache {
+    /// Créer un cache vide, avec saisie interactive autorisée
+    pub fn new() -> Self {
+        Self {
+            cache: Arc::new(RwLock::new(HashMap::new())),
+            default: None,
+            last_accepted: Arc::new(RwLock::new(None)),
+            sudo: Arc::new(RwLock::new(None)),
+            interactive: true,
+            prompt_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Lire le mot de passe sur l'entrée standard (première ligne).
+    /// Les invites sont alors désactivées: stdin est réservé au pipeline.
+    pub fn from_stdin() -> Result<Self> {
+        let mut line = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut line)
+            .context("Impossible de lire le mot de passe sur stdin")?;
+        let password = line.trim_end_matches(['\r', '\n']).to_string();
+        if password.is_empty() {
+            anyhow::bail!("--password-stdin: aucun mot de passe reçu sur stdin");
+        }
+
+        Ok(Self::new().with_default(password).without_prompt())
+    }
+
+    /// Mot de passe proposé à tout hôte sans entrée dédiée
+    pub fn with_default(mut self, password: String) -> Self {
+        self.default = Some(Arc::new(password));
+        self
+    }
+
+    /// Ne jamais demander de saisie (mode non interactif)
+    pub fn without_prompt(mut self) -> Self {
+        self.interactive = false;
+        self
+    }
+
+    /// Mot de passe connu pour cet hôte
+    pub fn get(&self, host_key: &str) -> Option<String> {
+        let cache = self.cache.read().ok()?;
+        match cache.get(host_key) {
+            Some(entry) => entry.clone(),
+            None => self.default.as_deref().cloned(),
+        }
+    }
+
+    /// Mémoriser un mot de passe accepté
+    pub fn set(&self, host_key: &str, password: String) {
+        if let Ok(mut last_accepted) = self.last_accepted.write() {
+            *last_accepted = Some(password.clone());
+        }
+        if let Ok(mut cache) = self.cache.write() {
+            cache.insert(host_key.to_string(), Some(password));
+        }
+    }
+
+    /// Oublier un mot de passe refusé (y compris celui de --password-stdin)
+    pub fn forget(&self, host_key: &str) {
+        if let Ok(mut cache) = self.cache.write() {
+            cache.insert(host_key.to_string(), None);
+        }
+    }
+
+    /// Mot de passe pour l'hôte: depuis le cache, sinon le dernier accepté par un
+    /// autre hôte, sinon saisi par l'utilisateur (après un refus de cet hôte).
+    /// `None` si aucun n'est connu et qu'aucune saisie n'est possible.
+    pub async fn obtain(&self, host_key: &str) -> Result<Option<String>> {
+        let _guard = self.prompt_lock.lock().await;
+
+        // Une autre connexion a pu répondre pendant l'attente
+        if let Some(password) = self.get(host_key) {
+            return Ok(Some(password));
+        }
+        let refused = self
+            .cache
+            .read()
+            .is_ok_and(|cache| cache.contains_key(host_key));
+        if !refused {
+            if let Some(last) = self.last_accepted.read().ok().and_then(|last| last.clone()) {
+                return Ok(Some(last));
+            }
+        }
+        if !self.can_prompt() {
+            return Ok(None);
+        }
+
+        let password = Password::new()
+            .with_prompt(format!("🔐 Mot de passe pour {}", host_key))
+            .interact()
+            .context("Saisie du mot de passe annulée")?;
+        Ok(Some(password))
+    }
+
+    /// Question keyboard-interactive autre qu'un mot de passe (code OTP...), jamais mise en cache
+    pub async fn ask(&self, host_key: &str, prompt: &str, echo: bool) -> Result<Option<String>> {
+        let _guard = self.prompt_lock.lock().await;
+        if !self.can_prompt() {
+            return Ok(None);
+        }
+
+        let prompt = format!("🔐 {} {}", host_key, prompt.trim().trim_end_matches(':'));
+        let answer = if echo {
+            Input::<String>::new()
+                .with_prompt(prompt)
+                .allow_empty(true)
+                .interact_text()
+        } else {
+            Password::new()
+                .with_prompt(prompt)
+                .allow_empty_password(true)
+                .interact()
+        }
+        .context("Saisie annulée")?;
+        Ok(Some(answer))
+    }
+
+    /// Mot de passe sudo: saisi une seule fois pour toute l'exécution.
+    /// À défaut de saisie possible, celui de --password-stdin est essayé.
+    pub async fn obtain_sudo(&self) -> Result<Option<String>> {
+        let _guard = self.prompt_lock.lock().await;
+
+        if let Some(known) = self.sudo.read().ok().and_then(|sudo| sudo.clone()) {
+            return Ok(known);
+        }
+        let password = if self.can_prompt() {
+            Password::new()
+                .with_prompt("🔐 Mot de passe sudo (--become)")
+                .interact()
+                .context("Saisie du mot de passe sudo annulée")?
+        } else if let Some(default) = &self.default {
+            default.as_ref().clone()
+        } else {
+            return Ok(None);
+        };
+
+        if let Ok(mut sudo) = self.sudo.write() {
+            *sudo = Some(Some(password.clone()));
+        }
+        Ok(Some(password))
+    }
+
+    /// Mot de passe sudo refusé: ne plus le proposer ni le redemander
+    pub fn forget_sudo(&self) {
+        if let Ok(mut sudo) = self.sudo.write() {
+            *sudo = Some(None);
+        }
+    }
+
+    fn can_prompt(&self) -> bool {
+        self.interactive && Term::stderr().is_term()
+    }
+}
+
+impl Default for PasswordCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_per_host() {
+        let cache = PasswordCache::new();
+        assert_eq!(cache.get("ops@web01:22"), None);
+
+        cache.set("ops@web01:22", "secret".to_string());
+        let shared = cache.clone();
+        assert_eq!(shared.get("ops@web01:22").as_deref(), Some("secret"));
+        assert_eq!(shared.get("ops@web02:22"), None);
+    }
+
+    #[test]
+    fn test_default_password_and_forget() {
+        let cache = PasswordCache::new()
+            .with_default("from-stdin".to_string())
+            .without_prompt();
+        assert_eq!(cache.get("ops@web01:22").as_deref(), Some("from-stdin"));
+
+        // Refusé par web01: ne plus le proposer à cet hôte seulement
+        cache.forget("ops@web01:22");
+        assert_eq!(cache.get("ops@web01:22"), None);
+        assert_eq!(cache.get("ops@web02:22").as_deref(), Some("from-stdin"));
+    }
+
+    #[tokio::test]
+    async fn test_sudo_password_shared_then_forgotten() {
+        let cache = PasswordCache::new()
+            .with_default("from-stdin".to_string())
+            .without_prompt();
+        assert_eq!(
+            cache.obtain_sudo().await.unwrap().as_deref(),
+            Some("from-stdin")
+        );
+
+        cache.clone().forget_sudo();
+        assert_eq!(cache.obtain_sudo().await.unwrap(), None);
+        // Le mot de passe SSH de l'hôte n'est pas concerné
+        assert_eq!(cache.get("ops@web01:22").as_deref(), Some("from-stdin"));
+    }
+
+    #[tokio::test]
+    async fn test_obtain_without_prompt() {
+        let cache = PasswordCache::new().without_prompt();
+        assert_eq!(cache.obtain("ops@web01:22").await.unwrap(), None);
+
+        cache.set("ops@web01:22", "secret".to_string());
+        assert_eq!(
+            cache.obtain("ops@web01:22").await.unwrap().as_deref(),
+            Some("secret")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_last_accepted_password_offered_to_other_hosts() {
+        let cache = PasswordCache::new().without_prompt();
+        cache.set("ops@web01:22", "secret".to_string());
+
+        // Proposé à web02 sans nouvelle saisie
+        assert_eq!(
+            cache.obtain("ops@web02:22").await.unwrap().as_deref(),
+            Some("secret")
+        );
+
+        // Refusé par web02: une saisie serait demandée, impossible ici
+        cache.forget("ops@web02:22");
+        assert_eq!(cache.obtain("ops@web02:22").await.unwrap(), None);
+        assert_eq!(
+            cache.obtain("ops@web03:22").await.unwrap().as_deref(),
+            Some("secret")
+        );
+    }
+}
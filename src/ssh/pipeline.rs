@@ -0,0 +1,391 @@
+// Écritures SFTP en pipeline
+//
+// Une requête WRITE attend sa réponse pendant un aller-retour complet : envoyées
+// une à une, le débit est plafonné à taille / latence (64 Kio à 100 ms ≈ 640 Kio/s).
+// Comme `sftp -R`, plusieurs requêtes restent en vol et le serveur écrit chacune à
+// son offset, dans l'ordre d'arrivée des réponses.
+use crate::utils::interrupt;
+use anyhow::{Context, Result};
+use futures::stream::{FuturesUnordered, StreamExt};
+use russh_sftp::client::RawSftpSession;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Requêtes d'écriture en vol par défaut (--requests)
+pub const DEFAULT_REQUESTS: usize = 16;
+/// Taille par défaut d'une requête d'écriture (--chunk-size)
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+/// Plus grande requête acceptée par OpenSSH (paquet de 256 Kio, en-têtes compris)
+pub const MAX_CHUNK_SIZE: usize = 255 * 1024;
+
+/// Fenêtre d'écriture: requêtes en vol et taille de chacune
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pipeline {
+    pub requests: usize,
+    pub chunk_size: usize,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline {
+            requests: DEFAULT_REQUESTS,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl Pipeline {
+    /// Octets en vol au plus
+    pub fn window(&self) -> usize {
+        self.requests * self.chunk_size
+    }
+
+    /// Écrit le contenu de `reader` dans le fichier distant ouvert `handle`, à
+    /// partir de `offset`. Renvoie le nombre d'octets écrits; `hasher` reçoit le
+    /// contenu dans l'ordre du fichier et `on_written` les octets acquittés.
+    pub async fn write<R: AsyncRead + Unpin>(
+        &self,
+        session: &RawSftpSession,
+        handle: &str,
+        reader: &mut R,
+        offset: u64,
+        hasher: &mut Sha256,
+        on_written: impl Fn(u64),
+    ) -> Result<u64> {
+        let requests = self.requests.max(1);
+        let chunk_size = self.chunk_size.clamp(1, MAX_CHUNK_SIZE);
+        let mut in_flight = FuturesUnordered::new();
+        let mut position = offset;
+        let mut eof = false;
+
+        loop {
+            if interrupt::is_interrupted() {
+                anyhow::bail!("Téléversement interrompu");
+            }
+
+            while !eof && in_flight.len() < requests {
+                let chunk = read_chunk(reader, chunk_size)
+                    .await
+                    .context("Erreur lors de la lecture du fichier local")?;
+                if chunk.is_empty() {
+                    eof = true;
+                    break;
+                }
+                hasher.update(&chunk);
+                let (at, len) = (position, chunk.len() as u64);
+                position += len;
+                in_flight.push(async move {
+                    session
+                        .write(handle.to_string(), at, chunk)
+                        .await
+                        .map(|_| len)
+                });
+            }
+
+            match in_flight.next().await {
+                Some(written) => {
+                    let written =
+                        written.context("Erreur lors de l'écriture du fichier distant")?;
+                    on_written(written);
+                }
+                None => break,
+            }
+        }
+        Ok(position - offset)
+    }
+}
+
+/// Lire jusqu'à `size` octets (moins seulement en fin de fichier)
+async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R, size: usize) -> std::io::Result<Vec<u8>> {
+    let mut chunk = vec![0u8; size];
+    let mut filled = 0;
+    while filled < size {
+        let n = reader.read(&mut chunk[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    chunk.truncate(filled);
+    Ok(chunk)
+}
+
+/// Taille d'une requête en octets: `65536` ou `64K` (Kio)
+pub fn parse_size(value: &str) -> Result<usize, String> {
+    let value = value.trim();
+    let (digits, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((index, _)) => value.split_at(index),
+        None => (value, ""),
+    };
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        _ => return Err(format!("taille invalide '{}' (ex: 65536, 64K)", value)),
+    };
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|size| (1..=MAX_CHUNK_SIZE).contains(size))
+        .ok_or_else(|| {
+            format!(
+                "taille invalide '{}' (entre 1 et {} octets)",
+                value, MAX_CHUNK_SIZE
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use russh_sftp::protocol::{FileAttributes, Handle, OpenFlags, Status, StatusCode};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+    /// Écritures reçues par le serveur: (offset, données)
+    type Writes = Arc<Mutex<Vec<(u64, Vec<u8>)>>>;
+
+    /// Serveur SFTP en mémoire (comme le `SinkServer` du benchmark) qui
+    /// enregistre chaque écriture et refuse celle qui tombe à `fail_at`
+    struct RecordingServer {
+        writes: Writes,
+        fail_at: Option<u64>,
+    }
+
+    impl russh_sftp::server::Handler for RecordingServer {
+        type Error = StatusCode;
+
+        fn unimplemented(&self) -> Self::Error {
+            StatusCode::OpUnsupported
+        }
+
+        async fn open(
+            &mut self,
+            id: u32,
+            filename: String,
+            _pflags: OpenFlags,
+            _attrs: FileAttributes,
+        ) -> Result<Handle, Self::Error> {
+            Ok(Handle {
+                id,
+                handle: filename,
+            })
+        }
+
+        async fn write(
+            &mut self,
+            id: u32,
+            _handle: String,
+            offset: u64,
+            data: Vec<u8>,
+        ) -> Result<Status, Self::Error> {
+            if self.fail_at == Some(offset) {
+                return Err(StatusCode::Failure);
+            }
+            self.writes.lock().unwrap().push((offset, data));
+            Ok(Status {
+                id,
+                status_code: StatusCode::Ok,
+                error_message: "Ok".to_string(),
+                language_tag: "en-US".to_string(),
+            })
+        }
+    }
+
+    /// Session cliente reliée à un `RecordingServer`; les réponses arrivées
+    /// ensemble sont renvoyées en ordre inverse (compté dans `reordered`)
+    async fn start_recording_server(
+        fail_at: Option<u64>,
+    ) -> (RawSftpSession, String, Writes, Arc<AtomicUsize>) {
+        let (client, client_link) = tokio::io::duplex(1024 * 1024);
+        let (server_link, server) = tokio::io::duplex(1024 * 1024);
+        let (client_read, client_write) = tokio::io::split(client_link);
+        let (server_read, mut server_write) = tokio::io::split(server_link);
+        tokio::spawn(async move {
+            let mut client_read = client_read;
+            let _ = tokio::io::copy(&mut client_read, &mut server_write).await;
+        });
+        let reordered = Arc::new(AtomicUsize::new(0));
+        tokio::spawn(reverse_batches(
+            server_read,
+            client_write,
+            Arc::clone(&reordered),
+        ));
+
+        let writes = Writes::default();
+        let server_handler = RecordingServer {
+            writes: Arc::clone(&writes),
+            fail_at,
+        };
+        russh_sftp::server::run(server, server_handler).await;
+        let session = RawSftpSession::new(client);
+        session.init().await.unwrap();
+        let handle = session
+            .open(
+                "test.bin",
+                OpenFlags::CREATE | OpenFlags::WRITE,
+                FileAttributes::empty(),
+            )
+            .await
+            .unwrap()
+            .handle;
+        (session, handle, writes, reordered)
+    }
+
+    /// Retransmet les paquets SFTP par lots: tout ce qui arrive sans pause de
+    /// 20 ms est renvoyé du dernier au premier
+    async fn reverse_batches<R, W>(mut reader: R, mut writer: W, reordered: Arc<AtomicUsize>)
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        async fn packet<R: AsyncRead + Unpin>(reader: &mut R) -> Option<Vec<u8>> {
+            let len = reader.read_u32().await.ok()?;
+            let mut packet = len.to_be_bytes().to_vec();
+            packet.resize(4 + len as usize, 0);
+            reader.read_exact(&mut packet[4..]).await.ok()?;
+            Some(packet)
+        }
+
+        while let Some(first) = packet(&mut reader).await {
+            let mut batch = vec![first];
+            while let Ok(next) =
+                tokio::time::timeout(Duration::from_millis(20), packet(&mut reader)).await
+            {
+                match next {
+                    Some(next) => batch.push(next),
+                    None => break,
+                }
+            }
+            if batch.len() > 1 {
+                reordered.fetch_add(1, Ordering::SeqCst);
+            }
+            for packet in batch.iter().rev() {
+                if writer.write_all(packet).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn content(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_parse_size_units_and_limits() {
+        assert_eq!(parse_size("65536"), Ok(65536));
+        assert_eq!(parse_size("64K"), Ok(64 * 1024));
+        assert_eq!(parse_size("128kib"), Ok(128 * 1024));
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("256K").is_err()); // au-delà de la limite d'OpenSSH
+        assert!(parse_size("64Q").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_chunk_fills_until_eof() {
+        let data = [7u8; 10];
+        let mut reader = &data[..];
+        assert_eq!(read_chunk(&mut reader, 4).await.unwrap().len(), 4);
+        assert_eq!(read_chunk(&mut reader, 4).await.unwrap().len(), 4);
+        assert_eq!(read_chunk(&mut reader, 4).await.unwrap().len(), 2);
+        assert!(read_chunk(&mut reader, 4).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_write_places_chunks_despite_out_of_order_acks() {
+        let (session, handle, writes, reordered) = start_recording_server(None).await;
+        let data = content(10 * 1000 + 123);
+        let pipeline = Pipeline {
+            requests: 4,
+            chunk_size: 1000,
+        };
+        let acked = AtomicUsize::new(0);
+        let mut hasher = Sha256::new();
+
+        let written = pipeline
+            .write(&session, &handle, &mut &data[..], 0, &mut hasher, |n| {
+                acked.fetch_add(n as usize, Ordering::SeqCst);
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(written, data.len() as u64);
+        assert_eq!(acked.load(Ordering::SeqCst), data.len());
+        assert!(
+            reordered.load(Ordering::SeqCst) > 0,
+            "aucun acquittement réordonné"
+        );
+        assert_eq!(hasher.finalize(), Sha256::digest(&data));
+
+        let mut writes = writes.lock().unwrap().clone();
+        assert_eq!(writes.len(), 11);
+        writes.sort_by_key(|(offset, _)| *offset);
+        let mut expected = 0;
+        for (offset, chunk) in &writes {
+            assert_eq!(*offset, expected);
+            assert_eq!(chunk[..], data[expected as usize..][..chunk.len()]);
+            expected += chunk.len() as u64;
+        }
+        assert_eq!(expected, data.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_write_resumes_at_offset() {
+        let (session, handle, writes, _) = start_recording_server(None).await;
+        let data = content(2500);
+        let pipeline = Pipeline {
+            requests: 2,
+            chunk_size: 1000,
+        };
+
+        // Reprise après 4096 octets déjà présents: seule la suite est envoyée
+        let written = pipeline
+            .write(
+                &session,
+                &handle,
+                &mut &data[..],
+                4096,
+                &mut Sha256::new(),
+                |_| {},
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(written, 2500);
+        let mut offsets: Vec<u64> = writes.lock().unwrap().iter().map(|(o, _)| *o).collect();
+        offsets.sort_unstable();
+        assert_eq!(offsets, vec![4096, 5096, 6096]);
+    }
+
+    #[tokio::test]
+    async fn test_write_error_mid_window_is_returned() {
+        let (session, handle, writes, _) = start_recording_server(Some(3000)).await;
+        let data = content(8000);
+        let pipeline = Pipeline {
+            requests: 4,
+            chunk_size: 1000,
+        };
+
+        let err = pipeline
+            .write(
+                &session,
+                &handle,
+                &mut &data[..],
+                0,
+                &mut Sha256::new(),
+                |_| {},
+            )
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("écriture du fichier distant"));
+        assert!(writes
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|(offset, _)| *offset != 3000));
+    }
+}
@@ -1,87 +1,397 @@
-use crate::ssh::client::SshClient;
+use crate::config::{HostEntry, HostOptions, SshConfig};
+use crate::ssh::client::{ConnectOptions, SharedConnection, SshClient};
 use crate::ssh::keys::PassphraseCache;
-use anyhow::Result;
+use crate::ssh::password::PasswordCache;
+use crate::ssh::retry;
+use crate::ssh::target::{self, SshTarget, DEFAULT_SSH_PORT};
+use anyhow::{Context, Result};
 use dashmap::DashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::sync::{Mutex, OwnedMutexGuard, OwnedSemaphorePermit, Semaphore};
 
 const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
-const DEFAULT_MAX_CONCURRENT: usize = 10;
+/// Opérations simultanées par défaut, tous serveurs confondus (--jobs)
+pub const DEFAULT_JOBS: usize = 16;
+/// Opérations simultanées par défaut sur un même serveur (--jobs-per-host)
+pub const DEFAULT_JOBS_PER_HOST: usize = 4;
+
+/// Limites de parallélisme du pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Concurrency {
+    /// Opérations simultanées, tous serveurs confondus
+    pub global: usize,
+    /// Opérations simultanées sur un même serveur. Chacune occupe une session
+    /// SFTP, plus un canal exec le temps d'une commande: 4 opérations ouvrent
+    /// au plus 8 canaux, sous le MaxSessions d'OpenSSH (10 par défaut)
+    pub per_host: usize,
+}
+
+impl Default for Concurrency {
+    fn default() -> Self {
+        Concurrency {
+            global: DEFAULT_JOBS,
+            per_host: DEFAULT_JOBS_PER_HOST,
+        }
+    }
+}
+
+impl Concurrency {
+    /// Au moins une opération à la fois (`--jobs 0` reviendrait à ne rien faire)
+    pub fn at_least_one(self) -> Self {
+        Concurrency {
+            global: self.global.max(1),
+            per_host: self.per_host.max(1),
+        }
+    }
+}
+
+/// Sessions SFTP supplémentaires ouvertes sur la connexion d'un hôte
+struct Lanes {
+    connection: SharedConnection,
+    clients: Vec<Arc<Mutex<SshClient>>>,
+}
 
 /// Pool de connexions SSH partagé entre les tâches parallèles.
 /// Clone cheap — toutes les copies partagent les mêmes Arc internes.
 pub struct ConnectionPool {
     connections: Arc<DashMap<String, Arc<Mutex<SshClient>>>>,
+    /// Sessions SFTP en plus de celle de la connexion, par hôte
+    lanes: Arc<DashMap<String, Arc<Mutex<Lanes>>>>,
+    /// Sérialise l'ouverture de la connexion d'un hôte
+    connecting: Arc<DashMap<String, Arc<Mutex<()>>>>,
     semaphore: Arc<Semaphore>,
+    /// Sessions SFTP utilisables par hôte (voir `acquire_lane`)
+    per_host: usize,
     passphrase_cache: PassphraseCache,
+    password_cache: PasswordCache,
+    /// Options imposées par la CLI, prioritaires sur celles de hosts.json
+    overrides: Arc<HostOptions>,
+    /// ~/.ssh/config, lu une seule fois à la création du pool
+    ssh_config: Arc<SshConfig>,
+    /// Sessions des hôtes de rebond, partagées par toutes les cibles derrière eux
+    jump_hosts: Arc<DashMap<String, Arc<Mutex<SshClient>>>>,
+    /// Sérialise l'ouverture de chaque rebond pour qu'une seule session soit créée,
+    /// sans faire attendre les chaînes passant par d'autres bastions
+    jump_connecting: Arc<DashMap<String, Arc<Mutex<()>>>>,
 }
 
 impl Clone for ConnectionPool {
     fn clone(&self) -> Self {
         ConnectionPool {
             connections: Arc::clone(&self.connections),
+            lanes: Arc::clone(&self.lanes),
+            connecting: Arc::clone(&self.connecting),
             semaphore: Arc::clone(&self.semaphore),
+            per_host: self.per_host,
             passphrase_cache: self.passphrase_cache.clone(),
+            password_cache: self.password_cache.clone(),
+            overrides: Arc::clone(&self.overrides),
+            ssh_config: Arc::clone(&self.ssh_config),
+            jump_hosts: Arc::clone(&self.jump_hosts),
+            jump_connecting: Arc::clone(&self.jump_connecting),
         }
     }
 }
 
 impl ConnectionPool {
     pub fn new(passphrase_cache: PassphraseCache) -> Self {
-        Self::with_concurrency(DEFAULT_MAX_CONCURRENT, passphrase_cache)
-    }
-
-    pub fn with_concurrency(max_concurrent: usize, passphrase_cache: PassphraseCache) -> Self {
+        let concurrency = Concurrency::default();
         ConnectionPool {
             connections: Arc::new(DashMap::new()),
-            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            lanes: Arc::new(DashMap::new()),
+            connecting: Arc::new(DashMap::new()),
+            semaphore: Arc::new(Semaphore::new(concurrency.global)),
+            per_host: concurrency.per_host,
             passphrase_cache,
+            password_cache: PasswordCache::new(),
+            overrides: Arc::new(HostOptions::default()),
+            ssh_config: Arc::new(SshConfig::load_default()),
+            jump_hosts: Arc::new(DashMap::new()),
+            jump_connecting: Arc::new(DashMap::new()),
         }
     }
 
+    /// Limites de parallélisme (--jobs, --jobs-per-host), avant toute connexion
+    pub fn with_concurrency(mut self, concurrency: Concurrency) -> Self {
+        let concurrency = concurrency.at_least_one();
+        self.semaphore = Arc::new(Semaphore::new(concurrency.global));
+        self.per_host = concurrency.per_host;
+        self
+    }
+
+    /// Imposer des options de connexion (CLI) à tous les hôtes du pool
+    pub fn with_overrides(mut self, overrides: HostOptions) -> Self {
+        self.overrides = Arc::new(overrides);
+        self
+    }
+
+    /// Partager un cache de mots de passe (--password-stdin, mode non interactif)
+    pub fn with_password_cache(mut self, password_cache: PasswordCache) -> Self {
+        self.password_cache = password_cache;
+        self
+    }
+
+    /// Remplacer la configuration ~/.ssh/config chargée par défaut
+    #[allow(dead_code)]
+    pub fn with_ssh_config(mut self, ssh_config: SshConfig) -> Self {
+        self.ssh_config = Arc::new(ssh_config);
+        self
+    }
+
+    /// Résout un hôte de hosts.json en cible de connexion.
+    /// Priorités: CLI > hosts.json > ~/.ssh/config; le port de l'alias prime sur le champ `port`.
+    /// Les blocs `Host` de ~/.ssh/config sont comparés à l'hôte tel qu'écrit dans l'alias.
+    pub fn resolve(&self, host_entry: &HostEntry) -> Result<SshTarget> {
+        let (alias_user, alias_host, alias_port) = target::parse_destination(&host_entry.alias)?;
+        let ssh_config = self.ssh_config.resolve(alias_host, alias_user);
+
+        let mut effective = (*self.overrides).clone();
+        effective.inherit(&host_entry.options);
+        effective.inherit(&ssh_config.options);
+
+        let username = alias_user
+            .map(str::to_string)
+            .or(ssh_config.user)
+            .with_context(|| {
+                format!(
+                    "Aucun utilisateur pour '{}' (ni dans l'alias, ni via User dans ~/.ssh/config)",
+                    host_entry.alias
+                )
+            })?;
+
+        let mut options = ConnectOptions::from_host_options(&effective);
+        options.connect_timeout = ssh_config.connect_timeout;
+
+        let jumps = match effective.jump.as_deref() {
+            Some(chain) if !chain.eq_ignore_ascii_case("none") => chain
+                .split(',')
+                .map(str::trim)
+                .filter(|hop| !hop.is_empty())
+                .map(|hop| self.resolve_jump(hop, &username))
+                .collect::<Result<Vec<_>>>()
+                .with_context(|| format!("Rebond invalide '{}'", chain))?,
+            _ => Vec::new(),
+        };
+
+        Ok(SshTarget {
+            username,
+            host: ssh_config
+                .hostname
+                .unwrap_or_else(|| alias_host.to_string()),
+            port: alias_port.or(effective.port).unwrap_or(DEFAULT_SSH_PORT),
+            options,
+            jumps,
+        })
+    }
+
+    /// Résout un hôte de rebond ("[user@]host[:port]"), avec ~/.ssh/config.
+    /// Sans utilisateur explicite ni `User`, celui de la cible est repris.
+    /// Le ProxyJump propre au rebond est ignoré: la chaîne complète est celle déclarée.
+    fn resolve_jump(&self, hop: &str, target_user: &str) -> Result<SshTarget> {
+        let (hop_user, hop_host, hop_port) = target::parse_destination(hop)?;
+        let ssh_config = self.ssh_config.resolve(hop_host, hop_user);
+
+        let mut effective = (*self.overrides).clone();
+        effective.inherit(&ssh_config.options);
+
+        let mut options = ConnectOptions::from_host_options(&effective);
+        options.connect_timeout = ssh_config.connect_timeout;
+
+        Ok(SshTarget {
+            username: hop_user
+                .map(str::to_string)
+                .or(ssh_config.user)
+                .unwrap_or_else(|| target_user.to_string()),
+            host: ssh_config.hostname.unwrap_or_else(|| hop_host.to_string()),
+            port: hop_port.or(effective.port).unwrap_or(DEFAULT_SSH_PORT),
+            options,
+            jumps: Vec::new(),
+        })
+    }
+
     /// Acquérir (ou réutiliser) une connexion SSH.
     /// Retourne un OwnedSemaphorePermit à conserver pendant toute la durée d'utilisation.
     pub async fn acquire(
         &self,
-        host_key: &str,
-        username: &str,
-        host: &str,
+        target: &SshTarget,
     ) -> Result<(Arc<Mutex<SshClient>>, OwnedSemaphorePermit)> {
+        let host_key = target.pool_key();
         let permit = Arc::clone(&self.semaphore)
             .acquire_owned()
             .await
             .map_err(|e| anyhow::anyhow!("Pool semaphore fermé : {}", e))?;
 
-        if let Some(existing) = self.connections.get(host_key) {
+        if let Some(existing) = self.connections.get(&host_key) {
             log::debug!("♻️  Réutilisation connexion SSH : {}", host_key);
             return Ok((Arc::clone(existing.value()), permit));
         }
 
-        log::debug!("🔌 Nouvelle connexion SSH : {}@{}", username, host);
-        let mut client = SshClient::new_with_cache(host, username, self.passphrase_cache.clone())?;
-        client.connect_with_timeout(CONNECT_TIMEOUT).await?;
+        // Une seule connexion par hôte, même demandée par plusieurs tâches à la fois
+        let connecting = Arc::clone(self.connecting.entry(host_key.clone()).or_default().value());
+        let _connecting = connecting.lock().await;
+        if let Some(existing) = self.connections.get(&host_key) {
+            return Ok((Arc::clone(existing.value()), permit));
+        }
+
+        log::debug!("🔌 Nouvelle connexion SSH : {}", host_key);
+        let jump = self.acquire_jump_chain(&target.jumps).await?;
+        let mut client = SshClient::new_with_cache(
+            &target.host,
+            &target.username,
+            self.passphrase_cache.clone(),
+        )?
+        .with_port(target.port)
+        .with_options(target.options.clone())
+        .with_password_cache(self.password_cache.clone());
+        let timeout = target.options.connect_timeout.unwrap_or(CONNECT_TIMEOUT);
+        match jump {
+            Some(jump) => client.connect_via(Some(&jump), timeout).await?,
+            None => client.connect_with_timeout(timeout).await?,
+        }
 
+        if let Some(connection) = client.shared_connection() {
+            let lanes = Lanes {
+                connection,
+                clients: Vec::new(),
+            };
+            self.lanes
+                .insert(host_key.clone(), Arc::new(Mutex::new(lanes)));
+        }
         let arc = Arc::new(Mutex::new(client));
-        self.connections
-            .insert(host_key.to_string(), Arc::clone(&arc));
+        self.connections.insert(host_key, Arc::clone(&arc));
 
         Ok((arc, permit))
     }
 
+    /// Acquérir un client libre de l'hôte, prêt à l'emploi (verrouillé).
+    /// Jusqu'à `per_host` clients partagent la connexion, chacun avec sa session
+    /// SFTP: des transferts simultanés vers un même hôte ne s'attendent pas.
+    pub async fn acquire_lane(
+        &self,
+        target: &SshTarget,
+    ) -> Result<(OwnedMutexGuard<SshClient>, OwnedSemaphorePermit)> {
+        let (primary, permit) = self.acquire(target).await?;
+        if let Ok(client) = Arc::clone(&primary).try_lock_owned() {
+            return Ok((client, permit));
+        }
+
+        let host_key = target.pool_key();
+        let lanes = self.lanes.get(&host_key).map(|l| Arc::clone(l.value()));
+        if let Some(lanes) = lanes {
+            let mut lanes = lanes.lock().await;
+            for client in &lanes.clients {
+                if let Ok(client) = Arc::clone(client).try_lock_owned() {
+                    return Ok((client, permit));
+                }
+            }
+            if lanes.clients.len() + 1 < self.per_host {
+                match lanes.connection.open_client().await {
+                    Ok(client) => {
+                        log::debug!(
+                            "➕ Session SFTP n°{} : {}",
+                            lanes.clients.len() + 2,
+                            host_key
+                        );
+                        let client = Arc::new(Mutex::new(client));
+                        lanes.clients.push(Arc::clone(&client));
+                        return Ok((client.lock_owned().await, permit));
+                    }
+                    // Limite du serveur (MaxSessions): attendre une session libre
+                    Err(e) => log::debug!(
+                        "Session SFTP supplémentaire refusée par {} : {:#}",
+                        host_key,
+                        e
+                    ),
+                }
+            }
+        }
+        Ok((primary.lock_owned().await, permit))
+    }
+
+    /// Ouvre (ou réutilise) chaque rebond de la chaîne, chacun à travers le précédent.
+    /// Retourne le dernier rebond, par lequel la cible sera jointe.
+    async fn acquire_jump_chain(
+        &self,
+        jumps: &[SshTarget],
+    ) -> Result<Option<Arc<Mutex<SshClient>>>> {
+        if jumps.is_empty() {
+            return Ok(None);
+        }
+
+        let mut previous: Option<Arc<Mutex<SshClient>>> = None;
+
+        for (index, hop) in jumps.iter().enumerate() {
+            // Un même bastion joint par des chemins différents donne des sessions distinctes
+            let key = jumps[..=index]
+                .iter()
+                .map(SshTarget::connection_key)
+                .collect::<Vec<_>>()
+                .join(" → ");
+
+            let connecting =
+                Arc::clone(self.jump_connecting.entry(key.clone()).or_default().value());
+            let _connecting = connecting.lock().await;
+
+            let existing = self.jump_hosts.get(&key).map(|e| Arc::clone(e.value()));
+            if let Some(existing) = existing {
+                if existing.lock().await.is_connected() {
+                    log::debug!("♻️  Réutilisation rebond SSH : {}", key);
+                    previous = Some(existing);
+                    continue;
+                }
+            }
+
+            log::debug!("🔌 Nouveau rebond SSH : {}", key);
+            let mut client =
+                SshClient::new_with_cache(&hop.host, &hop.username, self.passphrase_cache.clone())?
+                    .with_port(hop.port)
+                    .with_options(hop.options.clone())
+                    .with_password_cache(self.password_cache.clone());
+            client
+                .connect_as_jump_host(
+                    previous.as_deref(),
+                    hop.options.connect_timeout.unwrap_or(CONNECT_TIMEOUT),
+                )
+                .await
+                .with_context(|| format!("Échec de connexion au rebond {}", hop.endpoint()))?;
+
+            let arc = Arc::new(Mutex::new(client));
+            self.jump_hosts.insert(key, Arc::clone(&arc));
+            previous = Some(arc);
+        }
+
+        Ok(previous)
+    }
+
     /// Invalider une connexion morte — sera recréée au prochain appel.
     pub fn invalidate(&self, host_key: &str) {
         log::debug!("🗑️  Invalidation connexion SSH : {}", host_key);
         self.connections.remove(host_key);
+        self.lanes.remove(host_key);
+    }
+
+    /// Invalider la connexion après un échec seulement si elle est en cause
+    /// (erreur réseau passagère ou connexion fermée): un refus propre à un
+    /// fichier ne coupe pas les autres sessions ouvertes vers l'hôte.
+    pub fn invalidate_on_error(&self, host_key: &str, client: &SshClient, error: &anyhow::Error) {
+        if connection_lost(client.is_connected(), error) {
+            self.invalidate(host_key);
+        }
     }
 
     /// Fermer proprement toutes les connexions du pool.
     pub async fn close_all(&self) {
-        let keys: Vec<String> = self.connections.iter().map(|e| e.key().clone()).collect();
-        for key in keys {
-            if let Some((_, client_arc)) = self.connections.remove(&key) {
-                if let Ok(mut client) = client_arc.try_lock() {
-                    let _ = client.disconnect().await;
+        // Les sessions supplémentaires se ferment avec leur connexion
+        self.lanes.clear();
+        // Les cibles d'abord: leurs tunnels passent par les rebonds
+        for map in [&self.connections, &self.jump_hosts] {
+            let keys: Vec<String> = map.iter().map(|e| e.key().clone()).collect();
+            for key in keys {
+                if let Some((_, client_arc)) = map.remove(&key) {
+                    if let Ok(mut client) = client_arc.try_lock() {
+                        let _ = client.disconnect().await;
+                    }
                 }
             }
         }
@@ -92,16 +402,250 @@ impl ConnectionPool {
     }
 }
 
+/// La connexion doit-elle être recréée après `error` ?
+fn connection_lost(connected: bool, error: &anyhow::Error) -> bool {
+    !connected || retry::is_transient(error)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_file_errors_keep_the_connection() {
+        let denied = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+            .context("Impossible de créer /etc/app/app.conf");
+        assert!(!connection_lost(true, &denied));
+        assert!(!connection_lost(
+            true,
+            &anyhow::anyhow!("Empreinte SHA-256 différente")
+        ));
+        // Connexion fermée ou erreur réseau: à recréer
+        assert!(connection_lost(false, &denied));
+        let reset = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::ConnectionReset));
+        assert!(connection_lost(true, &reset));
+    }
+
     #[test]
     fn test_pool_creation() {
         let pool = ConnectionPool::new(PassphraseCache::new());
         assert_eq!(pool.active_connections(), 0);
     }
 
+    #[test]
+    fn test_resolve_cli_overrides_host() {
+        let pool = ConnectionPool::new(PassphraseCache::new())
+            .with_ssh_config(SshConfig::default())
+            .with_overrides(HostOptions {
+                identity_file: Some("/keys/cli_key".into()),
+                ..Default::default()
+            });
+        let host_entry = HostEntry {
+            alias: "deploy@web01".to_string(),
+            env: "PROD".to_string(),
+            options: HostOptions {
+                identity_file: Some("/keys/host_key".into()),
+                identities_only: Some(true),
+                ..Default::default()
+            },
+        };
+
+        let target = pool.resolve(&host_entry).unwrap();
+        assert_eq!(
+            target.options.identity_file.as_deref(),
+            Some(std::path::Path::new("/keys/cli_key"))
+        );
+        assert!(target.options.identities_only);
+    }
+
+    #[test]
+    fn test_resolve_port_precedence() {
+        let pool =
+            ConnectionPool::new(PassphraseCache::new()).with_ssh_config(SshConfig::default());
+        let entry = |alias: &str, port| HostEntry {
+            alias: alias.to_string(),
+            env: "PROD".to_string(),
+            options: HostOptions {
+                port,
+                ..Default::default()
+            },
+        };
+
+        assert_eq!(pool.resolve(&entry("ops@web01", None)).unwrap().port, 22);
+        assert_eq!(
+            pool.resolve(&entry("ops@web01", Some(2222))).unwrap().port,
+            2222
+        );
+        assert_eq!(
+            pool.resolve(&entry("ops@web01:2200", Some(2222)))
+                .unwrap()
+                .port,
+            2200
+        );
+    }
+
+    #[test]
+    fn test_resolve_applies_ssh_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config");
+        std::fs::write(
+            &path,
+            "Host web01\n    HostName 10.0.0.11\n    User deploy\n    Port 2200\n    \
+             IdentityFile /keys/ssh_config_key\n    ConnectTimeout 3\n    ProxyJump bastion\n",
+        )
+        .unwrap();
+        let pool = ConnectionPool::new(PassphraseCache::new())
+            .with_ssh_config(SshConfig::load(&path).unwrap());
+        let entry = |alias: &str, options| HostEntry {
+            alias: alias.to_string(),
+            env: "PROD".to_string(),
+            options,
+        };
+
+        // Alias sans utilisateur : tout vient de ~/.ssh/config
+        let target = pool
+            .resolve(&entry("web01", HostOptions::default()))
+            .unwrap();
+        assert_eq!(target.endpoint(), "deploy@10.0.0.11:2200");
+        assert_eq!(
+            target.options.identity_file.as_deref(),
+            Some(std::path::Path::new("/keys/ssh_config_key"))
+        );
+        assert_eq!(target.options.connect_timeout, Some(Duration::from_secs(3)));
+        assert_eq!(target.jumps.len(), 1);
+        assert_eq!(target.jumps[0].endpoint(), "deploy@bastion:22");
+        assert_eq!(
+            target.pool_key(),
+            "deploy@bastion:22 → deploy@10.0.0.11:2200 (identity_file=/keys/ssh_config_key)"
+        );
+
+        // hosts.json prime sur ~/.ssh/config
+        let target = pool
+            .resolve(&entry(
+                "ops@web01",
+                HostOptions {
+                    port: Some(2222),
+                    ..Default::default()
+                },
+            ))
+            .unwrap();
+        assert_eq!(target.endpoint(), "ops@10.0.0.11:2222");
+
+        assert!(pool
+            .resolve(&entry("db01", HostOptions::default()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_resolve_jump_chain() {
+        let entry = |jump: Option<&str>| HostEntry {
+            alias: "deploy@10.1.0.5".to_string(),
+            env: "PROD".to_string(),
+            options: HostOptions {
+                jump: jump.map(str::to_string),
+                ..Default::default()
+            },
+        };
+        let pool =
+            ConnectionPool::new(PassphraseCache::new()).with_ssh_config(SshConfig::default());
+
+        let target = pool
+            .resolve(&entry(Some("ops@edge.example.com:2222, inner")))
+            .unwrap();
+        let hops: Vec<String> = target.jumps.iter().map(SshTarget::endpoint).collect();
+        assert_eq!(hops, vec!["ops@edge.example.com:2222", "deploy@inner:22"]);
+        assert!(pool.resolve(&entry(None)).unwrap().jumps.is_empty());
+
+        // --jump none impose une connexion directe malgré hosts.json
+        let direct = pool.clone().with_overrides(HostOptions {
+            jump: Some("none".to_string()),
+            ..Default::default()
+        });
+        assert!(direct
+            .resolve(&entry(Some("ops@edge.example.com")))
+            .unwrap()
+            .jumps
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_same_address_behind_two_bastions_is_two_connections() {
+        let entry = |jump: &str| HostEntry {
+            alias: "deploy@10.0.0.5".to_string(),
+            env: "PROD".to_string(),
+            options: HostOptions {
+                jump: Some(jump.to_string()),
+                ..Default::default()
+            },
+        };
+        let pool =
+            ConnectionPool::new(PassphraseCache::new()).with_ssh_config(SshConfig::default());
+
+        let eu = pool.resolve(&entry("ops@bastion-eu")).unwrap();
+        let us = pool.resolve(&entry("ops@bastion-us")).unwrap();
+        assert_eq!(eu.endpoint(), us.endpoint());
+        assert_ne!(eu.pool_key(), us.pool_key());
+
+        // Une connexion poolée via bastion-eu n'est pas réutilisée pour bastion-us
+        let client =
+            SshClient::new_with_cache(&eu.host, &eu.username, PassphraseCache::new()).unwrap();
+        let pooled = Arc::new(Mutex::new(client));
+        pool.connections.insert(eu.pool_key(), Arc::clone(&pooled));
+        let (reused, _permit) = pool.acquire(&eu).await.unwrap();
+        assert!(Arc::ptr_eq(&reused, &pooled));
+        assert!(!pool.connections.contains_key(&us.pool_key()));
+    }
+
+    #[tokio::test]
+    async fn test_jump_hosts_connect_independently() {
+        let hop = |host: &str| SshTarget {
+            username: "ops".to_string(),
+            host: host.to_string(),
+            port: 1,
+            options: ConnectOptions {
+                connect_timeout: Some(Duration::from_secs(1)),
+                ..Default::default()
+            },
+            jumps: Vec::new(),
+        };
+        let pool =
+            ConnectionPool::new(PassphraseCache::new()).with_ssh_config(SshConfig::default());
+
+        // Un rebond en cours d'ouverture vers bastion-eu ne bloque pas les autres bastions
+        let eu = Arc::clone(
+            pool.jump_connecting
+                .entry(hop("bastion-eu").connection_key())
+                .or_default()
+                .value(),
+        );
+        let _opening = eu.lock().await;
+
+        let other = tokio::time::timeout(
+            Duration::from_secs(5),
+            pool.acquire_jump_chain(&[hop("127.0.0.1")]),
+        )
+        .await;
+        assert!(matches!(other, Ok(Err(_))));
+
+        // Le même rebond attend la fin de l'ouverture en cours
+        let same = tokio::time::timeout(
+            Duration::from_millis(200),
+            pool.acquire_jump_chain(&[hop("bastion-eu")]),
+        )
+        .await;
+        assert!(same.is_err());
+    }
+
+    #[test]
+    fn test_concurrency_limits_are_at_least_one() {
+        let pool = ConnectionPool::new(PassphraseCache::new()).with_concurrency(Concurrency {
+            global: 0,
+            per_host: 0,
+        });
+        assert_eq!(pool.semaphore.available_permits(), 1);
+        assert_eq!(pool.per_host, 1);
+    }
+
     #[test]
     fn test_pool_clone_shares_state() {
         let pool = ConnectionPool::new(PassphraseCache::new());
@@ -0,0 +1,377 @@
+// Nouvelles tentatives après une erreur réseau passagère
+//
+// Seules les erreurs de transport (connexion coupée, délai dépassé, canal fermé)
+// sont retentées ; une erreur de permission, d'authentification ou d'intégrité
+// échoue immédiatement.
+use anyhow::Result;
+use serde::Serialize;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+use super::client::{AuthenticationError, ChecksumMismatch};
+use super::known_hosts::HostKeyError;
+use crate::utils::interrupt;
+
+/// Nombre de nouvelles tentatives par défaut (--retries)
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Politique de nouvelles tentatives: délai doublé à chaque essai
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Nouvelles tentatives après le premier essai (0 = aucune)
+    pub retries: u32,
+    /// Délai avant la première nouvelle tentative
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: DEFAULT_RETRIES,
+            delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(retries: u32) -> Self {
+        Self {
+            retries,
+            ..Default::default()
+        }
+    }
+
+    /// Exécuter `operation`, en la relançant tant que l'erreur est passagère
+    pub async fn run<T, F, Fut>(&self, label: &str, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(value),
+                Err(e)
+                    if attempt < self.retries
+                        && is_transient(&e)
+                        && !interrupt::is_interrupted() =>
+                {
+                    attempt += 1;
+                    let delay = self.delay * 2u32.saturating_pow(attempt - 1);
+                    log::warn!(
+                        "🔁 {} : nouvelle tentative {}/{} dans {:.0?} ({:#})",
+                        label,
+                        attempt,
+                        self.retries,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// L'erreur vient-elle du transport (à retenter) plutôt que de l'opération ?
+pub fn is_transient(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<russh_sftp::client::error::Error>() {
+            return is_transient_sftp(e);
+        }
+        if let Some(e) = cause.downcast_ref::<russh::Error>() {
+            return matches!(
+                e,
+                russh::Error::Disconnect
+                    | russh::Error::HUP
+                    | russh::Error::IO(_)
+                    | russh::Error::SendError
+                    | russh::Error::RecvError
+                    | russh::Error::ConnectionTimeout
+                    | russh::Error::KeepaliveTimeout
+                    | russh::Error::InactivityTimeout
+                    | russh::Error::Elapsed(_)
+            );
+        }
+        if let Some(e) = cause.downcast_ref::<io::Error>() {
+            return is_transient_io(e);
+        }
+        cause.is::<tokio::time::error::Elapsed>()
+    })
+}
+
+/// Nature d'un échec, pour les rapports (--output-format json) et la CI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// Clé d'hôte changée, révoquée ou inconnue (known_hosts)
+    HostKey,
+    /// Aucune méthode d'authentification acceptée
+    Authentication,
+    /// Connexion refusée, coupée ou délai dépassé
+    Network,
+    /// Droits insuffisants sur le serveur ou en local
+    Permission,
+    /// Fichier ou répertoire introuvable
+    NotFound,
+    /// Empreinte SHA-256 différente après transfert (--verify)
+    Integrity,
+    /// Ctrl-C
+    Interrupted,
+    Other,
+}
+
+impl FailureKind {
+    /// Classer une erreur d'après sa chaîne de causes
+    pub fn of(error: &anyhow::Error) -> Self {
+        Self::classify(error, interrupt::is_interrupted())
+    }
+
+    /// Une fois Ctrl-C reçu, les erreurs sans nature précise (connexion fermée,
+    /// commande arrêtée, transfert abandonné) viennent de l'interruption; une clé
+    /// d'hôte refusée ou une empreinte différente garde sa nature
+    fn classify(error: &anyhow::Error, interrupted: bool) -> Self {
+        if error.downcast_ref::<HostKeyError>().is_some() {
+            return FailureKind::HostKey;
+        }
+        if error.downcast_ref::<AuthenticationError>().is_some() {
+            return FailureKind::Authentication;
+        }
+        if error.downcast_ref::<ChecksumMismatch>().is_some() {
+            return FailureKind::Integrity;
+        }
+        if interrupted {
+            return FailureKind::Interrupted;
+        }
+        if is_transient(error) {
+            return FailureKind::Network;
+        }
+        for cause in error.chain() {
+            if let Some(russh_sftp::client::error::Error::Status(status)) =
+                cause.downcast_ref::<russh_sftp::client::error::Error>()
+            {
+                use russh_sftp::protocol::StatusCode;
+                match status.status_code {
+                    StatusCode::PermissionDenied => return FailureKind::Permission,
+                    StatusCode::NoSuchFile => return FailureKind::NotFound,
+                    _ => {}
+                }
+            }
+            if let Some(e) = cause.downcast_ref::<io::Error>() {
+                match e.kind() {
+                    io::ErrorKind::PermissionDenied => return FailureKind::Permission,
+                    io::ErrorKind::NotFound => return FailureKind::NotFound,
+                    _ => {}
+                }
+            }
+            // Erreurs SFTP converties en texte, messages de commandes distantes
+            let message = cause.to_string();
+            if message.contains("Permission denied") {
+                return FailureKind::Permission;
+            }
+            if message.contains("No such file") {
+                return FailureKind::NotFound;
+            }
+        }
+        FailureKind::Other
+    }
+}
+
+impl fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            FailureKind::HostKey => "clé d'hôte",
+            FailureKind::Authentication => "authentification",
+            FailureKind::Network => "réseau",
+            FailureKind::Permission => "permission",
+            FailureKind::NotFound => "introuvable",
+            FailureKind::Integrity => "intégrité",
+            FailureKind::Interrupted => "interrompu",
+            FailureKind::Other => "autre",
+        };
+        f.write_str(label)
+    }
+}
+
+fn is_transient_sftp(error: &russh_sftp::client::error::Error) -> bool {
+    use russh_sftp::client::error::Error;
+    use russh_sftp::protocol::StatusCode;
+
+    match error {
+        Error::Status(status) => matches!(
+            status.status_code,
+            StatusCode::NoConnection | StatusCode::ConnectionLost
+        ),
+        Error::IO(_) | Error::Timeout | Error::UnexpectedBehavior(_) => true,
+        Error::Limited(_) | Error::UnexpectedPacket => false,
+    }
+}
+
+fn is_transient_io(error: &io::Error) -> bool {
+    match error.kind() {
+        io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::ConnectionRefused
+        | io::ErrorKind::NotConnected
+        | io::ErrorKind::BrokenPipe
+        | io::ErrorKind::TimedOut
+        | io::ErrorKind::UnexpectedEof => true,
+        // Les fichiers SFTP convertissent leurs erreurs en texte (io::ErrorKind::Other)
+        io::ErrorKind::Other => {
+            let message = error.to_string();
+            [
+                "Timeout",
+                "I/O: ",
+                "SendError",
+                "RecvError",
+                "Connection lost",
+                "No connection",
+            ]
+            .iter()
+            .any(|marker| message.starts_with(marker))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_transient_classification() {
+        let reset = anyhow::Error::new(io::Error::from(io::ErrorKind::ConnectionReset))
+            .context("Erreur lors de l'écriture du fichier distant");
+        assert!(is_transient(&reset));
+
+        let sftp_timeout = anyhow::Error::new(io::Error::other("Timeout"));
+        assert!(is_transient(&sftp_timeout));
+
+        let denied = anyhow::Error::new(io::Error::other("Permission denied: /etc/app"));
+        assert!(!is_transient(&denied));
+
+        let local_missing = anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound));
+        assert!(!is_transient(&local_missing));
+
+        assert!(!is_transient(&anyhow::anyhow!(
+            "Empreinte SHA-256 différente pour /srv/app/x"
+        )));
+    }
+
+    #[test]
+    fn test_failure_kind_classification() {
+        let auth = anyhow::anyhow!("Aucune clé SSH n'a fonctionné").context(AuthenticationError {
+            user: "web".to_string(),
+            host: "web01:22".to_string(),
+        });
+        assert_eq!(
+            FailureKind::of(&auth.context("Connexion poolée")),
+            FailureKind::Authentication
+        );
+
+        let mismatch = anyhow::Error::new(ChecksumMismatch {
+            path: "/srv/app/x".to_string(),
+            expected: "aa".to_string(),
+            actual: "bb".to_string(),
+        });
+        assert_eq!(FailureKind::of(&mismatch), FailureKind::Integrity);
+
+        let denied = anyhow::Error::new(io::Error::other("Permission denied: /etc/app"))
+            .context("Impossible de créer le fichier distant: /etc/app");
+        assert_eq!(FailureKind::of(&denied), FailureKind::Permission);
+
+        let missing = anyhow::Error::new(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(FailureKind::of(&missing), FailureKind::NotFound);
+
+        let reset = anyhow::Error::new(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert_eq!(FailureKind::of(&reset), FailureKind::Network);
+
+        assert_eq!(
+            FailureKind::of(&anyhow::anyhow!("install: code 1")),
+            FailureKind::Other
+        );
+    }
+
+    #[test]
+    fn test_failure_kind_keeps_specific_kinds_after_interrupt() {
+        let unknown = anyhow::Error::new(HostKeyError::Unknown {
+            host: "web01".to_string(),
+            fingerprint: "SHA256:aa".to_string(),
+            path: "known_hosts".into(),
+        });
+        assert_eq!(FailureKind::classify(&unknown, true), FailureKind::HostKey);
+
+        let mismatch = anyhow::Error::new(ChecksumMismatch {
+            path: "/srv/app/x".to_string(),
+            expected: "aa".to_string(),
+            actual: "bb".to_string(),
+        });
+        assert_eq!(
+            FailureKind::classify(&mismatch, true),
+            FailureKind::Integrity
+        );
+
+        let auth = anyhow::Error::new(AuthenticationError {
+            user: "web".to_string(),
+            host: "web01:22".to_string(),
+        });
+        assert_eq!(
+            FailureKind::classify(&auth, true),
+            FailureKind::Authentication
+        );
+
+        // Connexion coupée par l'arrêt des opérations en cours
+        let reset = anyhow::Error::new(io::Error::from(io::ErrorKind::ConnectionReset));
+        assert_eq!(
+            FailureKind::classify(&reset, true),
+            FailureKind::Interrupted
+        );
+        assert_eq!(FailureKind::classify(&reset, false), FailureKind::Network);
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_only_transient_errors() {
+        let policy = RetryPolicy {
+            retries: 2,
+            delay: Duration::ZERO,
+        };
+
+        let calls = AtomicU32::new(0);
+        let value = policy
+            .run("web01", || async {
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(io::Error::from(io::ErrorKind::BrokenPipe)).context("envoi")
+                } else {
+                    Ok(42)
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!((value, calls.load(Ordering::SeqCst)), (42, 2));
+
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = policy
+            .run("web01", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                anyhow::bail!("Permission refusée")
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let calls = AtomicU32::new(0);
+        let result: Result<()> = policy
+            .run("web01", || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(io::Error::from(io::ErrorKind::TimedOut).into())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,179 @@
+// Session SFTP d'un client
+//
+// Chaque client n'ouvre qu'un canal SFTP: les écritures en pipeline (ssh::pipeline),
+// les extensions OpenSSH et les opérations de fichiers courantes passent toutes par
+// la même `RawSftpSession`. Une connexion compte ainsi un canal SFTP par transfert
+// simultané, plus un canal le temps d'une commande (sha256sum, chown, sudo...).
+use anyhow::{Context, Result};
+use russh_sftp::client::error::Error;
+use russh_sftp::client::rawsession::SftpResult;
+use russh_sftp::client::RawSftpSession;
+use russh_sftp::protocol::{FileAttributes, FileType, OpenFlags, StatusCode};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Extension OpenSSH: renommage qui écrase la destination, comme rename(2)
+pub const POSIX_RENAME: &str = "posix-rename@openssh.com";
+/// Extension OpenSSH: fsync(2) du fichier distant avant sa fermeture
+pub const FSYNC: &str = "fsync@openssh.com";
+/// Délai de réponse d'une requête SFTP
+const TIMEOUT: Duration = Duration::from_secs(60);
+/// Taille d'une requête de lecture
+const READ_SIZE: u32 = 64 * 1024;
+
+/// Session SFTP et extensions annoncées par le serveur
+pub struct Sftp {
+    pub session: RawSftpSession,
+    pub posix_rename: bool,
+    pub fsync: bool,
+}
+
+/// Entrée d'un répertoire distant
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    name: String,
+    attributes: FileAttributes,
+}
+
+impl DirEntry {
+    pub fn file_name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn file_type(&self) -> FileType {
+        self.attributes.file_type()
+    }
+
+    pub fn metadata(&self) -> FileAttributes {
+        self.attributes.clone()
+    }
+}
+
+impl Sftp {
+    /// Démarrer le protocole SFTP sur un canal où le sous-système est lancé
+    pub async fn start<S>(stream: S) -> Result<Self>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let session = RawSftpSession::new(stream);
+        // Avec plusieurs écritures en vol, la dernière attend que les
+        // précédentes aient traversé le lien
+        session.set_timeout(TIMEOUT.as_secs()).await;
+        let version = session
+            .init()
+            .await
+            .context("Impossible d'initialiser la session SFTP")?;
+        log::debug!(
+            "Extensions SFTP: {:?}",
+            version.extensions.keys().collect::<Vec<_>>()
+        );
+
+        Ok(Self {
+            posix_rename: version
+                .extensions
+                .get(POSIX_RENAME)
+                .is_some_and(|v| v == "1"),
+            fsync: version.extensions.get(FSYNC).is_some_and(|v| v == "1"),
+            session,
+        })
+    }
+
+    /// Attributs d'un chemin distant (liens suivis)
+    pub async fn metadata(&self, path: &str) -> SftpResult<FileAttributes> {
+        Ok(self.session.stat(path).await?.attrs)
+    }
+
+    pub async fn set_metadata(&self, path: &str, attributes: FileAttributes) -> SftpResult<()> {
+        self.session.setstat(path, attributes).await.map(|_| ())
+    }
+
+    pub async fn try_exists(&self, path: &str) -> SftpResult<bool> {
+        match self.metadata(path).await {
+            Ok(_) => Ok(true),
+            Err(Error::Status(status)) if status.status_code == StatusCode::NoSuchFile => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn read_link(&self, path: &str) -> SftpResult<String> {
+        let name = self.session.readlink(path).await?;
+        name.files
+            .into_iter()
+            .next()
+            .map(|file| file.filename)
+            .ok_or_else(|| Error::UnexpectedBehavior("lien sans cible".to_string()))
+    }
+
+    pub async fn create_dir(&self, path: &str) -> SftpResult<()> {
+        self.session
+            .mkdir(path, FileAttributes::empty())
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn remove_file(&self, path: &str) -> SftpResult<()> {
+        self.session.remove(path).await.map(|_| ())
+    }
+
+    pub async fn remove_dir(&self, path: &str) -> SftpResult<()> {
+        self.session.rmdir(path).await.map(|_| ())
+    }
+
+    pub async fn rename(&self, from: &str, to: &str) -> SftpResult<()> {
+        self.session.rename(from, to).await.map(|_| ())
+    }
+
+    /// Contenu d'un répertoire (sans `.` ni `..`)
+    pub async fn read_dir(&self, path: &str) -> SftpResult<Vec<DirEntry>> {
+        let handle = self.session.opendir(path).await?.handle;
+        let mut entries = Vec::new();
+        let listed = loop {
+            match self.session.readdir(handle.as_str()).await {
+                Ok(name) => entries.extend(
+                    name.files
+                        .into_iter()
+                        .filter(|file| file.filename != "." && file.filename != "..")
+                        .map(|file| DirEntry {
+                            name: file.filename,
+                            attributes: file.attrs,
+                        }),
+                ),
+                Err(Error::Status(status)) if status.status_code == StatusCode::Eof => {
+                    break Ok(entries)
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        self.session.close(handle).await?;
+        listed
+    }
+
+    /// Ouvrir un fichier distant en lecture; renvoie son handle
+    pub async fn open_read(&self, path: &str) -> SftpResult<String> {
+        Ok(self
+            .session
+            .open(path, OpenFlags::READ, FileAttributes::empty())
+            .await?
+            .handle)
+    }
+
+    /// Lire le bloc suivant à `offset` (au plus `max` octets); `None` en fin de fichier
+    pub async fn read_chunk(
+        &self,
+        handle: &str,
+        offset: u64,
+        max: usize,
+    ) -> SftpResult<Option<Vec<u8>>> {
+        let len = u32::try_from(max).unwrap_or(READ_SIZE).min(READ_SIZE);
+        match self.session.read(handle, offset, len).await {
+            Ok(data) if data.data.is_empty() => Ok(None),
+            Ok(data) => Ok(Some(data.data)),
+            Err(Error::Status(status)) if status.status_code == StatusCode::Eof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn close(&self, handle: &str) -> SftpResult<()> {
+        self.session.close(handle).await.map(|_| ())
+    }
+}
@@ -0,0 +1,159 @@
+// Élévation de privilèges (--become) via sudo
+//
+// La commande est lancée par `sudo -p <invite> -u <user>` dans un pseudo-terminal.
+// L'invite, reconnaissable, est retirée de la sortie et le mot de passe y est
+// répondu depuis `PasswordCache` (saisi une seule fois par exécution).
+
+/// Invite de mot de passe demandée à sudo, repérée dans la sortie
+pub const SUDO_PROMPT: &str = "[xsshend] sudo password:";
+
+/// Utilisateur cible par défaut de `--become`
+pub const DEFAULT_BECOME_USER: &str = "root";
+
+/// Protéger une valeur pour le shell distant (guillemets simples)
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Envelopper une commande pour l'exécuter en tant que `user`
+pub fn wrap_command(command: &str, user: &str) -> String {
+    format!(
+        "sudo -p {} -u {} -- sh -c {}",
+        shell_quote(SUDO_PROMPT),
+        shell_quote(user),
+        shell_quote(command)
+    )
+}
+
+/// Rediriger l'entrée standard d'une commande (enveloppée ou non) depuis un
+/// fichier distant: la redirection est faite par le shell de connexion, qui
+/// peut lire le fichier même quand l'utilisateur cible ne le peut pas
+pub fn redirect_input(command: &str, path: &str) -> String {
+    format!("{} < {}", command, shell_quote(path))
+}
+
+/// Retire l'invite sudo d'un flux de sortie, même découpée entre plusieurs paquets
+#[derive(Debug, Default)]
+pub struct PromptFilter {
+    /// Octets retenus: début possible d'une invite
+    pending: Vec<u8>,
+    /// Retour à la ligne écrit par sudo après la saisie, à retirer aussi
+    skip_newline: bool,
+}
+
+impl PromptFilter {
+    /// Filtrer des données reçues: renvoie la sortie nettoyée et le nombre
+    /// d'invites rencontrées
+    pub fn push(&mut self, data: &[u8]) -> (Vec<u8>, usize) {
+        let prompt = SUDO_PROMPT.as_bytes();
+        self.pending.extend_from_slice(data);
+
+        let mut output = Vec::new();
+        let mut prompts = 0;
+        loop {
+            if self.skip_newline {
+                if self.pending.starts_with(b"\r\n") {
+                    self.pending.drain(..2);
+                } else if self.pending.starts_with(b"\n") {
+                    self.pending.drain(..1);
+                } else if self.pending == b"\r" || self.pending.is_empty() {
+                    // Attendre la suite pour décider
+                    break;
+                }
+                self.skip_newline = false;
+            }
+
+            match find(&self.pending, prompt) {
+                Some(pos) => {
+                    output.extend(self.pending.drain(..pos));
+                    self.pending.drain(..prompt.len());
+                    prompts += 1;
+                    self.skip_newline = true;
+                }
+                None => {
+                    // Conserver la fin si elle peut être le début d'une invite
+                    let keep = (1..prompt.len().min(self.pending.len() + 1))
+                        .rev()
+                        .find(|n| self.pending.ends_with(&prompt[..*n]))
+                        .unwrap_or(0);
+                    let emit = self.pending.len() - keep;
+                    output.extend(self.pending.drain(..emit));
+                    break;
+                }
+            }
+        }
+
+        (output, prompts)
+    }
+
+    /// Fin du flux: rendre les octets retenus
+    pub fn flush(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_command_quotes() {
+        assert_eq!(
+            wrap_command("echo 'hi' && id", "www-data"),
+            r#"sudo -p '[xsshend] sudo password:' -u 'www-data' -- sh -c 'echo '\''hi'\'' && id'"#
+        );
+    }
+
+    #[test]
+    fn test_redirect_stays_outside_sudo() {
+        let command = redirect_input(&wrap_command("cat /dev/stdin", "app"), "/tmp/.x y");
+        assert_eq!(
+            command,
+            r#"sudo -p '[xsshend] sudo password:' -u 'app' -- sh -c 'cat /dev/stdin' < '/tmp/.x y'"#
+        );
+    }
+
+    #[test]
+    fn test_prompt_stripped_with_following_newline() {
+        let mut filter = PromptFilter::default();
+        let (output, prompts) = filter.push(b"[xsshend] sudo password:\r\nuid=0(root)\r\n");
+        assert_eq!(prompts, 1);
+        assert_eq!(output, b"uid=0(root)\r\n");
+    }
+
+    #[test]
+    fn test_prompt_split_across_chunks() {
+        let mut filter = PromptFilter::default();
+        let (output, prompts) = filter.push(b"lecture\r\n[xsshend] su");
+        assert_eq!((output.as_slice(), prompts), (&b"lecture\r\n"[..], 0));
+
+        let (output, prompts) = filter.push(b"do password:");
+        assert_eq!((output.as_slice(), prompts), (&b""[..], 1));
+
+        let (output, prompts) = filter.push(b"\nok\n");
+        assert_eq!((output.as_slice(), prompts), (&b"ok\n"[..], 0));
+    }
+
+    #[test]
+    fn test_partial_prompt_released_on_flush() {
+        let mut filter = PromptFilter::default();
+        let (output, _) = filter.push(b"total: 3 [xs");
+        assert_eq!(output, b"total: 3 ");
+        assert_eq!(filter.flush(), b"[xs");
+    }
+
+    #[test]
+    fn test_repeated_prompt_counted() {
+        let mut filter = PromptFilter::default();
+        let (output, prompts) = filter
+            .push(b"[xsshend] sudo password:\r\nSorry, try again.\r\n[xsshend] sudo password:");
+        assert_eq!(prompts, 2);
+        assert_eq!(output, b"Sorry, try again.\r\n");
+    }
+}
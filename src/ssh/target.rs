@@ -0,0 +1,279 @@
+// Résolution des cibles de connexion SSH (alias hosts.json → utilisateur, hôte, port)
+use anyhow::{Context, Result};
+
+use super::client::ConnectOptions;
+use super::known_hosts::HostKeyPolicy;
+
+/// Port SSH par défaut
+pub const DEFAULT_SSH_PORT: u16 = 22;
+
+/// Cible de connexion entièrement résolue
+#[derive(Debug, Clone)]
+pub struct SshTarget {
+    pub username: String,
+    pub host: String,
+    pub port: u16,
+    pub options: ConnectOptions,
+    /// Hôtes de rebond, du premier au dernier (vide: connexion directe)
+    pub jumps: Vec<SshTarget>,
+}
+
+impl SshTarget {
+    /// `user@hôte:port`, sans les rebonds
+    pub fn endpoint(&self) -> String {
+        format!(
+            "{}@{}",
+            self.username,
+            format_host_port(&self.host, self.port)
+        )
+    }
+
+    /// `endpoint()` suivi des options qui changent la session obtenue: une clé
+    /// imposée ou une autre politique de clé d'hôte donne une connexion distincte
+    pub fn connection_key(&self) -> String {
+        let options = &self.options;
+        let mut settings = Vec::new();
+        if let Some(identity_file) = &options.identity_file {
+            settings.push(format!("identity_file={}", identity_file.display()));
+        }
+        if options.identities_only {
+            settings.push("identities_only".to_string());
+        }
+        if options.host_key_policy != HostKeyPolicy::default() {
+            settings.push(format!("host_key_policy={}", options.host_key_policy));
+        }
+
+        if settings.is_empty() {
+            self.endpoint()
+        } else {
+            format!("{} ({})", self.endpoint(), settings.join(", "))
+        }
+    }
+
+    /// Clé du pool de connexions: deux ports différents ne partagent pas de connexion,
+    /// ni une même adresse privée jointe par deux rebonds différents, ni un même
+    /// serveur déclaré avec deux clés ou deux politiques de clé d'hôte
+    pub fn pool_key(&self) -> String {
+        self.jumps
+            .iter()
+            .chain(std::iter::once(self))
+            .map(SshTarget::connection_key)
+            .collect::<Vec<_>>()
+            .join(" → ")
+    }
+}
+
+/// Formate `hôte:port`, avec crochets pour les adresses IPv6
+pub fn format_host_port(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// Décompose un alias serveur en (utilisateur, hôte, port optionnel)
+///
+/// Formats acceptés :
+/// - `user@host` et `user@host:port`
+/// - `user@[2001:db8::1]` et `user@[2001:db8::1]:port`
+/// - `ssh://user@host[:port]` (les deux formes précédentes)
+/// - `user@2001:db8::1` (IPv6 sans crochets, donc sans port)
+pub fn parse_alias(alias: &str) -> Result<(&str, &str, Option<u16>)> {
+    match parse_destination(alias)? {
+        (Some(username), host, port) => Ok((username, host, port)),
+        (None, _, _) => Err(invalid_alias(alias)),
+    }
+}
+
+/// Comme `parse_alias`, mais l'utilisateur est facultatif (`host`, `host:port`) :
+/// il peut alors venir de `User` dans ~/.ssh/config.
+pub fn parse_destination(alias: &str) -> Result<(Option<&str>, &str, Option<u16>)> {
+    let invalid = || invalid_alias(alias);
+
+    let stripped = alias
+        .strip_prefix("ssh://")
+        .map(|rest| rest.trim_end_matches('/'))
+        .unwrap_or(alias);
+
+    let (username, host_part) = match stripped.rsplit_once('@') {
+        Some(("", _)) => return Err(invalid()),
+        Some((username, host_part)) => (Some(username), host_part),
+        None => (None, stripped),
+    };
+    if host_part.is_empty() {
+        return Err(invalid());
+    }
+
+    let (host, port) = if let Some(bracketed) = host_part.strip_prefix('[') {
+        // IPv6 entre crochets, port optionnel après "]:"
+        let (host, rest) = bracketed.split_once(']').ok_or_else(invalid)?;
+        let port = match rest {
+            "" => None,
+            _ => Some(rest.strip_prefix(':').ok_or_else(invalid)?),
+        };
+        (host, port)
+    } else if host_part.matches(':').count() > 1 {
+        // IPv6 nue : impossible d'y distinguer un port
+        (host_part, None)
+    } else {
+        match host_part.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (host_part, None),
+        }
+    };
+
+    if host.is_empty() {
+        return Err(invalid());
+    }
+
+    let port = port
+        .map(|p| {
+            p.parse::<u16>()
+                .ok()
+                .filter(|p| *p != 0)
+                .with_context(|| format!("Port invalide '{}' dans l'alias '{}'", p, alias))
+        })
+        .transpose()?;
+
+    Ok((username, host, port))
+}
+
+fn invalid_alias(alias: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Alias serveur invalide '{}' - format attendu: user@host[:port], user@[ipv6]:port ou ssh://user@host:port",
+        alias
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_alias_forms() {
+        assert_eq!(
+            parse_alias("deploy@web01").unwrap(),
+            ("deploy", "web01", None)
+        );
+        assert_eq!(
+            parse_alias("deploy@web01:2222").unwrap(),
+            ("deploy", "web01", Some(2222))
+        );
+        assert_eq!(
+            parse_alias("ops@[2001:db8::1]:2200").unwrap(),
+            ("ops", "2001:db8::1", Some(2200))
+        );
+        assert_eq!(
+            parse_alias("ops@[2001:db8::1]").unwrap(),
+            ("ops", "2001:db8::1", None)
+        );
+        assert_eq!(
+            parse_alias("ops@2001:db8::1").unwrap(),
+            ("ops", "2001:db8::1", None)
+        );
+        assert_eq!(
+            parse_alias("ssh://root@bastion.example.com:22022/").unwrap(),
+            ("root", "bastion.example.com", Some(22022))
+        );
+    }
+
+    #[test]
+    fn test_parse_alias_invalid() {
+        assert!(parse_alias("web01").is_err());
+        assert!(parse_alias("user@web01:ssh").is_err());
+        assert!(parse_alias("user@web01:0").is_err());
+        assert!(parse_alias("user@[::1]2222").is_err());
+        assert!(parse_alias("ssh://@web01").is_err());
+    }
+
+    #[test]
+    fn test_parse_destination_without_user() {
+        assert_eq!(parse_destination("web01").unwrap(), (None, "web01", None));
+        assert_eq!(
+            parse_destination("web01:2222").unwrap(),
+            (None, "web01", Some(2222))
+        );
+        assert!(parse_destination("@web01").is_err());
+        assert!(parse_destination("").is_err());
+    }
+
+    #[test]
+    fn test_pool_key_includes_port() {
+        let target = |host: &str, port| SshTarget {
+            username: "ops".to_string(),
+            host: host.to_string(),
+            port,
+            options: ConnectOptions::default(),
+            jumps: Vec::new(),
+        };
+        assert_eq!(target("web01", 22).pool_key(), "ops@web01:22");
+        assert_ne!(
+            target("web01", 22).pool_key(),
+            target("web01", 2222).pool_key()
+        );
+        assert_eq!(target("::1", 2222).pool_key(), "ops@[::1]:2222");
+    }
+
+    #[test]
+    fn test_pool_key_includes_connect_options() {
+        let target = |options| SshTarget {
+            username: "ops".to_string(),
+            host: "web01".to_string(),
+            port: DEFAULT_SSH_PORT,
+            options,
+            jumps: Vec::new(),
+        };
+        let deploy_key = ConnectOptions {
+            identity_file: Some("/keys/deploy".into()),
+            identities_only: true,
+            ..Default::default()
+        };
+        let strict = ConnectOptions {
+            host_key_policy: HostKeyPolicy::Strict,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            target(deploy_key.clone()).pool_key(),
+            "ops@web01:22 (identity_file=/keys/deploy, identities_only)"
+        );
+        assert_eq!(
+            target(strict.clone()).pool_key(),
+            "ops@web01:22 (host_key_policy=strict)"
+        );
+        // Deux entrées hosts.json vers le même serveur ne partagent pas leur session
+        let default = target(ConnectOptions::default()).pool_key();
+        assert_ne!(default, target(deploy_key).pool_key());
+        assert_ne!(default, target(strict).pool_key());
+        // Le délai de connexion ne change pas la session obtenue
+        let slow = ConnectOptions {
+            connect_timeout: Some(std::time::Duration::from_secs(30)),
+            ..Default::default()
+        };
+        assert_eq!(default, target(slow).pool_key());
+    }
+
+    #[test]
+    fn test_pool_key_includes_jump_chain() {
+        let target = |host: &str, jumps| SshTarget {
+            username: "ops".to_string(),
+            host: host.to_string(),
+            port: DEFAULT_SSH_PORT,
+            options: ConnectOptions::default(),
+            jumps,
+        };
+        let via = |bastion: &str| target("10.0.0.5", vec![target(bastion, Vec::new())]);
+
+        assert_eq!(
+            via("bastion-eu").pool_key(),
+            "ops@bastion-eu:22 → ops@10.0.0.5:22"
+        );
+        assert_eq!(via("bastion-eu").endpoint(), "ops@10.0.0.5:22");
+        assert_ne!(via("bastion-eu").pool_key(), via("bastion-us").pool_key());
+        assert_ne!(
+            via("bastion-eu").pool_key(),
+            target("10.0.0.5", Vec::new()).pool_key()
+        );
+    }
+}
@@ -0,0 +1,94 @@
+// Interruption (Ctrl-C) des commandes et transferts distants en cours
+//
+// Premier Ctrl-C : chaque commande en cours reçoit TERM puis KILL (voir SshClient),
+// chaque transfert s'arrête et supprime son fichier temporaire.
+// Sans opération en cours, ou au second Ctrl-C : arrêt immédiat (code 130).
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use tokio::sync::watch;
+
+/// Code de sortie conventionnel après SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+struct InterruptState {
+    /// Passe à `true` au premier Ctrl-C
+    sender: watch::Sender<bool>,
+    /// Nombre de commandes distantes en cours
+    running: AtomicUsize,
+}
+
+fn state() -> &'static InterruptState {
+    static STATE: OnceLock<InterruptState> = OnceLock::new();
+    STATE.get_or_init(|| InterruptState {
+        sender: watch::Sender::new(false),
+        running: AtomicUsize::new(0),
+    })
+}
+
+/// Intercepter Ctrl-C (à appeler une fois, depuis le runtime tokio)
+pub fn install() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+
+        let state = state();
+        if state.running.load(Ordering::SeqCst) == 0 {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        eprintln!(
+            "\n⚠️  Interruption : arrêt des opérations distantes (Ctrl-C à nouveau pour forcer)"
+        );
+        state.sender.send_replace(true);
+
+        let _ = tokio::signal::ctrl_c().await;
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    });
+}
+
+/// L'utilisateur a-t-il demandé l'interruption ?
+pub fn is_interrupted() -> bool {
+    *state().sender.borrow()
+}
+
+/// Commande ou transfert distant en cours, à l'écoute des interruptions
+pub struct RunningCommand {
+    receiver: watch::Receiver<bool>,
+}
+
+impl RunningCommand {
+    pub fn start() -> Self {
+        let state = state();
+        state.running.fetch_add(1, Ordering::SeqCst);
+        Self {
+            receiver: state.sender.subscribe(),
+        }
+    }
+
+    /// Se termine quand l'interruption est demandée (immédiatement si elle l'est déjà)
+    pub async fn interrupted(&mut self) {
+        let _ = self.receiver.wait_for(|interrupted| *interrupted).await;
+    }
+}
+
+impl Drop for RunningCommand {
+    fn drop(&mut self) {
+        state().running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_running_command_waits_for_interrupt() {
+        let mut running = RunningCommand::start();
+        assert!(state().running.load(Ordering::SeqCst) >= 1);
+        assert!(!is_interrupted());
+
+        let waited = tokio::time::timeout(Duration::from_millis(20), running.interrupted()).await;
+        assert!(waited.is_err());
+    }
+}
@@ -1,3 +1,6 @@
 // Module utilitaires
+pub mod interrupt;
 pub mod logger;
 pub mod path_expansion;
+pub mod pattern;
+pub mod progress;
@@ -4,6 +4,7 @@
 /// - Variables d'environnement ($HOME, $USER, etc.)
 /// - Tilde (~user ou ~ seul)
 use anyhow::Result;
+use std::path::{Path, PathBuf};
 
 /// Expanse un chemin contenant des variables d'environnement ou tilde
 ///
@@ -70,3 +71,17 @@ fn expand_variables(path: &str, target_user: &str, remote_home: Option<&str>) ->
 
     Ok(result)
 }
+
+/// Expanse `~` en début d'un chemin local avec le HOME de l'utilisateur courant
+///
+/// Utilisé pour les chemins de clés déclarés dans hosts.json ou `~/.ssh/config`.
+pub fn expand_local_path(path: &Path) -> PathBuf {
+    let Ok(rest) = path.strip_prefix("~") else {
+        return path.to_path_buf();
+    };
+
+    match dirs::home_dir() {
+        Some(home) => home.join(rest),
+        None => path.to_path_buf(),
+    }
+}
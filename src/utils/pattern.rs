@@ -0,0 +1,79 @@
+// Correspondance de motifs à la manière d'OpenSSH
+//
+// Utilisé pour les noms d'hôtes de `known_hosts` et de `~/.ssh/config` :
+// - `*` correspond à zéro ou plusieurs caractères
+// - `?` correspond à exactement un caractère
+// - `!motif` dans une liste exclut les noms correspondants
+
+/// Teste si `text` correspond au motif `pattern` (`*` et `?` supportés)
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position du dernier '*' rencontré et position du texte associée
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Teste une liste de motifs séparés par des virgules (ou des espaces).
+///
+/// Retourne `true` si au moins un motif positif correspond et qu'aucun
+/// motif négatif (`!motif`) ne correspond.
+pub fn match_pattern_list<'a, I>(patterns: I, text: &str) -> bool
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut matched = false;
+
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if wildcard_match(negated, text) {
+                return false;
+            }
+        } else if wildcard_match(pattern, text) {
+            matched = true;
+        }
+    }
+
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.example.com", "web01.example.com"));
+        assert!(wildcard_match("web0?", "web01"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("web0?", "web010"));
+        assert!(!wildcard_match("*.example.com", "example.org"));
+    }
+
+    #[test]
+    fn test_pattern_list_negation() {
+        let patterns = ["*.example.com", "!db*.example.com"];
+        assert!(match_pattern_list(patterns, "web01.example.com"));
+        assert!(!match_pattern_list(patterns, "db01.example.com"));
+        assert!(!match_pattern_list(["!db*"], "web01"));
+    }
+}
@@ -0,0 +1,275 @@
+// Progression des transferts, à l'octet près
+//
+// Une barre par transfert actif (serveur) et une barre globale, avec débit et
+// temps restant, alimentées par la boucle de lecture/écriture de SshClient.
+// Hors terminal (stdout redirigé, CI), les barres sont masquées et une ligne
+// d'avancement est écrite sur stderr à intervalle régulier.
+use indicatif::{
+    HumanBytes, HumanDuration, MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Intervalle des lignes d'avancement hors terminal
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Affichage de l'ensemble des transferts d'une exécution
+#[derive(Clone)]
+pub struct TransferDisplay {
+    multi: MultiProgress,
+    total: ProgressBar,
+    /// Barres dessinées (terminal); sinon lignes périodiques
+    interactive: bool,
+    /// Pas de messages sur stdout (sortie JSON)
+    quiet: bool,
+    last_report: Arc<Mutex<Instant>>,
+}
+
+impl TransferDisplay {
+    /// `total_bytes`: somme des octets à envoyer, tous serveurs confondus
+    pub fn new(total_bytes: u64, quiet: bool) -> Self {
+        Self::with_interactive(total_bytes, quiet, std::io::stdout().is_terminal())
+    }
+
+    fn with_interactive(total_bytes: u64, quiet: bool, interactive: bool) -> Self {
+        let target = if interactive {
+            ProgressDrawTarget::stderr()
+        } else {
+            ProgressDrawTarget::hidden()
+        };
+        let multi = MultiProgress::with_draw_target(target);
+        let total = multi.add(ProgressBar::new(total_bytes));
+        total.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {bytes_per_sec} ETA {eta} {msg}",
+                )
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        total.set_message("total");
+
+        Self {
+            multi,
+            total,
+            interactive,
+            quiet,
+            last_report: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Nouvelle barre pour un transfert vers un serveur (`size` octets au total)
+    pub fn start(&self, label: &str, size: u64) -> TransferProgress {
+        let bar = self
+            .multi
+            .insert_before(&self.total, ProgressBar::new(size));
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "  {prefix:20!} [{bar:30.green/white}] {bytes}/{total_bytes} {bytes_per_sec} ETA {eta} {wide_msg}",
+                )
+                .unwrap()
+                .progress_chars("=> "),
+        );
+        bar.set_prefix(label.to_string());
+
+        TransferProgress {
+            bar,
+            display: Some(self.clone()),
+            file_sent: Arc::new(AtomicU64::new(0)),
+            last_report: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Ajouter `bytes` au total attendu (taille découverte en cours d'exécution)
+    pub fn add_total(&self, bytes: u64) {
+        self.total.inc_length(bytes);
+    }
+
+    /// Afficher une ligne au-dessus des barres
+    pub fn println(&self, line: &str) {
+        if self.interactive {
+            self.total.println(line);
+        } else if !self.quiet {
+            println!("{}", line);
+        }
+    }
+
+    /// Fin de l'exécution: figer la barre globale
+    pub fn finish(&self) {
+        self.total.finish();
+    }
+
+    /// Ligne d'avancement global (hors terminal), au plus une par intervalle
+    fn report_total(&self) {
+        if self.interactive || !due(&self.last_report) {
+            return;
+        }
+        eprintln!("📊 Total: {}", describe(&self.total));
+    }
+}
+
+/// Progression d'un transfert vers un serveur, un ou plusieurs fichiers
+#[derive(Clone)]
+pub struct TransferProgress {
+    bar: ProgressBar,
+    display: Option<TransferDisplay>,
+    /// Octets comptés pour le fichier en cours
+    file_sent: Arc<AtomicU64>,
+    last_report: Arc<Mutex<Instant>>,
+}
+
+impl TransferProgress {
+    /// Passer au fichier suivant (nom affiché à côté de la barre)
+    pub fn next_file(&self, name: &str) {
+        self.file_sent.store(0, Ordering::SeqCst);
+        self.bar.set_message(name.to_string());
+    }
+
+    /// Progression d'un fichier envoyé en même temps que d'autres vers ce
+    /// serveur: même barre, décompte propre au fichier
+    pub fn file(&self, name: &str) -> TransferProgress {
+        self.bar.set_message(name.to_string());
+        TransferProgress {
+            file_sent: Arc::new(AtomicU64::new(0)),
+            ..self.clone()
+        }
+    }
+
+    /// (Re)commencer l'envoi du fichier en cours à `offset` octets: une
+    /// tentative précédente est décomptée, une reprise (--resume) comptée
+    pub fn restart_file(&self, offset: u64) {
+        let sent = self.file_sent.swap(offset, Ordering::SeqCst);
+        self.shift(sent, offset);
+    }
+
+    /// Fichier terminé (envoyé ou inchangé): `size` octets comptés au total
+    pub fn complete_file(&self, size: u64) {
+        let sent = self.file_sent.swap(0, Ordering::SeqCst);
+        self.shift(sent, size);
+    }
+
+    /// Octets écrits sur le serveur
+    pub fn inc(&self, bytes: u64) {
+        self.file_sent.fetch_add(bytes, Ordering::SeqCst);
+        self.bar.inc(bytes);
+        if let Some(display) = &self.display {
+            display.total.inc(bytes);
+            if !display.interactive {
+                if due(&self.last_report) {
+                    eprintln!(
+                        "📤 {} {}: {}",
+                        self.bar.prefix(),
+                        self.bar.message(),
+                        describe(&self.bar)
+                    );
+                }
+                display.report_total();
+            }
+        }
+    }
+
+    /// Transfert terminé: retirer la barre
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+        if let Some(display) = &self.display {
+            display.multi.remove(&self.bar);
+        }
+    }
+
+    /// Corriger le décompte du fichier en cours de `from` à `to` octets
+    fn shift(&self, from: u64, to: u64) {
+        if to >= from {
+            self.bar.inc(to - from);
+            if let Some(display) = &self.display {
+                display.total.inc(to - from);
+            }
+        } else {
+            self.bar.dec(from - to);
+            if let Some(display) = &self.display {
+                display.total.dec(from - to);
+            }
+        }
+    }
+}
+
+/// L'intervalle de rapport est-il écoulé ? (le remet à zéro si oui)
+fn due(last_report: &Mutex<Instant>) -> bool {
+    let Ok(mut last) = last_report.lock() else {
+        return false;
+    };
+    if last.elapsed() < REPORT_INTERVAL {
+        return false;
+    }
+    *last = Instant::now();
+    true
+}
+
+/// "1.2 GiB / 5.0 GiB (45.3 MiB/s, reste 1m 20s)"
+fn describe(bar: &ProgressBar) -> String {
+    let total = bar.length().unwrap_or(0);
+    let mut line = format!(
+        "{} / {} ({}/s",
+        HumanBytes(bar.position()),
+        HumanBytes(total),
+        HumanBytes(bar.per_sec() as u64)
+    );
+    if bar.position() < total && bar.per_sec() > 0.0 {
+        line.push_str(&format!(", reste {}", HumanDuration(bar.eta())));
+    }
+    line.push(')');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_and_resume_keep_totals_consistent() {
+        let display = TransferDisplay::with_interactive(300, true, false);
+        let transfer = display.start("WEB_01", 300);
+
+        transfer.next_file("a.bin");
+        transfer.restart_file(0);
+        transfer.inc(60);
+        // Échec puis reprise à 40 octets: les 20 octets perdus sont décomptés
+        transfer.restart_file(40);
+        assert_eq!(
+            (transfer.bar.position(), display.total.position()),
+            (40, 40)
+        );
+        transfer.inc(60);
+        transfer.complete_file(100);
+
+        // Fichier inchangé: compté sans transfert
+        transfer.next_file("b.bin");
+        transfer.complete_file(200);
+        assert_eq!(transfer.bar.position(), 300);
+        assert_eq!(display.total.position(), 300);
+    }
+
+    #[test]
+    fn test_concurrent_files_keep_their_own_count() {
+        let display = TransferDisplay::with_interactive(300, true, false);
+        let transfer = display.start("WEB_01", 300);
+
+        let a = transfer.file("a.bin");
+        let b = transfer.file("b.bin");
+        a.restart_file(0);
+        b.restart_file(0);
+        a.inc(50);
+        b.inc(80);
+        // Nouvelle tentative de b: seuls ses 80 octets sont décomptés
+        b.restart_file(0);
+        assert_eq!(transfer.bar.position(), 50);
+        b.inc(200);
+        b.complete_file(200);
+        a.inc(50);
+        a.complete_file(100);
+        assert_eq!(transfer.bar.position(), 300);
+        assert_eq!(display.total.position(), 300);
+    }
+}
@@ -67,6 +67,7 @@ mod cli_tests {
         assert!(stdout.contains("--server-type")); // Changed from --type to --server-type
         assert!(stdout.contains("--dest"));
         assert!(stdout.contains("--dry-run"));
+        assert!(stdout.contains("--jobs-per-host"));
         assert!(stdout.contains("--key")); // Now --key is present in global options
                                            // Vérifier que --ssh-key n'est plus présent
         assert!(!stdout.contains("--ssh-key"));
@@ -79,6 +80,7 @@ mod cli_tests {
         assert!(output.status.success());
         let stdout = String::from_utf8(output.stdout).unwrap();
         assert!(stdout.contains("Liste les serveurs"));
+        assert!(stdout.contains("--resolved"));
     }
 
     #[tokio::test]
@@ -129,6 +131,32 @@ mod cli_tests {
         assert!(stdout.contains("Simulation terminée"));
     }
 
+    #[tokio::test]
+    async fn test_cli_upload_directory_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let dist = temp_dir.path().join("dist");
+        fs::create_dir_all(dist.join("assets")).unwrap();
+        fs::write(dist.join("index.html"), "<html>").unwrap();
+        fs::write(dist.join("assets/app.js"), "js").unwrap();
+        fs::write(dist.join("assets/app.js.map"), "map").unwrap();
+
+        let output = run_xsshend_with_args(&[
+            "upload",
+            dist.to_str().unwrap(),
+            "--env",
+            "Development",
+            "--exclude",
+            "*.map",
+            "--dry-run",
+        ]);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("2 fichier(s)"));
+        assert!(stdout.contains("dist/assets/app.js"));
+        assert!(!stdout.contains("app.js.map"));
+    }
+
     #[tokio::test]
     async fn test_cli_upload_nonexistent_file() {
         let output = run_xsshend_with_args(&[
@@ -225,6 +253,79 @@ mod cli_tests {
         assert!(stdout.contains("Liste des cibles SSH"));
     }
 
+    #[tokio::test]
+    async fn test_cli_sync_help() {
+        let output = run_xsshend_with_args(&["sync", "--help"]);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("Reproduit à l'identique"));
+        assert!(stdout.contains("--dest"));
+        assert!(stdout.contains("--delete"));
+        assert!(stdout.contains("--exclude"));
+        assert!(stdout.contains("--dry-run"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_sync_rejects_file_and_root_destination() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = create_test_file(&temp_dir, "index.html", "<html>");
+
+        let output = run_xsshend_with_args(&[
+            "sync",
+            test_file.to_str().unwrap(),
+            "--dest",
+            "/var/www",
+            "--dry-run",
+        ]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("n'est pas un répertoire local"));
+
+        let output = run_xsshend_with_args(&[
+            "sync",
+            temp_dir.path().to_str().unwrap(),
+            "--dest",
+            "/",
+            "--dry-run",
+        ]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Refus de synchroniser la racine"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_download_help() {
+        let output = run_xsshend_with_args(&["download", "--help"]);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("Télécharge des fichiers"));
+        assert!(stdout.contains("--into"));
+        assert!(stdout.contains("--template"));
+        assert!(stdout.contains("--recursive"));
+        assert!(stdout.contains("--jobs-per-host"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_download_rejects_unknown_template_variable() {
+        let output =
+            run_xsshend_with_args(&["download", "/etc/hosts", "--template", "{host}/{file}"]);
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("variable inconnue {file}"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_rollback_rejects_invalid_run_id() {
+        let output = run_xsshend_with_args(&["rollback", "../hosts", "--yes"]);
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Identifiant d'exécution invalide"));
+    }
+
     #[tokio::test]
     async fn test_cli_invalid_command() {
         let output = run_xsshend_with_args(&["invalid-command"]);
@@ -338,6 +439,24 @@ mod cli_tests {
         assert!(stderr.contains("unexpected argument") || stderr.contains("unrecognized"));
     }
 
+    #[tokio::test]
+    async fn test_cli_command_stdin_conflicts_with_password_stdin() {
+        let output = run_xsshend_with_args(&[
+            "--password-stdin",
+            "command",
+            "--inline",
+            "psql app",
+            "--env",
+            "Staging",
+            "--stdin",
+            "--yes",
+        ]);
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("--stdin et --password-stdin"));
+    }
+
     #[tokio::test]
     async fn test_cli_destination_default() {
         let temp_dir = TempDir::new().unwrap();
@@ -355,4 +474,94 @@ mod cli_tests {
         let stdout = String::from_utf8(output.stdout).unwrap();
         assert!(stdout.contains("/tmp/")); // Destination par défaut
     }
+
+    #[tokio::test]
+    async fn test_cli_upload_rejects_oversized_chunk() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = create_test_file(&temp_dir, "app.jar", "jar");
+
+        let output = run_xsshend_with_args(&[
+            "upload",
+            test_file.to_str().unwrap(),
+            "--chunk-size",
+            "1024K",
+            "--dry-run",
+        ]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("taille invalide"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_upload_rejects_invalid_min_success() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = create_test_file(&temp_dir, "app.jar", "jar");
+
+        let output = run_xsshend_with_args(&[
+            "upload",
+            test_file.to_str().unwrap(),
+            "--min-success",
+            "150%",
+            "--dry-run",
+        ]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("seuil invalide"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_upload_expands_glob_relative_to_base() {
+        let temp_dir = TempDir::new().unwrap();
+        let build = temp_dir.path().join("build");
+        fs::create_dir_all(build.join("libs")).unwrap();
+        fs::write(build.join("app.jar"), "app").unwrap();
+        fs::write(build.join("libs/core.jar"), "core").unwrap();
+        fs::write(build.join("libs/notes.txt"), "notes").unwrap();
+        let pattern = format!("{}/**/*.jar", build.display());
+
+        let output = run_xsshend_with_args(&[
+            "upload",
+            &pattern,
+            "--base",
+            build.to_str().unwrap(),
+            "--env",
+            "Development",
+            "--dry-run",
+        ]);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("→ libs/core.jar"));
+        assert!(stdout.contains("→ app.jar"));
+        assert!(!stdout.contains("notes.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_cli_upload_files_from_reports_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let jar = create_test_file(&temp_dir, "app.jar", "jar");
+        let list = create_test_file(
+            &temp_dir,
+            "list.txt",
+            &format!(
+                "{0}\n{0}\n# commentaire\n{1}/*.war\n",
+                jar.display(),
+                temp_dir.path().display()
+            ),
+        );
+
+        let output = run_xsshend_with_args(&[
+            "upload",
+            "--files-from",
+            list.to_str().unwrap(),
+            "--env",
+            "Development",
+            "--dry-run",
+        ]);
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("1 source(s) introuvable(s)"));
+        assert!(stderr.contains("*.war (aucune correspondance)"));
+    }
 }
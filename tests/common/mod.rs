@@ -94,10 +94,12 @@ impl TestEnvironment {
         public_a.insert("WEB_SERVER_01".to_string(), HostEntry {
             alias: "web01@prod-web-01.example.com".to_string(),
             env: "PROD".to_string(),
+            ..Default::default()
         });
         public_a.insert("API_SERVER_01".to_string(), HostEntry {
             alias: "api01@prod-api-01.example.com".to_string(),
             env: "PROD".to_string(),
+            ..Default::default()
         });
 
         // Region-A Private
@@ -105,6 +107,7 @@ impl TestEnvironment {
         private_a.insert("DATABASE_01".to_string(), HostEntry {
             alias: "db01@prod-db-01.example.com".to_string(),
             env: "PROD".to_string(),
+            ..Default::default()
         });
 
         region_a.insert("Public".to_string(), public_a);
@@ -115,6 +118,7 @@ impl TestEnvironment {
         public_b.insert("WEB_SERVER_02".to_string(), HostEntry {
             alias: "web02@prod-web-02.example.com".to_string(),
             env: "PROD".to_string(),
+            ..Default::default()
         });
 
         region_b.insert("Public".to_string(), public_b);
@@ -131,6 +135,7 @@ impl TestEnvironment {
         stage_public.insert("STAGE_WEB_01".to_string(), HostEntry {
             alias: "web01@stage-web-01.example.com".to_string(),
             env: "STAGE".to_string(),
+            ..Default::default()
         });
 
         stage_region_a.insert("Public".to_string(), stage_public);
@@ -145,13 +150,17 @@ impl TestEnvironment {
         dev_services.insert("DEV_DATABASE".to_string(), HostEntry {
             alias: "dev@dev-db.local.example.com".to_string(),
             env: "DEV".to_string(),
+            ..Default::default()
         });
 
         dev_local.insert("Services".to_string(), dev_services);
         development.insert("Local".to_string(), dev_local);
         environments.insert("Development".to_string(), development);
 
-        HostsConfig { environments }
+        HostsConfig {
+        environments,
+        ..Default::default()
+    }
     }
 
     /// Contenu d'une clé Ed25519 de test
@@ -186,6 +195,7 @@ pub fn create_simple_test_hosts() -> Vec<(String, HostEntry)> {
             HostEntry {
                 alias: "user1@server1.example.com".to_string(),
                 env: "TEST".to_string(),
+                ..Default::default()
             },
         ),
         (
@@ -193,6 +203,7 @@ pub fn create_simple_test_hosts() -> Vec<(String, HostEntry)> {
             HostEntry {
                 alias: "user2@server2.example.com".to_string(),
                 env: "TEST".to_string(),
+                ..Default::default()
             },
         ),
     ]
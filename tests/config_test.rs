@@ -74,6 +74,7 @@ mod config_tests {
         let host_entry = HostEntry {
             alias: "test@example.com".to_string(),
             env: "TEST".to_string(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&host_entry).unwrap();
@@ -192,6 +193,7 @@ mod config_tests {
     async fn test_empty_config() {
         let empty_config = HostsConfig {
             environments: std::collections::HashMap::new(),
+            ..Default::default()
         };
 
         let all_hosts = empty_config.filter_hosts(None, None, None);
@@ -227,6 +229,7 @@ mod config_tests {
         let host_entry = HostEntry {
             alias: "testuser@testhost.com".to_string(),
             env: "TESTING".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(host_entry.alias, "testuser@testhost.com");
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use xsshend::utils::path_expansion::expand_path;
+    use std::path::Path;
+    use xsshend::utils::path_expansion::{expand_local_path, expand_path};
 
     #[test]
     fn test_tilde_expansion_default_home() {
@@ -85,4 +86,22 @@ mod tests {
             "/opt/$APP/logs"
         );
     }
+
+    #[test]
+    fn test_local_tilde_expansion() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            expand_local_path(Path::new("~/.ssh/prod_key")),
+            home.join(".ssh/prod_key")
+        );
+        // Chemins absolus et ~user laissés tels quels
+        assert_eq!(
+            expand_local_path(Path::new("/etc/ssh/key")),
+            Path::new("/etc/ssh/key")
+        );
+        assert_eq!(
+            expand_local_path(Path::new("~bob/key")),
+            Path::new("~bob/key")
+        );
+    }
 }
@@ -19,6 +19,7 @@ mod uploader_tests {
                 HostEntry {
                     alias: "web01@prod-web-01.example.com".to_string(),
                     env: "PROD".to_string(),
+                    ..Default::default()
                 },
             ),
             (
@@ -26,6 +27,7 @@ mod uploader_tests {
                 HostEntry {
                     alias: "api01@prod-api-01.example.com".to_string(),
                     env: "PROD".to_string(),
+                    ..Default::default()
                 },
             ),
         ]
@@ -47,14 +49,30 @@ mod uploader_tests {
     #[tokio::test]
     async fn test_parse_server_alias_valid() {
         // Test avec un alias valide
-        let (username, host) = Uploader::parse_server_alias("user@example.com").unwrap();
+        let (username, host, port) = Uploader::parse_server_alias("user@example.com").unwrap();
         assert_eq!(username, "user");
         assert_eq!(host, "example.com");
+        assert_eq!(port, None);
 
         // Test avec port
-        let (username, host) = Uploader::parse_server_alias("admin@server.local:2222").unwrap();
+        let (username, host, port) =
+            Uploader::parse_server_alias("admin@server.local:2222").unwrap();
         assert_eq!(username, "admin");
-        assert_eq!(host, "server.local:2222");
+        assert_eq!(host, "server.local");
+        assert_eq!(port, Some(2222));
+
+        // Test IPv6 entre crochets et forme URL
+        let (username, host, port) =
+            Uploader::parse_server_alias("ops@[2001:db8::10]:2200").unwrap();
+        assert_eq!(username, "ops");
+        assert_eq!(host, "2001:db8::10");
+        assert_eq!(port, Some(2200));
+
+        let (username, host, port) =
+            Uploader::parse_server_alias("ssh://deploy@bastion.example.com:22022").unwrap();
+        assert_eq!(username, "deploy");
+        assert_eq!(host, "bastion.example.com");
+        assert_eq!(port, Some(22022));
     }
 
     #[tokio::test]
@@ -231,21 +249,21 @@ mod uploader_tests {
         // Test avec des caractères spéciaux
         let result = Uploader::parse_server_alias("user-name@server-name.example.com");
         assert!(result.is_ok());
-        let (username, host) = result.unwrap();
+        let (username, host, _) = result.unwrap();
         assert_eq!(username, "user-name");
         assert_eq!(host, "server-name.example.com");
 
         // Test avec des numéros
         let result = Uploader::parse_server_alias("user123@192.168.1.100");
         assert!(result.is_ok());
-        let (username, host) = result.unwrap();
+        let (username, host, _) = result.unwrap();
         assert_eq!(username, "user123");
         assert_eq!(host, "192.168.1.100");
 
         // Test avec sous-domaines
         let result = Uploader::parse_server_alias("admin@app.staging.example.com");
         assert!(result.is_ok());
-        let (username, host) = result.unwrap();
+        let (username, host, _) = result.unwrap();
         assert_eq!(username, "admin");
         assert_eq!(host, "app.staging.example.com");
     }
@@ -297,6 +315,7 @@ mod uploader_tests {
                 HostEntry {
                     alias: format!("user{}@server{}.example.com", i, i),
                     env: "TEST".to_string(),
+                    ..Default::default()
                 },
             ));
         }